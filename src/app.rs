@@ -1,8 +1,21 @@
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{self, BufRead, BufReader, IsTerminal, Read, Write};
+use std::time::Instant;
 
+use super::batch;
+use super::booklet::{self, Booklet};
+use super::error::SudokuError;
+use super::hodoku;
+use super::input::{self, FileFormat, ParsedPuzzle};
+use super::json_format;
+use super::sadman;
+use super::scan::{self, ScanResult};
+use super::sudoku::drawing::{DrawOptions, Drawing};
+use super::sudoku::pencil_marks::PencilMarks;
+use super::sudoku::raster::RasterOptions;
 use super::sudoku::solver::SudokuSolver;
-use super::sudoku::SudokuTable;
+use super::sudoku::tikz;
+use super::sudoku::{CellLocation, ParseMode, SudokuTable};
 
 const PACKAGE_NAME: &str = env!("CARGO_PKG_NAME");
 const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -16,65 +29,525 @@ impl App {
         App { config }
     }
 
-    pub fn run(&self) -> Result<(), String> {
+    pub fn run(&self) -> Result<(), SudokuError> {
         if self.config.print_version {
             println!("{} v{}", PACKAGE_NAME, VERSION);
             return Ok(());
         }
 
-        if let None = self.config.file_name {
-            return Err(String::from("Input file name not specified"));
+        let result = self.solve();
+
+        if let (OutputFormat::Json | OutputFormat::Ndjson, Err(e)) = (self.config.format, &result) {
+            Self::check_output(writeln!(
+                io::stdout().lock(),
+                "{}",
+                json_format::error_to_json(e)
+            ))?;
         }
 
-        let input_reader = Self::open_reader_to_file(self.config.file_name.as_ref().unwrap())?;
-        let input_file = Self::read_input(input_reader);
+        result
+    }
 
-        let input_table = SudokuTable::from_string(input_file?.into_iter())?;
+    fn solve(&self) -> Result<(), SudokuError> {
+        let (input_reader, file_format) = self.open_input_reader()?;
+        self.solve_input(input_reader, file_format)
+    }
 
-        Self::print_solutions(&mut SudokuSolver::new(&input_table));
+    fn solve_input(
+        &self,
+        input_reader: Box<dyn BufRead>,
+        file_format: Option<FileFormat>,
+    ) -> Result<(), SudokuError> {
+        if let OutputFormat::Html | OutputFormat::Pdf = self.config.format {
+            return self.print_booklet(input_reader);
+        }
 
-        Ok(())
+        if self.config.format == OutputFormat::Tex && self.config.batch {
+            return self.print_tikz_grids(input_reader);
+        }
+
+        if self.is_batch() {
+            let options = batch::BatchOptions {
+                mode: self.parse_mode(),
+                format: self.config.format,
+                solution_limit: self.config.solution_limit,
+            };
+            let summary = batch::run(input_reader, options, &mut io::stdout().lock());
+            return Self::check_output(summary);
+        }
+
+        let parsed = self.read_puzzle(input_reader, file_format)?;
+        let input_table = &parsed.table;
+        let mut solver = parsed.solver();
+
+        if self.config.hint {
+            let marks = parsed
+                .pencil_marks
+                .clone()
+                .unwrap_or_else(|| PencilMarks::from_table(input_table));
+
+            return Self::check_output(match marks.hint() {
+                Some(hint) => writeln!(io::stdout().lock(), " => Hint: {}", hint),
+                None => writeln!(
+                    io::stdout().lock(),
+                    " => No hidden or naked single is left in the candidates"
+                ),
+            });
+        }
+
+        match self.config.format {
+            OutputFormat::Text => {
+                Self::check_output(Self::print_solutions(&mut solver, &mut io::stdout().lock()))
+            }
+            OutputFormat::Json => {
+                let start = Instant::now();
+                let limit = self.config.solution_limit;
+                let solutions: Vec<SudokuTable> = solver.by_ref().take(limit).collect();
+                let result = json_format::solve_result_to_json(
+                    input_table,
+                    &solutions,
+                    limit,
+                    solver.stats(),
+                    start.elapsed(),
+                )?;
+
+                Self::check_output(writeln!(io::stdout().lock(), "{}", result))
+            }
+            OutputFormat::Ndjson => Self::check_output(Self::print_solution_lines(
+                &mut solver,
+                &mut io::stdout().lock(),
+            )),
+            OutputFormat::Svg | OutputFormat::Tex => {
+                let solution = match self.config.show_solution {
+                    true => solver.next(),
+                    false => None,
+                };
+                let options = self.draw_options(solution.as_ref(), parsed.pencil_marks.as_ref());
+                let output = match self.config.format {
+                    OutputFormat::Svg => input_table.to_svg(&options),
+                    _ => tikz::document(&input_table.to_tikz(&options)),
+                };
+
+                Self::check_output(write!(io::stdout().lock(), "{}", output))
+            }
+            OutputFormat::Png => {
+                let solution = match self.config.show_solution {
+                    true => solver.next(),
+                    false => None,
+                };
+                let raster_options = RasterOptions {
+                    cell_size: self.config.cell_size,
+                    font_size: self.config.font_size,
+                };
+                let png = input_table.to_png(
+                    &self.draw_options(solution.as_ref(), parsed.pencil_marks.as_ref()),
+                    &raster_options,
+                );
+
+                Self::check_output(io::stdout().lock().write_all(&png))
+            }
+            OutputFormat::PencilMarks => {
+                let marks = match parsed.pencil_marks {
+                    Some(marks) => marks,
+                    None => PencilMarks::from_table(input_table),
+                };
+
+                Self::check_output(write!(io::stdout().lock(), "{}", marks))
+            }
+            OutputFormat::Sdk
+            | OutputFormat::Sdx
+            | OutputFormat::HodokuLibrary
+            | OutputFormat::Hsol => {
+                let progress = parsed.pencil_marks.as_ref();
+                let (name, output) = match self.config.format {
+                    OutputFormat::Sdk => ("sdk", sadman::write_sdk(input_table, progress)),
+                    OutputFormat::Sdx => ("sdx", sadman::write_sdx(input_table, progress)?),
+                    OutputFormat::Hsol => ("hsol", hodoku::write_save_file(input_table, progress)),
+                    _ => ("HoDoKu library", hodoku::write(input_table, progress)),
+                };
+                let metadata = input_table.metadata();
+
+                if !input_table.constraints().is_empty()
+                    || !input_table.extra_houses().is_empty()
+                    || !input_table.has_box_regions()
+                {
+                    eprintln!(
+                        " !=> Warning: {} files hold classic sudoku only, the variant rules are left out",
+                        name
+                    );
+                }
+
+                let is_metadata_lost = match self.config.format {
+                    OutputFormat::HodokuLibrary => !metadata.is_empty(),
+                    OutputFormat::Hsol => false,
+                    _ => metadata.title.is_some(),
+                };
+                if is_metadata_lost {
+                    eprintln!(
+                        " !=> Warning: {} files have no place for the {}, it is left out",
+                        name,
+                        match self.config.format {
+                            OutputFormat::HodokuLibrary => "metadata",
+                            _ => "title",
+                        }
+                    );
+                }
+
+                Self::check_output(write!(io::stdout().lock(), "{}", output))
+            }
+            OutputFormat::Csv => unreachable!("csv output is written in batch mode"),
+            OutputFormat::Html | OutputFormat::Pdf => unreachable!("booklets are written above"),
+        }
     }
 
-    fn open_reader_to_file(path: &str) -> Result<BufReader<File>, String> {
+    fn draw_options<'a>(
+        &'a self,
+        solution: Option<&'a SudokuTable>,
+        progress: Option<&'a PencilMarks>,
+    ) -> DrawOptions<'a> {
+        DrawOptions {
+            solution,
+            pencil_marks: self.config.pencil_marks,
+            progress,
+            highlighted: &self.config.highlighted,
+        }
+    }
+
+    fn print_tikz_grids(&self, input_reader: impl BufRead) -> Result<(), SudokuError> {
+        let booklet = Booklet::read(
+            input_reader,
+            self.parse_mode(),
+            booklet::DEFAULT_PUZZLES_PER_PAGE,
+        )?;
+
+        for warning in &booklet.warnings {
+            eprintln!(" !=> Warning: {}", warning);
+        }
+
+        let drawings: Vec<Drawing> = booklet
+            .puzzles
+            .iter()
+            .map(|x| {
+                let solution = x.solution.as_ref().filter(|_| self.config.show_solution);
+                Drawing::new(&x.table, &self.draw_options(solution, None))
+            })
+            .collect();
+        let picture = tikz::write(&drawings, tikz::CELL_SIZE);
+
+        Self::check_output(write!(io::stdout().lock(), "{}", tikz::document(&picture)))
+    }
+
+    fn print_booklet(&self, input_reader: impl BufRead) -> Result<(), SudokuError> {
+        let booklet = Booklet::read(input_reader, self.parse_mode(), self.config.per_page)?;
+
+        for warning in &booklet.warnings {
+            eprintln!(" !=> Warning: {}", warning);
+        }
+
+        let mut out = io::stdout().lock();
+        let result = match self.config.format {
+            OutputFormat::Pdf => out.write_all(&booklet::pdf::write(&booklet)),
+            _ => out.write_all(booklet::html::write(&booklet).as_bytes()),
+        };
+
+        Self::check_output(result.and_then(|_| out.flush()))
+    }
+
+    fn is_batch(&self) -> bool {
+        match self.config.format {
+            // A CSV file holds a row per puzzle, so a single puzzle is read as a batch of one.
+            OutputFormat::Csv => true,
+            OutputFormat::Svg
+            | OutputFormat::Png
+            | OutputFormat::Tex
+            | OutputFormat::Html
+            | OutputFormat::Pdf
+            | OutputFormat::PencilMarks
+            | OutputFormat::Sdk
+            | OutputFormat::Sdx
+            | OutputFormat::HodokuLibrary
+            | OutputFormat::Hsol => false,
+            _ => self.config.batch,
+        }
+    }
+
+    fn check_output<T>(result: io::Result<T>) -> Result<(), SudokuError> {
+        match result {
+            Ok(_) => Ok(()),
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+            Err(e) => Err(SudokuError::Io(e)),
+        }
+    }
+
+    fn open_input_reader(&self) -> Result<(Box<dyn BufRead>, Option<FileFormat>), SudokuError> {
+        match self.config.file_name.as_deref() {
+            Some("-") => Ok((Box::new(io::stdin().lock()), None)),
+            Some(path) => {
+                let (reader, format) = Self::open_reader_to_file(path)?;
+                Ok((Box::new(reader), format))
+            }
+            None if !io::stdin().is_terminal() => Ok((Box::new(io::stdin().lock()), None)),
+            None => Err(SudokuError::NoInput),
+        }
+    }
+
+    /// Also returns the format of another sudoku program that the file extension names. Without
+    /// one, the format is told from the content when the puzzle is read.
+    fn open_reader_to_file(
+        path: &str,
+    ) -> Result<(BufReader<File>, Option<FileFormat>), SudokuError> {
         let input_file = match File::open(path) {
             Ok(x) => x,
             Err(e) => {
-                return Err(format!("Cannot open {}: {}", path, e.to_string()));
+                let message = format!("Cannot open {}: {}", path, e);
+                return Err(SudokuError::Io(io::Error::new(e.kind(), message)));
             }
         };
 
-        Ok(BufReader::new(input_file))
+        Ok((BufReader::new(input_file), FileFormat::from_path(path)))
     }
 
-    fn read_input(reader: impl BufRead) -> Result<Vec<String>, String> {
-        reader
-            .lines()
-            .map(|x| match x {
-                Ok(x) => Ok(x),
-                Err(e) => Err(format!("Error reading file: {}", e)),
-            })
-            .take(10)
-            .collect()
+    fn parse_mode(&self) -> ParseMode {
+        match self.config.strict {
+            true => ParseMode::Strict,
+            false => ParseMode::Lenient,
+        }
     }
 
-    fn print_solutions(solver: &mut SudokuSolver) {
+    fn read_puzzle(
+        &self,
+        mut input_reader: Box<dyn BufRead>,
+        file_format: Option<FileFormat>,
+    ) -> Result<ParsedPuzzle, SudokuError> {
+        let parsed = match scan::is_image(input_reader.fill_buf()?) {
+            true => {
+                let mut data = vec![];
+                input_reader.read_to_end(&mut data)?;
+
+                ParsedPuzzle {
+                    table: Self::scan_table(&data)?,
+                    pencil_marks: None,
+                    warnings: vec![],
+                }
+            }
+            false => {
+                let input_file = input_reader.lines().collect::<io::Result<Vec<String>>>()?;
+
+                match file_format {
+                    Some(format) => input::read_file(&input_file, format)?,
+                    None => input::read_puzzle(input_file, self.parse_mode())?,
+                }
+            }
+        };
+
+        for warning in &parsed.warnings {
+            eprintln!(" !=> Warning: {}", warning);
+        }
+
+        if let Err(e) = parsed.check() {
+            if self.config.show_conflicts {
+                Self::print_conflicting_cells(parsed.current_table(), &e);
+            }
+
+            return Err(e);
+        }
+
+        Ok(parsed)
+    }
+
+    fn scan_table(data: &[u8]) -> Result<SudokuTable, SudokuError> {
+        let scan = ScanResult::from_image(data)?;
+
+        eprintln!(
+            " => Scanned grid, with the confidence of each cell in percent:\n{}",
+            scan
+        );
+
+        for (cell, result) in scan.uncertain_cells() {
+            let digit = result
+                .digit
+                .map_or(String::from("empty"), |x| x.to_string());
+            eprintln!(
+                " !=> Warning: {} was read as {} with {}% confidence",
+                cell,
+                digit,
+                (result.confidence * 100.0).round()
+            );
+        }
+
+        Ok(scan.table)
+    }
+
+    fn print_conflicting_cells(table: &SudokuTable, error: &SudokuError) {
+        let cells = match error {
+            SudokuError::RuleConflict { cells, .. } => cells.clone(),
+            SudokuError::Contradiction { contradictions } => contradictions
+                .iter()
+                .flat_map(|x| x.cells().to_vec())
+                .collect(),
+            _ => return,
+        };
+
+        eprintln!(" !=> Conflicting cells:\n{}", table.display_marked(&cells));
+    }
+
+    fn print_solutions(solver: &mut SudokuSolver, out: &mut impl Write) -> io::Result<()> {
         for (i, solution) in solver.enumerate() {
-            println!(" => Solution {}:\n{}", i + 1, solution);
+            writeln!(out, " => Solution {}:\n{}", i + 1, solution)?;
+        }
+
+        Ok(())
+    }
+
+    fn print_solution_lines(solver: &mut SudokuSolver, out: &mut impl Write) -> io::Result<()> {
+        let start = Instant::now();
+        let mut count = 0;
+
+        for solution in solver.by_ref() {
+            count += 1;
+
+            let line = json_format::solution_to_json(count, &solution, start.elapsed());
+            writeln!(out, "{}", line)?;
+            out.flush()?;
+        }
+
+        let summary = json_format::stream_summary_to_json(count, solver.stats(), start.elapsed());
+        writeln!(out, "{}", summary)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+    /// One JSON object per line, written as soon as it is known.
+    Ndjson,
+    /// One row per puzzle, as in batch mode.
+    Csv,
+    /// A drawing of a single puzzle.
+    Svg,
+    Png,
+    /// A standalone LaTeX document, drawing the puzzle, or every puzzle of a batch side by side.
+    Tex,
+    /// A printable booklet of every puzzle in the input, with an answer key.
+    Html,
+    Pdf,
+    /// The candidates left in each cell, in the layout of Sudoku Explainer and HoDoKu.
+    PencilMarks,
+    /// The files of SadMan Sudoku and HoDoKu, and a HoDoKu library line, keeping the player's
+    /// progress.
+    Sdk,
+    Sdx,
+    HodokuLibrary,
+    Hsol,
+}
+
+impl OutputFormat {
+    pub fn from_name(name: &str) -> Option<OutputFormat> {
+        match name {
+            "text" => Some(Self::Text),
+            "json" => Some(Self::Json),
+            "ndjson" => Some(Self::Ndjson),
+            "csv" => Some(Self::Csv),
+            "svg" => Some(Self::Svg),
+            "png" => Some(Self::Png),
+            "tex" => Some(Self::Tex),
+            "html" => Some(Self::Html),
+            "pdf" => Some(Self::Pdf),
+            "pencil-marks" => Some(Self::PencilMarks),
+            "sdk" => Some(Self::Sdk),
+            "sdx" => Some(Self::Sdx),
+            "hodoku-library" => Some(Self::HodokuLibrary),
+            "hsol" => Some(Self::Hsol),
+            _ => None,
         }
     }
 }
 
 pub struct AppConfig {
-    file_name: Option<String>,
-    print_version: bool,
+    pub file_name: Option<String>,
+    pub print_version: bool,
+    pub strict: bool,
+    pub batch: bool,
+    pub show_conflicts: bool,
+    pub format: OutputFormat,
+    pub solution_limit: usize,
+    pub show_solution: bool,
+    pub pencil_marks: bool,
+    pub per_page: usize,
+    pub highlighted: Vec<CellLocation>,
+    pub cell_size: f64,
+    pub font_size: Option<f64>,
+    pub hint: bool,
 }
 
 impl AppConfig {
+    /// A config that reads `file_name`, with the defaults for every other option.
     pub fn new(file_name: Option<String>, print_version: bool) -> AppConfig {
         AppConfig {
             file_name,
             print_version,
+            ..AppConfig::default()
         }
     }
 }
+
+impl Default for AppConfig {
+    fn default() -> AppConfig {
+        AppConfig {
+            file_name: None,
+            print_version: false,
+            strict: false,
+            batch: false,
+            show_conflicts: false,
+            format: OutputFormat::Text,
+            solution_limit: batch::DEFAULT_SOLUTION_LIMIT,
+            show_solution: false,
+            pencil_marks: false,
+            per_page: booklet::DEFAULT_PUZZLES_PER_PAGE,
+            highlighted: vec![],
+            cell_size: RasterOptions::default().cell_size,
+            font_size: None,
+            hint: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufRead;
+
+    use super::super::error::SudokuError;
+    use super::{App, AppConfig};
+
+    const PUZZLE: &str =
+        "XX1XXXXX2XXXX34XXXX5XXX1XX6X2X6XXXX3X3XXXXX5X7XXXX8X9X9XX4XXX3XXXX71XXXX8XXXXX4XX\n";
+
+    fn reader(text: &'static str) -> Box<dyn BufRead> {
+        Box::new(text.as_bytes())
+    }
+
+    #[test]
+    fn standard_input() {
+        let app = App::new(AppConfig::new(Some(String::from("-")), false));
+        let (_, file_format) = app.open_input_reader().unwrap();
+        assert!(file_format.is_none());
+
+        // What is read from `-` goes through the same paths as a file.
+        assert!(app.solve_input(reader(PUZZLE), None).is_ok());
+        assert!(matches!(
+            app.solve_input(reader("1 2\n"), None),
+            Err(SudokuError::WrongLineLength { line: 1, .. })
+        ));
+
+        let batch = App::new(AppConfig {
+            batch: true,
+            ..AppConfig::new(Some(String::from("-")), false)
+        });
+        let puzzles =
+            "XX1XXXXX2XXXX34XXXX5XXX1XX6X2X6XXXX3X3XXXXX5X7XXXX8X9X9XX4XXX3XXXX71XXXX8XXXXX4XX\n\
+            \n\
+            1 2\n";
+        assert!(batch.solve_input(reader(puzzles), None).is_ok());
+    }
+}
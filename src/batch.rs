@@ -0,0 +1,506 @@
+use std::fmt::Display;
+use std::io::{self, BufRead, Lines, Write};
+use std::time::{Duration, Instant};
+
+use super::app::OutputFormat;
+use super::csv;
+use super::error::SudokuError;
+use super::input::{self, ParsedPuzzle};
+use super::json::JsonValue;
+use super::json_format;
+use super::sudoku::lenient;
+use super::sudoku::solver::SolverStats;
+use super::sudoku::{ParseMode, SudokuTable};
+
+const MAX_PUZZLE_LINES: usize = 64;
+pub const DEFAULT_SOLUTION_LIMIT: usize = 2;
+
+pub struct Puzzle {
+    pub index: usize,
+    pub line_number: usize,
+    /// The lines of the block, only the first `MAX_PUZZLE_LINES` of them when it is longer.
+    pub lines: Vec<String>,
+    pub is_too_long: bool,
+}
+
+pub struct PuzzleStream<R: BufRead> {
+    lines: Lines<R>,
+    line_number: usize,
+    puzzle_count: usize,
+}
+
+impl<R: BufRead> PuzzleStream<R> {
+    pub fn new(reader: R) -> PuzzleStream<R> {
+        PuzzleStream {
+            lines: reader.lines(),
+            line_number: 0,
+            puzzle_count: 0,
+        }
+    }
+
+    /// A JSON puzzle on one line is a whole puzzle. Pretty-printed over several lines, it is read
+    /// as a block like the others.
+    fn is_single_line_puzzle(line: &str) -> bool {
+        let is_json = line.trim_start().starts_with('{') && JsonValue::parse(line).is_ok();
+        let is_grid = !line.contains(':') && lenient::strip_separators(line).chars().count() >= 81;
+
+        is_json || is_grid
+    }
+
+    fn make_puzzle(&mut self, line_number: usize, lines: Vec<String>, is_too_long: bool) -> Puzzle {
+        self.puzzle_count += 1;
+
+        Puzzle {
+            index: self.puzzle_count,
+            line_number,
+            lines,
+            is_too_long,
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for PuzzleStream<R> {
+    type Item = io::Result<Puzzle>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut lines: Vec<String> = vec![];
+        let mut first_line_number = 0;
+        let mut is_too_long = false;
+
+        for line in self.lines.by_ref() {
+            self.line_number += 1;
+
+            let line = match line {
+                Ok(x) => x,
+                Err(e) => return Some(Err(e)),
+            };
+
+            if line.trim().is_empty() {
+                if lines.is_empty() {
+                    continue;
+                }
+                break;
+            }
+
+            if lines.is_empty() {
+                first_line_number = self.line_number;
+
+                if Self::is_single_line_puzzle(&line) {
+                    lines.push(line);
+                    break;
+                }
+            }
+
+            // The rest of an overlong block is skipped, up to the next blank line.
+            match lines.len() < MAX_PUZZLE_LINES {
+                true => lines.push(line),
+                false => is_too_long = true,
+            }
+        }
+
+        match lines.is_empty() {
+            true => None,
+            false => Some(Ok(self.make_puzzle(first_line_number, lines, is_too_long))),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum PuzzleStatus {
+    Unique,
+    Multiple,
+    Unsolvable,
+    Invalid(SudokuError),
+}
+
+impl PuzzleStatus {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Unique => "unique",
+            Self::Multiple => "multiple",
+            Self::Unsolvable => "unsolvable",
+            Self::Invalid(_) => "invalid",
+        }
+    }
+}
+
+impl Display for PuzzleStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unique => write!(f, "solved, unique solution"),
+            Self::Multiple => write!(f, "solved, multiple solutions"),
+            Self::Unsolvable => write!(f, "no solution"),
+            Self::Invalid(reason) => write!(f, "invalid: {}", reason),
+        }
+    }
+}
+
+pub struct PuzzleReport {
+    pub index: usize,
+    pub line_number: usize,
+    /// The puzzle as a single line, or its raw lines when it cannot be read.
+    pub puzzle: String,
+    /// The puzzle, once it has been read and checked.
+    pub table: Option<SudokuTable>,
+    pub difficulty: Option<String>,
+    pub status: PuzzleStatus,
+    /// The number of solutions found, up to the solution limit.
+    pub solution_count: usize,
+    pub solution: Option<SudokuTable>,
+    pub stats: SolverStats,
+    pub elapsed: Duration,
+}
+
+impl PuzzleReport {
+    pub fn solve(puzzle: Puzzle, mode: ParseMode, solution_limit: usize) -> PuzzleReport {
+        let start = Instant::now();
+        let mut report = PuzzleReport {
+            index: puzzle.index,
+            line_number: puzzle.line_number,
+            puzzle: puzzle.lines.join(" "),
+            table: None,
+            difficulty: None,
+            status: PuzzleStatus::Unsolvable,
+            solution_count: 0,
+            solution: None,
+            stats: SolverStats::default(),
+            elapsed: Duration::ZERO,
+        };
+
+        if puzzle.is_too_long {
+            report.status = PuzzleStatus::Invalid(SudokuError::BlockTooLong {
+                limit: MAX_PUZZLE_LINES,
+            });
+        } else {
+            report.solve_lines(puzzle.lines, mode, solution_limit);
+        }
+
+        report.elapsed = start.elapsed();
+        report
+    }
+
+    fn solve_lines(&mut self, lines: Vec<String>, mode: ParseMode, solution_limit: usize) {
+        match self.read_table(lines, mode) {
+            Ok(parsed) => {
+                let mut solver = parsed.solver();
+
+                for solution in solver.by_ref().take(solution_limit) {
+                    self.solution_count += 1;
+                    self.solution.get_or_insert(solution);
+                }
+
+                self.status = match self.solution_count {
+                    0 => PuzzleStatus::Unsolvable,
+                    1 => PuzzleStatus::Unique,
+                    _ => PuzzleStatus::Multiple,
+                };
+                self.stats = solver.stats();
+                self.table = Some(parsed.table);
+            }
+            Err(e) => self.status = PuzzleStatus::Invalid(e),
+        }
+    }
+
+    fn read_table(
+        &mut self,
+        lines: Vec<String>,
+        mode: ParseMode,
+    ) -> Result<ParsedPuzzle, SudokuError> {
+        let parsed = input::read_puzzle(lines, mode)?;
+        self.puzzle = parsed.table.to_single_line();
+        self.difficulty = parsed.table.metadata().difficulty.clone();
+
+        parsed.check()?;
+
+        Ok(parsed)
+    }
+}
+
+impl Display for PuzzleReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            " => Puzzle {} (line {}): {} ({})",
+            self.index,
+            self.line_number,
+            self.status,
+            format_duration(self.elapsed)
+        )?;
+
+        if let Some(solution) = &self.solution {
+            write!(f, "\n\t{}", solution.to_single_line())?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+pub struct BatchSummary {
+    pub unique: usize,
+    pub multiple: usize,
+    pub unsolvable: usize,
+    pub invalid: usize,
+    pub elapsed: Duration,
+}
+
+impl BatchSummary {
+    pub fn add(&mut self, report: &PuzzleReport) {
+        match report.status {
+            PuzzleStatus::Unique => self.unique += 1,
+            PuzzleStatus::Multiple => self.multiple += 1,
+            PuzzleStatus::Unsolvable => self.unsolvable += 1,
+            PuzzleStatus::Invalid(_) => self.invalid += 1,
+        }
+
+        self.elapsed += report.elapsed;
+    }
+
+    pub fn total(&self) -> usize {
+        self.unique + self.multiple + self.unsolvable + self.invalid
+    }
+}
+
+impl Display for BatchSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            " => Summary: {} puzzles ({})",
+            self.total(),
+            format_duration(self.elapsed)
+        )?;
+        writeln!(f, "\t{} solved with a unique solution", self.unique)?;
+        writeln!(f, "\t{} solved with multiple solutions", self.multiple)?;
+        writeln!(f, "\t{} without solution", self.unsolvable)?;
+        write!(f, "\t{} invalid", self.invalid)
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    format!("{:.3} ms", duration.as_secs_f64() * 1000.0)
+}
+
+#[derive(Clone, Copy)]
+pub struct BatchOptions {
+    pub mode: ParseMode,
+    pub format: OutputFormat,
+    /// Solutions are counted up to this number, which should be at least 2.
+    pub solution_limit: usize,
+}
+
+pub fn run(
+    reader: impl BufRead,
+    options: BatchOptions,
+    out: &mut impl Write,
+) -> io::Result<BatchSummary> {
+    let mut summary = BatchSummary::default();
+
+    if options.format == OutputFormat::Csv {
+        writeln!(out, "{}", csv::HEADER)?;
+    }
+
+    for puzzle in PuzzleStream::new(reader) {
+        let report = PuzzleReport::solve(puzzle?, options.mode, options.solution_limit);
+        summary.add(&report);
+
+        match options.format {
+            OutputFormat::Text => writeln!(out, "{}", report)?,
+            OutputFormat::Json | OutputFormat::Ndjson => {
+                writeln!(out, "{}", json_format::report_to_json(&report))?
+            }
+            OutputFormat::Csv => writeln!(out, "{}", csv::report_to_row(&report))?,
+            OutputFormat::Svg
+            | OutputFormat::Png
+            | OutputFormat::Tex
+            | OutputFormat::Html
+            | OutputFormat::Pdf
+            | OutputFormat::PencilMarks
+            | OutputFormat::Sdk
+            | OutputFormat::Sdx
+            | OutputFormat::HodokuLibrary
+            | OutputFormat::Hsol => {
+                unreachable!("drawings and saved grids are not written in batch mode")
+            }
+        }
+    }
+
+    match options.format {
+        OutputFormat::Text => writeln!(out, "{}", summary)?,
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            writeln!(out, "{}", json_format::summary_to_json(&summary))?
+        }
+        OutputFormat::Csv
+        | OutputFormat::Svg
+        | OutputFormat::Png
+        | OutputFormat::Tex
+        | OutputFormat::Html
+        | OutputFormat::Pdf
+        | OutputFormat::PencilMarks
+        | OutputFormat::Sdk
+        | OutputFormat::Sdx
+        | OutputFormat::HodokuLibrary
+        | OutputFormat::Hsol => (),
+    }
+
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::app::OutputFormat;
+    use super::super::error::SudokuError;
+    use super::super::sudoku::ParseMode;
+    use super::{
+        run, BatchOptions, PuzzleReport, PuzzleStatus, PuzzleStream, DEFAULT_SOLUTION_LIMIT,
+    };
+
+    #[test]
+    fn mixed_batch() {
+        let input =
+            "XX1XXXXX2XXXX34XXXX5XXX1XX6X2X6XXXX3X3XXXXX5X7XXXX8X9X9XX4XXX3XXXX71XXXX8XXXXX4XX\n\
+        \n\
+        XX1XXXXX2\n\
+        XXXX34XXX\n\
+        X5XXX1XX6\n\
+        X2X6XXXX3\n\
+        X3XXXXX5X\n\
+        7XXXX8X9X\n\
+        9XX4XXX3X\n\
+        XXXXXXXXX\n\
+        XXXXXXXXX\n\
+        \n\
+        11XXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXX\n";
+
+        let mut out = vec![];
+        let options = BatchOptions {
+            mode: ParseMode::Lenient,
+            format: OutputFormat::Text,
+            solution_limit: DEFAULT_SOLUTION_LIMIT,
+        };
+        let summary = run(input.as_bytes(), options, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+
+        assert_eq!(
+            (summary.unique, summary.multiple, summary.invalid),
+            (1, 1, 1)
+        );
+        assert!(out.contains(" => Puzzle 2 (line 3): solved, multiple solutions"));
+        assert!(out.contains(" => Puzzle 3 (line 13): invalid: Invalid input: illegal table"));
+    }
+
+    #[test]
+    fn conflicting_player_values() {
+        let grid = "..1.....2\n....34...\n.5...1..6\n.2.6....3\n.3.....5.\n\
+            7....8.9.\n9..4...3.\n...71....\n8.....4..\n";
+        let input = format!(
+            "[Puzzle]\n{}[State]\n1{}\n[Puzzle]\n{}[State]\n3{}",
+            grid,
+            &grid[1..],
+            grid,
+            &grid[1..]
+        );
+
+        let mut out = vec![];
+        let options = BatchOptions {
+            mode: ParseMode::Lenient,
+            format: OutputFormat::Text,
+            solution_limit: DEFAULT_SOLUTION_LIMIT,
+        };
+        let summary = run(input.as_bytes(), options, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+
+        assert_eq!((summary.unique, summary.invalid), (1, 1));
+        assert!(out.contains(" => Puzzle 1 (line 1): invalid: Invalid input: illegal table"));
+    }
+
+    #[test]
+    fn json_puzzles() {
+        let grid =
+            "..1.....2....34....5...1..6.2.6....3.3.....5.7....8.9.9..4...3....71....8.....4..";
+        let input = format!(
+            "{{\"grid\": \"{}\"}}\n\
+            {{\n  \"title\": \"Pretty\",\n  \"grid\": \"{}\"\n}}\n\
+            \n\
+            {}\n",
+            grid, grid, grid
+        );
+
+        let mut out = vec![];
+        let options = BatchOptions {
+            mode: ParseMode::Lenient,
+            format: OutputFormat::Text,
+            solution_limit: DEFAULT_SOLUTION_LIMIT,
+        };
+        let summary = run(input.as_bytes(), options, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+
+        assert_eq!((summary.unique, summary.invalid), (3, 0));
+        assert!(out.contains(" => Puzzle 2 (line 2): solved"));
+        assert!(out.contains(" => Puzzle 3 (line 7): solved"));
+    }
+
+    #[test]
+    fn overlong_block() {
+        let input = format!(
+            "{}\n\
+            XX1XXXXX2XXXX34XXXX5XXX1XX6X2X6XXXX3X3XXXXX5X7XXXX8X9X9XX4XXX3XXXX71XXXX8XXXXX4XX\n",
+            "comment: filler\n".repeat(70)
+        );
+
+        let mut out = vec![];
+        let options = BatchOptions {
+            mode: ParseMode::Lenient,
+            format: OutputFormat::Text,
+            solution_limit: DEFAULT_SOLUTION_LIMIT,
+        };
+        let summary = run(input.as_bytes(), options, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+
+        assert_eq!((summary.unique, summary.invalid), (1, 1));
+        assert!(out.contains(" => Puzzle 1 (line 1): invalid: Invalid input: block longer than 64"));
+
+        let puzzle = PuzzleStream::new(input.as_bytes()).next().unwrap().unwrap();
+        let report = PuzzleReport::solve(puzzle, ParseMode::Lenient, DEFAULT_SOLUTION_LIMIT);
+        assert!(matches!(
+            report.status,
+            PuzzleStatus::Invalid(SudokuError::BlockTooLong { limit: 64 })
+        ));
+        assert!(out.contains(" => Puzzle 2 (line 72): solved"));
+    }
+
+    #[test]
+    fn csv_rows() {
+        let input = "difficulty: easy\n\
+        XX1XXXXX2XXXX34XXXX5XXX1XX6X2X6XXXX3X3XXXXX5X7XXXX8X9X9XX4XXX3XXXX71XXXX8XXXXX4XX\n\
+        \n\
+        XX1XXXXX2XXXX34XXXX5XXX1XX6X2X6XXXX3X3XXXXX5X7XXXX8X9X9XX4XXX3XXXXXXXXXXXXXXXXXXX\n\
+        \n\
+        XX1XXXXX2\n";
+
+        let mut out = vec![];
+        let options = BatchOptions {
+            mode: ParseMode::Lenient,
+            format: OutputFormat::Csv,
+            solution_limit: 5,
+        };
+        run(input.as_bytes(), options, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let rows: Vec<Vec<&str>> = out.lines().map(|x| x.split(',').collect()).collect();
+
+        assert_eq!(rows.len(), 4);
+        assert_eq!(rows[0][0], "puzzle");
+        assert_eq!(
+            (rows[1][1], rows[1][2], rows[1][7]),
+            ("unique", "1", "easy")
+        );
+        assert_eq!(
+            rows[1][3],
+            "391867542286534719457291386129645873638179254745328691972486135564713928813952467"
+        );
+        assert_eq!((rows[2][1], rows[2][2]), ("multiple", "5"));
+        assert_eq!(
+            (rows[3][0], rows[3][1], rows[3][2]),
+            ("XX1XXXXX2", "invalid", "0")
+        );
+    }
+}
@@ -0,0 +1,175 @@
+use std::io::{self, BufRead};
+
+use super::batch::{PuzzleReport, PuzzleStatus, PuzzleStream, DEFAULT_SOLUTION_LIMIT};
+use super::sudoku::drawing::{DrawOptions, Drawing};
+use super::sudoku::{ParseMode, SudokuTable};
+
+pub mod html;
+pub mod pdf;
+
+pub const DEFAULT_PUZZLES_PER_PAGE: usize = 4;
+const ANSWER_COLUMNS: usize = 3;
+const ANSWER_ROWS: usize = 4;
+
+pub struct BookletPuzzle {
+    pub number: usize,
+    pub table: SudokuTable,
+    pub solution: Option<SudokuTable>,
+}
+
+/// A set of puzzles laid out for printing, followed by their answer key.
+pub struct Booklet {
+    pub puzzles: Vec<BookletPuzzle>,
+    pub per_page: usize,
+    /// Puzzles that were skipped, or whose answer may not be the intended one.
+    pub warnings: Vec<String>,
+}
+
+pub struct Page {
+    pub number: usize,
+    pub heading: Option<String>,
+    pub columns: usize,
+    pub rows: usize,
+    pub is_answer_key: bool,
+    pub items: Vec<PageItem>,
+}
+
+pub struct PageItem {
+    pub label: String,
+    pub caption: Option<String>,
+    pub drawing: Drawing,
+}
+
+impl Booklet {
+    pub fn read(reader: impl BufRead, mode: ParseMode, per_page: usize) -> io::Result<Booklet> {
+        let mut booklet = Booklet {
+            puzzles: vec![],
+            per_page: per_page.max(1),
+            warnings: vec![],
+        };
+
+        for puzzle in PuzzleStream::new(reader) {
+            let report = PuzzleReport::solve(puzzle?, mode, DEFAULT_SOLUTION_LIMIT);
+            let name = format!("puzzle {} (line {})", report.index, report.line_number);
+
+            match (&report.status, report.table) {
+                (PuzzleStatus::Unique, Some(table)) => booklet.add(table, report.solution),
+                (PuzzleStatus::Multiple, Some(table)) => {
+                    booklet.warnings.push(format!(
+                        "{} has more than one solution, the answer key shows one of them",
+                        name
+                    ));
+                    booklet.add(table, report.solution);
+                }
+                (status, _) => booklet
+                    .warnings
+                    .push(format!("skipping {}: {}", name, status)),
+            }
+        }
+
+        Ok(booklet)
+    }
+
+    fn add(&mut self, table: SudokuTable, solution: Option<SudokuTable>) {
+        self.puzzles.push(BookletPuzzle {
+            number: self.puzzles.len() + 1,
+            table,
+            solution,
+        });
+    }
+
+    pub fn pages(&self) -> Vec<Page> {
+        let columns = match self.per_page {
+            1 => 1,
+            _ => 2,
+        };
+        let mut pages = vec![];
+
+        for chunk in self.puzzles.chunks(self.per_page) {
+            pages.push(Page {
+                number: pages.len() + 1,
+                heading: None,
+                columns,
+                rows: self.per_page.div_ceil(columns),
+                is_answer_key: false,
+                items: chunk.iter().map(Self::puzzle_item).collect(),
+            });
+        }
+
+        for (i, chunk) in self
+            .puzzles
+            .chunks(ANSWER_COLUMNS * ANSWER_ROWS)
+            .enumerate()
+        {
+            pages.push(Page {
+                number: pages.len() + 1,
+                heading: (i == 0).then(|| String::from("Answers")),
+                columns: ANSWER_COLUMNS,
+                rows: ANSWER_ROWS,
+                is_answer_key: true,
+                items: chunk.iter().map(Self::answer_item).collect(),
+            });
+        }
+
+        pages
+    }
+
+    fn puzzle_item(puzzle: &BookletPuzzle) -> PageItem {
+        let metadata = puzzle.table.metadata();
+        let label = match &metadata.title {
+            Some(title) => format!("Puzzle {}: {}", puzzle.number, title),
+            None => format!("Puzzle {}", puzzle.number),
+        };
+
+        PageItem {
+            label,
+            caption: metadata.difficulty.clone(),
+            drawing: Drawing::new(&puzzle.table, &DrawOptions::default()),
+        }
+    }
+
+    fn answer_item(puzzle: &BookletPuzzle) -> PageItem {
+        let options = DrawOptions {
+            solution: puzzle.solution.as_ref(),
+            ..Default::default()
+        };
+
+        PageItem {
+            label: puzzle.number.to_string(),
+            caption: None,
+            drawing: Drawing::new(&puzzle.table, &options),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::sudoku::ParseMode;
+    use super::Booklet;
+
+    pub const PUZZLES: &str = "title: First\n\
+    difficulty: easy\n\
+    XX1XXXXX2XXXX34XXXX5XXX1XX6X2X6XXXX3X3XXXXX5X7XXXX8X9X9XX4XXX3XXXX71XXXX8XXXXX4XX\n\
+    \n\
+    XX1XXXXX2XXXX34XXXX5XXX1XX6X2X6XXXX3X3XXXXX5X7XXXX8X9X9XX4XXX3XXXX71XXXX8XXXXX4XX\n\
+    55XXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXX\n\
+    XX1XXXXX2XXXX34XXXX5XXX1XX6X2X6XXXX3X3XXXXX5X7XXXX8X9X9XX4XXX3XXXX71XXXX8XXXXX4XX\n";
+
+    #[test]
+    fn pages_and_answer_key() {
+        let booklet = Booklet::read(PUZZLES.as_bytes(), ParseMode::Lenient, 2).unwrap();
+        let pages = booklet.pages();
+
+        assert_eq!(booklet.puzzles.len(), 3);
+        assert_eq!(booklet.warnings.len(), 1);
+        assert!(booklet.warnings[0].starts_with("skipping puzzle 3 (line 6): invalid"));
+
+        assert_eq!(pages.len(), 3);
+        assert_eq!(pages[0].items[0].label, "Puzzle 1: First");
+        assert_eq!(pages[0].items[0].caption.as_deref(), Some("easy"));
+        assert_eq!(pages[1].items.len(), 1);
+        assert_eq!(pages[1].items[0].label, "Puzzle 3");
+        assert_eq!(pages[2].heading.as_deref(), Some("Answers"));
+        assert_eq!(pages[2].items.len(), 3);
+    }
+}
@@ -0,0 +1,94 @@
+use std::fmt::Write;
+
+use super::super::sudoku::drawing::escape_xml;
+use super::super::sudoku::svg;
+use super::{Booklet, Page};
+
+const STYLE: &str = "\
+@page { size: A4; margin: 12mm; }
+body { margin: 0; font-family: sans-serif; color: black; }
+.page { display: grid; gap: 6mm; align-content: start; padding: 12mm; break-after: page; }
+.page h1 { grid-column: 1 / -1; margin: 0; font-size: 18pt; }
+.page footer { grid-column: 1 / -1; text-align: center; font-size: 9pt; }
+figure { margin: 0; }
+figcaption { display: flex; justify-content: space-between; margin-bottom: 2mm; font-weight: bold; }
+figcaption .difficulty { font-weight: normal; font-style: italic; }
+figure svg { display: block; width: 100%; height: auto; }
+.answers { gap: 4mm; }
+.answers figcaption { margin-bottom: 1mm; font-size: 8pt; }
+@media print { .page { padding: 0; } }
+";
+
+/// Writes the booklet as a single HTML file, with its grids as inline SVG.
+pub fn write(booklet: &Booklet) -> String {
+    let mut html = String::new();
+
+    html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str("<title>Sudoku booklet</title>\n");
+    let _ = write!(html, "<style>\n{}</style>\n", STYLE);
+    html.push_str("</head>\n<body>\n");
+
+    for page in booklet.pages() {
+        write_page(&mut html, &page);
+    }
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+fn write_page(html: &mut String, page: &Page) {
+    let class = match page.is_answer_key {
+        true => "page answers",
+        false => "page",
+    };
+
+    let _ = writeln!(
+        html,
+        "<section class=\"{}\" style=\"grid-template-columns: repeat({}, 1fr)\">",
+        class, page.columns
+    );
+
+    if let Some(heading) = &page.heading {
+        let _ = writeln!(html, "<h1>{}</h1>", escape_xml(heading));
+    }
+
+    for item in &page.items {
+        html.push_str("<figure>\n<figcaption>");
+        let _ = write!(html, "<span>{}</span>", escape_xml(&item.label));
+
+        if let Some(caption) = &item.caption {
+            let _ = write!(
+                html,
+                "<span class=\"difficulty\">{}</span>",
+                escape_xml(caption)
+            );
+        }
+
+        html.push_str("</figcaption>\n");
+        html.push_str(&svg::write(&item.drawing, svg::CELL_SIZE));
+        html.push_str("</figure>\n");
+    }
+
+    let _ = writeln!(html, "<footer>{}</footer>\n</section>", page.number);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::sudoku::ParseMode;
+    use super::super::tests::PUZZLES;
+    use super::super::Booklet;
+    use super::write;
+
+    #[test]
+    fn self_contained_pages() {
+        let booklet = Booklet::read(PUZZLES.as_bytes(), ParseMode::Lenient, 4).unwrap();
+        let html = write(&booklet);
+
+        assert_eq!(html.matches("<section class=\"page\"").count(), 1);
+        assert_eq!(html.matches("<section class=\"page answers\"").count(), 1);
+        assert_eq!(html.matches("<svg ").count(), 6);
+        assert!(html.contains("<span>Puzzle 1: First</span><span class=\"difficulty\">easy</span>"));
+        assert!(html.contains("<h1>Answers</h1>"));
+        assert!(!html.contains("<link") && !html.contains("<script"));
+    }
+}
@@ -0,0 +1,426 @@
+use std::fmt::Write;
+
+use super::super::sudoku::drawing::{number, Color, Drawing, Point, Shape};
+use super::{Booklet, Page};
+
+const PAGE_WIDTH: f64 = 595.0;
+const PAGE_HEIGHT: f64 = 842.0;
+const PAGE_MARGIN: f64 = 40.0;
+const HEADING_SIZE: f64 = 18.0;
+const FOOTER_SIZE: f64 = 9.0;
+const SLOT_GAP: f64 = 16.0;
+const DECIMALS: usize = 2;
+
+/// Helvetica advance widths of the printable ASCII characters, in thousandths of the font size.
+const HELVETICA_WIDTHS: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, 1015, 667, 667, 722, 722, 667,
+    611, 778, 722, 278, 500, 667, 556, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 278, 278, 278, 469, 556, 333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500,
+    222, 833, 556, 556, 556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+
+/// Writes the booklet as a PDF file, with one A4 page per booklet page.
+///
+/// Text uses the standard Helvetica fonts, which PDF readers provide, so no font is embedded.
+pub fn write(booklet: &Booklet) -> Vec<u8> {
+    let pages = booklet.pages();
+    let mut objects = vec![
+        String::from("<< /Type /Catalog /Pages 2 0 R >>"),
+        String::new(),
+        font("Helvetica"),
+        font("Helvetica-Bold"),
+    ];
+    let mut page_ids = vec![];
+
+    for page in &pages {
+        let content = page_content(page, pages.len());
+        let content_id = objects.len() + 2;
+
+        page_ids.push(format!("{} 0 R", objects.len() + 1));
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] \
+             /Resources << /Font << /F1 3 0 R /F2 4 0 R >> >> /Contents {} 0 R >>",
+            PAGE_WIDTH, PAGE_HEIGHT, content_id
+        ));
+        objects.push(format!(
+            "<< /Length {} >>\nstream\n{}endstream",
+            content.len(),
+            content
+        ));
+    }
+
+    objects[1] = format!(
+        "<< /Type /Pages /Kids [{}] /Count {} >>",
+        page_ids.join(" "),
+        page_ids.len()
+    );
+
+    let mut pdf = b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n".to_vec();
+    let mut offsets = vec![];
+
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.extend(format!("{} 0 obj\n{}\nendobj\n", i + 1, object).bytes());
+    }
+
+    let xref_offset = pdf.len();
+    let mut xref = format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+    for offset in offsets {
+        let _ = writeln!(xref, "{:010} 00000 n ", offset);
+    }
+    let _ = write!(
+        xref,
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+        objects.len() + 1,
+        xref_offset
+    );
+    pdf.extend(xref.bytes());
+
+    pdf
+}
+
+fn font(name: &str) -> String {
+    format!(
+        "<< /Type /Font /Subtype /Type1 /BaseFont /{} /Encoding /WinAnsiEncoding >>",
+        name
+    )
+}
+
+fn page_content(page: &Page, page_count: usize) -> String {
+    let mut content = String::new();
+    let mut top = PAGE_MARGIN;
+
+    if let Some(heading) = &page.heading {
+        text(
+            &mut content,
+            heading,
+            (PAGE_MARGIN, top),
+            HEADING_SIZE,
+            true,
+        );
+        top += HEADING_SIZE * 2.0;
+    }
+
+    let footer = format!("{} / {}", page.number, page_count);
+    let footer_width = text_width(&footer, FOOTER_SIZE);
+    let footer_position = (
+        (PAGE_WIDTH - footer_width) / 2.0,
+        PAGE_HEIGHT - PAGE_MARGIN / 2.0,
+    );
+    text(&mut content, &footer, footer_position, FOOTER_SIZE, false);
+
+    let label_size = match page.is_answer_key {
+        true => 8.0,
+        false => 12.0,
+    };
+    let slot_width = (PAGE_WIDTH - 2.0 * PAGE_MARGIN) / page.columns as f64;
+    let slot_height = (PAGE_HEIGHT - PAGE_MARGIN - top) / page.rows as f64;
+
+    for (i, item) in page.items.iter().enumerate() {
+        let left = PAGE_MARGIN + slot_width * (i % page.columns) as f64;
+        let slot_top = top + slot_height * (i / page.columns) as f64;
+        let grid_size = 9.0 + 2.0 * item.drawing.margin();
+        let available = (slot_width - SLOT_GAP).min(slot_height - SLOT_GAP - label_size * 1.5);
+        let scale = available / grid_size;
+        let grid_left = left + (slot_width - available) / 2.0;
+
+        text(
+            &mut content,
+            &item.label,
+            (grid_left, slot_top),
+            label_size,
+            true,
+        );
+
+        if let Some(caption) = &item.caption {
+            let x = grid_left + available - text_width(caption, label_size);
+            text(&mut content, caption, (x, slot_top), label_size, false);
+        }
+
+        let margin = item.drawing.margin() * scale;
+        let origin = (grid_left + margin, slot_top + label_size * 1.5 + margin);
+        draw(&mut content, &item.drawing, origin, scale);
+    }
+
+    content
+}
+
+/// Writes text with its top left corner at `(x, y)`, in top-down page coordinates.
+fn text(content: &mut String, text: &str, (x, y): (f64, f64), size: f64, bold: bool) {
+    let font = match bold {
+        true => "F2",
+        false => "F1",
+    };
+
+    let _ = writeln!(
+        content,
+        "0 g BT /{} {} Tf {} {} Td ({}) Tj ET",
+        font,
+        number(size, DECIMALS),
+        number(x, DECIMALS),
+        number(PAGE_HEIGHT - y - size * 0.8, DECIMALS),
+        encode(text)
+    );
+}
+
+fn draw(content: &mut String, drawing: &Drawing, (left, top): (f64, f64), scale: f64) {
+    let point = |point: &Point| {
+        (
+            number(left + point.x * scale, DECIMALS),
+            number(PAGE_HEIGHT - top - point.y * scale, DECIMALS),
+        )
+    };
+
+    for shape in drawing.shapes() {
+        let _ = match shape {
+            Shape::Line {
+                from,
+                to,
+                width,
+                color,
+                dashed,
+            } => {
+                let ((x1, y1), (x2, y2)) = (point(from), point(to));
+                let dash = match dashed {
+                    true => format!("[{0} {0}] 0 d", number(width * scale * 3.0, DECIMALS)),
+                    false => String::from("[] 0 d"),
+                };
+
+                writeln!(
+                    content,
+                    "{} RG {} w 2 J {} {} {} m {} {} l S",
+                    rgb(*color),
+                    number(width * scale, DECIMALS),
+                    dash,
+                    x1,
+                    y1,
+                    x2,
+                    y2
+                )
+            }
+            Shape::Polyline {
+                points,
+                width,
+                color,
+            } => {
+                let mut path = String::new();
+
+                for (i, (x, y)) in points.iter().map(point).enumerate() {
+                    let operator = match i {
+                        0 => "m",
+                        _ => "l",
+                    };
+                    let _ = write!(path, "{} {} {} ", x, y, operator);
+                }
+
+                writeln!(
+                    content,
+                    "{} RG {} w 1 J 1 j [] 0 d {}S",
+                    rgb(*color),
+                    number(width * scale, DECIMALS),
+                    path
+                )
+            }
+            Shape::Circle {
+                center,
+                radius,
+                fill,
+                stroke,
+            } => {
+                let center = Point::new(left + center.x * scale, top + center.y * scale);
+                let path = circle_path(center, radius * scale);
+                let (style, operator) = match (fill, stroke) {
+                    (Some(fill), Some((width, color))) => (
+                        format!(
+                            "{} rg {} RG {} w [] 0 d",
+                            rgb(*fill),
+                            rgb(*color),
+                            number(width * scale, DECIMALS)
+                        ),
+                        "B",
+                    ),
+                    (Some(fill), None) => (format!("{} rg", rgb(*fill)), "f"),
+                    (None, Some((width, color))) => (
+                        format!(
+                            "{} RG {} w [] 0 d",
+                            rgb(*color),
+                            number(width * scale, DECIMALS)
+                        ),
+                        "S",
+                    ),
+                    (None, None) => continue,
+                };
+
+                writeln!(content, "{} {}{}", style, path, operator)
+            }
+            Shape::Rect {
+                corner,
+                width,
+                height,
+                fill,
+            } => {
+                let (x, _) = point(corner);
+                let bottom = Point::new(corner.x, corner.y + height);
+                let (_, y) = point(&bottom);
+
+                writeln!(
+                    content,
+                    "{} rg {} {} {} {} re f",
+                    rgb(*fill),
+                    x,
+                    y,
+                    number(width * scale, DECIMALS),
+                    number(height * scale, DECIMALS)
+                )
+            }
+            Shape::Text {
+                center,
+                text,
+                size,
+                color,
+                bold,
+            } => {
+                let size = size * scale;
+                let font = match bold {
+                    true => "F2",
+                    false => "F1",
+                };
+                let x = left + center.x * scale - text_width(text, size) / 2.0;
+                let y = PAGE_HEIGHT - top - center.y * scale - size * 0.35;
+
+                writeln!(
+                    content,
+                    "{} rg BT /{} {} Tf {} {} Td ({}) Tj ET",
+                    rgb(*color),
+                    font,
+                    number(size, DECIMALS),
+                    number(x, DECIMALS),
+                    number(y, DECIMALS),
+                    encode(text)
+                )
+            }
+        };
+    }
+}
+
+/// A circle as four Bézier curves, with its center in top-down page coordinates.
+fn circle_path(center: Point, radius: f64) -> String {
+    const KAPPA: f64 = 0.5523;
+    let (x, y, r, k) = (center.x, PAGE_HEIGHT - center.y, radius, radius * KAPPA);
+    let n = |x: f64| number(x, DECIMALS);
+
+    format!(
+        "{} {} m {} {} {} {} {} {} c {} {} {} {} {} {} c {} {} {} {} {} {} c {} {} {} {} {} {} c ",
+        n(x + r),
+        n(y),
+        n(x + r),
+        n(y + k),
+        n(x + k),
+        n(y + r),
+        n(x),
+        n(y + r),
+        n(x - k),
+        n(y + r),
+        n(x - r),
+        n(y + k),
+        n(x - r),
+        n(y),
+        n(x - r),
+        n(y - k),
+        n(x - k),
+        n(y - r),
+        n(x),
+        n(y - r),
+        n(x + k),
+        n(y - r),
+        n(x + r),
+        n(y - k),
+        n(x + r),
+        n(y)
+    )
+}
+
+fn text_width(text: &str, size: f64) -> f64 {
+    let width: u32 = text
+        .chars()
+        .map(|x| match x as u32 {
+            32..=126 => HELVETICA_WIDTHS[x as usize - 32] as u32,
+            _ => 556,
+        })
+        .sum();
+
+    width as f64 * size / 1000.0
+}
+
+fn rgb(color: Color) -> String {
+    let Color(r, g, b) = color;
+
+    format!(
+        "{} {} {}",
+        number(r as f64 / 255.0, DECIMALS),
+        number(g as f64 / 255.0, DECIMALS),
+        number(b as f64 / 255.0, DECIMALS)
+    )
+}
+
+/// Writes a PDF string literal body. Characters outside Latin-1 have no glyph in the
+/// standard fonts, and are replaced with `?`.
+fn encode(text: &str) -> String {
+    let mut result = String::new();
+
+    for c in text.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                result.push('\\');
+                result.push(c);
+            }
+            ' '..='~' => result.push(c),
+            '\u{a0}'..='\u{ff}' => {
+                let _ = write!(result, "\\{:03o}", c as u32);
+            }
+            _ => result.push('?'),
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::sudoku::ParseMode;
+    use super::super::tests::PUZZLES;
+    use super::super::Booklet;
+    use super::{encode, write};
+
+    #[test]
+    fn cross_reference_table() {
+        let booklet = Booklet::read(PUZZLES.as_bytes(), ParseMode::Lenient, 2).unwrap();
+        let pdf = write(&booklet);
+        let text = String::from_utf8_lossy(&pdf);
+
+        assert!(pdf.starts_with(b"%PDF-1.4\n"));
+        assert!(pdf.ends_with(b"%%EOF\n"));
+        assert_eq!(text.matches("/Type /Page ").count(), 3);
+        assert!(text.contains("/Count 3"));
+
+        let xref = text.rfind("xref\n").unwrap();
+        let offsets: Vec<usize> = text[xref..]
+            .lines()
+            .skip(3)
+            .take_while(|x| x.ends_with(" n "))
+            .map(|x| x[..10].parse().unwrap())
+            .collect();
+
+        for (i, offset) in offsets.iter().enumerate() {
+            assert!(text[*offset..].starts_with(&format!("{} 0 obj", i + 1)));
+        }
+    }
+
+    #[test]
+    fn string_literals() {
+        assert_eq!(encode("Puzzle (1)"), "Puzzle \\(1\\)");
+        assert_eq!(encode("Mélange"), "M\\351lange");
+        assert_eq!(encode("数独"), "??");
+    }
+}
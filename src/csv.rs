@@ -0,0 +1,53 @@
+use super::batch::{PuzzleReport, PuzzleStatus};
+
+pub const HEADER: &str =
+    "puzzle,status,solution_count,solution,nodes_explored,backtracks,elapsed_us,difficulty,error";
+
+pub fn report_to_row(report: &PuzzleReport) -> String {
+    let solution = report.solution.as_ref().map(|x| x.to_single_line());
+    let error = match &report.status {
+        // Keeps each row on a single line, as spreadsheets expect.
+        PuzzleStatus::Invalid(e) => Some(e.to_string().replace("\n\t", "; ")),
+        _ => None,
+    };
+
+    let fields = [
+        report.puzzle.clone(),
+        report.status.name().to_string(),
+        report.solution_count.to_string(),
+        solution.unwrap_or_default(),
+        report.stats.nodes_explored.to_string(),
+        report.stats.backtracks.to_string(),
+        report.elapsed.as_micros().to_string(),
+        report.difficulty.clone().unwrap_or_default(),
+        error.unwrap_or_default(),
+    ];
+
+    fields
+        .iter()
+        .map(|x| escape_field(x))
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+fn escape_field(field: &str) -> String {
+    match field.contains([',', '"', '\n', '\r']) {
+        true => format!("\"{}\"", field.replace('"', "\"\"")),
+        false => field.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::escape_field;
+
+    #[test]
+    fn quoted_fields() {
+        assert_eq!(escape_field("no solution"), "no solution");
+        assert_eq!(escape_field("a, b"), "\"a, b\"");
+        assert_eq!(
+            escape_field("illegal character '\"'\n\tline 2"),
+            "\"illegal character '\"\"'\n\tline 2\""
+        );
+    }
+}
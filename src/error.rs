@@ -0,0 +1,121 @@
+use std::fmt::Display;
+use std::io;
+
+use super::sudoku::conflict::Conflict;
+use super::sudoku::consistency::Contradiction;
+use super::sudoku::{constraint, CellLocation};
+
+#[derive(Debug)]
+pub enum SudokuError {
+    Io(io::Error),
+    NoInput,
+    WrongLineCount {
+        found: usize,
+    },
+    /// A block of a batch file with more lines than any puzzle needs.
+    BlockTooLong {
+        limit: usize,
+    },
+    WrongLineLength {
+        line: usize,
+        length: usize,
+    },
+    /// `column` counts the characters of the table row, starting from 1.
+    IllegalCharacter {
+        line: usize,
+        column: usize,
+        character: char,
+    },
+    RuleConflict {
+        cells: Vec<CellLocation>,
+        conflicts: Vec<Conflict>,
+    },
+    Contradiction {
+        contradictions: Vec<Contradiction>,
+    },
+    InvalidRule {
+        line: usize,
+        message: String,
+    },
+    Import(String),
+    UnwritableConstraint,
+    /// An unsolved cell of the player's grid has no candidates left to save.
+    NoCandidates {
+        cell: CellLocation,
+    },
+}
+
+impl Display for SudokuError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "I/O error: {}", e),
+            Self::NoInput => write!(f, "Input file name not specified"),
+            Self::WrongLineCount { found } => {
+                write!(f, "Invalid input: expected 9 lines, found {}", found)
+            }
+            Self::BlockTooLong { limit } => {
+                write!(f, "Invalid input: block longer than {} lines", limit)
+            }
+            Self::WrongLineLength { line, length } => write!(
+                f,
+                "Invalid input: line {} should have exactly 9 characters, found {}",
+                line, length
+            ),
+            Self::IllegalCharacter {
+                line,
+                column,
+                character,
+            } => write!(
+                f,
+                "Invalid input: illegal character '{}' at line {}, column {}",
+                character, line, column
+            ),
+            Self::RuleConflict { cells, conflicts } => {
+                write!(
+                    f,
+                    "Invalid input: illegal table, conflicting cells: {}",
+                    constraint::cells_to_string(cells)
+                )?;
+
+                for conflict in conflicts {
+                    write!(f, "\n\t{}", conflict)?;
+                }
+
+                Ok(())
+            }
+            Self::Contradiction { contradictions } => {
+                write!(f, "Invalid input: the puzzle has no solution")?;
+
+                for contradiction in contradictions {
+                    write!(f, "\n\t{}", contradiction)?;
+                }
+
+                Ok(())
+            }
+            Self::InvalidRule { line, message } => write!(f, "{} (line {})", message, line),
+            Self::Import(message) => write!(f, "{}", message),
+            Self::UnwritableConstraint => write!(
+                f,
+                "Cannot write puzzle: a constraint has no puzzle file representation"
+            ),
+            Self::NoCandidates { cell } => {
+                write!(f, "Cannot write puzzle: {} has no candidates left", cell)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SudokuError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for SudokuError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
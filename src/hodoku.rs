@@ -0,0 +1,341 @@
+use super::error::SudokuError;
+use super::input::ParsedPuzzle;
+use super::sudoku::drawing::escape_xml;
+use super::sudoku::metadata::Metadata;
+use super::sudoku::pencil_marks::PencilMarks;
+use super::sudoku::{CellLocation, ParseMode, SudokuCell, SudokuTable};
+
+/// Whether the line is a puzzle in HoDoKu's library layout, with 81 cells in its grid field.
+pub fn is_puzzle_line(line: &str) -> bool {
+    line.starts_with(':')
+        && line
+            .split(':')
+            .nth(3)
+            .is_some_and(|x| x.chars().filter(|x| *x != '+').count() == 81)
+}
+
+/// Reads a puzzle written as a line of a HoDoKu library file, `:0000:x:<grid>:<deleted
+/// candidates>:…`. This is not HoDoKu's `.hsol` save file, and it holds no metadata. In the grid,
+/// a digit after `+` was placed by the player, and each deleted candidate is written as its
+/// digit, row and column.
+pub fn read(lines: &[String]) -> Result<ParsedPuzzle, SudokuError> {
+    let mut puzzle = None;
+
+    for line in lines
+        .iter()
+        .map(|x| x.trim_start_matches('\u{feff}').trim())
+    {
+        if is_puzzle_line(line) && puzzle.is_none() {
+            puzzle = Some(line);
+        } else if !line.is_empty() {
+            return Err(SudokuError::Import(format!(
+                "Unexpected line in HoDoKu library file: '{}'",
+                line
+            )));
+        }
+    }
+
+    let fields: Vec<&str> = puzzle
+        .ok_or_else(|| SudokuError::Import(String::from("No puzzle found in HoDoKu library file")))?
+        .split(':')
+        .collect();
+    let (mut givens, mut state) = (String::new(), String::new());
+    let mut is_player_value = false;
+    let invalid_grid = || SudokuError::Import(format!("Invalid HoDoKu grid '{}'", fields[3]));
+
+    for c in fields[3].chars() {
+        match (c, is_player_value) {
+            ('+', false) => is_player_value = true,
+            ('1'..='9', true) => {
+                givens.push('.');
+                state.push(c);
+                is_player_value = false;
+            }
+            ('1'..='9' | '.' | '0', false) => {
+                givens.push(c);
+                state.push(c);
+            }
+            _ => return Err(invalid_grid()),
+        }
+    }
+
+    if is_player_value {
+        return Err(invalid_grid());
+    }
+
+    let table = SudokuTable::parse_unchecked([givens].into_iter(), ParseMode::Strict)?;
+    let state = SudokuTable::parse_unchecked([state].into_iter(), ParseMode::Strict)?;
+    let deleted = fields.get(4).copied().unwrap_or_default();
+
+    let pencil_marks = match deleted.trim().is_empty() && state.contents() == table.contents() {
+        true => None,
+        false => {
+            let mut marks = PencilMarks::from_table(&state);
+
+            for candidate in deleted.split_whitespace() {
+                let (digit, cell) = parse_candidate(candidate).ok_or_else(|| {
+                    SudokuError::Import(format!("Invalid deleted candidate '{}'", candidate))
+                })?;
+                let digits: Vec<u8> = marks
+                    .candidates(cell)
+                    .into_iter()
+                    .filter(|x| *x != digit)
+                    .collect();
+
+                marks.set_candidates(cell, &digits);
+            }
+
+            Some(marks)
+        }
+    };
+
+    Ok(ParsedPuzzle {
+        table,
+        pencil_marks,
+        warnings: vec![],
+    })
+}
+
+/// Writes the puzzle as a HoDoKu library line. The metadata is left out, as the line has no
+/// place for it.
+pub fn write(table: &SudokuTable, progress: Option<&PencilMarks>) -> String {
+    let state = progress.map_or(table, |x| x.table());
+    let mut grid = String::new();
+
+    for (row, cols) in table.contents().iter().enumerate() {
+        for (col, cell) in cols.iter().enumerate() {
+            match (cell, state.contents()[row][col]) {
+                (SudokuCell::Filled(value), _) => grid.push((b'0' + value) as char),
+                (SudokuCell::Empty, SudokuCell::Filled(value)) => {
+                    grid.push('+');
+                    grid.push((b'0' + value) as char);
+                }
+                (SudokuCell::Empty, SudokuCell::Empty) => grid.push('.'),
+            }
+        }
+    }
+
+    // Only the candidates the player erased are saved; the others follow from the grid.
+    let mut deleted = vec![];
+
+    if let Some(progress) = progress {
+        let computed = PencilMarks::from_table(progress.table());
+
+        for i in 0..81 {
+            let cell = CellLocation::new(i / 9, i % 9);
+            let kept = progress.candidate_mask(cell);
+
+            for digit in computed.candidates(cell) {
+                if kept & 1 << (digit - 1) == 0 {
+                    deleted.push(format!("{}{}{}", digit, cell.row() + 1, cell.col() + 1));
+                }
+            }
+        }
+    }
+
+    format!(":0000:x:{}:{}::\n", grid, deleted.join(" "))
+}
+
+/// Whether the lines are a HoDoKu `.hsol` save file, the XML document of Java's `XMLEncoder`.
+pub fn is_save_file(lines: &[String]) -> bool {
+    lines.iter().any(|x| x.contains("java.beans.XMLDecoder"))
+}
+
+/// Reads a HoDoKu `.hsol` save file. The puzzle, with the player's values and deleted
+/// candidates, is the `sudoku` string property, written as a library line, and the `title`,
+/// `author`, `description`, `difficulty` and `comment` string properties hold the metadata.
+pub fn read_save_file(lines: &[String]) -> Result<ParsedPuzzle, SudokuError> {
+    let text = lines.join("\n");
+    let mut metadata = Metadata::default();
+    let mut warnings = vec![];
+    let mut sudoku = None;
+
+    for (name, value) in string_properties(&text)? {
+        match (name, value) {
+            ("sudoku", Some(value)) => sudoku = Some(value),
+            (name, Some(value)) if metadata.add_from_line(name, &value) => (),
+            (name, _) => warnings.push(format!("ignoring HoDoKu property '{}'", name)),
+        }
+    }
+
+    let sudoku = sudoku.ok_or_else(|| {
+        SudokuError::Import(String::from("No sudoku property found in HoDoKu save file"))
+    })?;
+    let mut parsed = read(&[sudoku])?;
+    *parsed.table.metadata_mut() = metadata;
+    parsed.warnings = warnings;
+
+    Ok(parsed)
+}
+
+/// Writes the puzzle as a HoDoKu `.hsol` save file, in the layout `read_save_file` reads.
+pub fn write_save_file(table: &SudokuTable, progress: Option<&PencilMarks>) -> String {
+    let line = write(table, progress);
+    let mut properties = vec![(String::from("sudoku"), line.trim_end().to_string())];
+
+    for line in table.metadata().to_lines() {
+        if let Some((key, value)) = line.split_once(": ") {
+            properties.push((key.to_string(), value.to_string()));
+        }
+    }
+
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+        <java version=\"1.8.0\" class=\"java.beans.XMLDecoder\">\n \
+        <object class=\"sudoku.GuiState\">\n",
+    );
+
+    for (name, value) in properties {
+        xml.push_str(&format!(
+            "  <void property=\"{}\">\n   <string>{}</string>\n  </void>\n",
+            name,
+            escape_xml(&value)
+        ));
+    }
+
+    xml.push_str(" </object>\n</java>\n");
+    xml
+}
+
+/// The `<void property="…">` elements of an `XMLEncoder` document, with their value when it is
+/// a string.
+fn string_properties(text: &str) -> Result<Vec<(&str, Option<String>)>, SudokuError> {
+    const START: &str = "<void property=\"";
+    let mut properties = vec![];
+    let mut rest = text;
+
+    while let Some(start) = rest.find(START) {
+        let (name, after) = rest[start + START.len()..]
+            .split_once("\">")
+            .ok_or_else(|| {
+                SudokuError::Import(String::from("Unclosed property in HoDoKu save file"))
+            })?;
+        rest = after.trim_start();
+
+        match rest
+            .strip_prefix("<string>")
+            .and_then(|x| x.split_once("</string>"))
+        {
+            Some((value, after)) => {
+                properties.push((name, Some(unescape_xml(value)?)));
+                rest = after;
+            }
+            None => properties.push((name, None)),
+        }
+    }
+
+    Ok(properties)
+}
+
+/// Replaces the character and entity references of XML text.
+fn unescape_xml(text: &str) -> Result<String, SudokuError> {
+    let mut result = String::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        result.push_str(&rest[..start]);
+
+        let (reference, after) = rest[start + 1..].split_once(';').unwrap_or(("", ""));
+        let c = match reference {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => match reference.strip_prefix("#x") {
+                Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                None => reference.strip_prefix('#').and_then(|x| x.parse().ok()),
+            }
+            .and_then(char::from_u32),
+        };
+
+        result.push(c.ok_or_else(|| {
+            SudokuError::Import(format!("Invalid XML reference '&{};'", reference))
+        })?);
+        rest = after;
+    }
+
+    result.push_str(rest);
+    Ok(result)
+}
+
+/// A candidate written as its digit, row and column, such as `513` for a 5 in r1c3.
+fn parse_candidate(text: &str) -> Option<(u8, CellLocation)> {
+    let digits: Vec<usize> = text
+        .chars()
+        .map(|x| x.to_digit(10).filter(|x| *x > 0).map(|x| x as usize))
+        .collect::<Option<_>>()?;
+
+    match digits[..] {
+        [digit, row, col] => Some((digit as u8, format!("r{}c{}", row, col).parse().ok()?)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::sudoku::{CellLocation, SudokuCell};
+    use super::{is_puzzle_line, is_save_file, read, read_save_file, write, write_save_file};
+
+    const SAVED: &str = ":0000:x:+3.1.....2....34....5...1..6.2.6....3.3.....5.7....8.9.9..4...3....71....8.....4..:712 812::\n";
+
+    #[test]
+    fn saved_progress() {
+        let lines: Vec<String> = SAVED.lines().map(String::from).collect();
+        let parsed = read(&lines).unwrap();
+        let progress = parsed.pencil_marks.as_ref().unwrap();
+
+        assert!(is_puzzle_line(&lines[0]));
+        assert_eq!(parsed.table.contents()[0][0], SudokuCell::Empty);
+        assert_eq!(progress.table().contents()[0][0], SudokuCell::Filled(3));
+        assert_eq!(progress.candidates(CellLocation::new(0, 1)), [4, 6, 9]);
+        assert_eq!(write(&parsed.table, Some(progress)), SAVED);
+
+        let givens = lines[0].replace('+', "").replace("712 812", "");
+        let givens = read(&[givens]);
+        assert!(givens.unwrap().pencil_marks.is_none());
+        assert!(read(&[String::from(":0000:x:12.3::")]).is_err());
+        assert!(read(&[lines[0].replace("4..:", "4..+:")]).is_err());
+        assert!(read(&[String::from("# author: Someone"), lines[0].clone()]).is_err());
+    }
+
+    #[test]
+    fn save_files() {
+        let lines: Vec<String> = SAVED.lines().map(String::from).collect();
+        let mut parsed = read(&lines).unwrap();
+        let metadata = parsed.table.metadata_mut();
+        metadata.author = Some(String::from("Someone & Co."));
+        metadata.description = Some(String::from("A <hard> one"));
+        metadata.comments = vec![String::from("first"), String::from("second")];
+
+        let hsol = write_save_file(&parsed.table, parsed.pencil_marks.as_ref());
+        let hsol: Vec<String> = hsol.lines().map(String::from).collect();
+        assert!(is_save_file(&hsol));
+        assert!(hsol.contains(&String::from("   <string>Someone &amp; Co.</string>")));
+
+        let saved = read_save_file(&hsol).unwrap();
+        let progress = saved.pencil_marks.as_ref().unwrap();
+        assert_eq!(saved.table.metadata(), parsed.table.metadata());
+        assert_eq!(saved.table.contents(), parsed.table.contents());
+        assert_eq!(progress.table().contents()[0][0], SudokuCell::Filled(3));
+        assert_eq!(progress.candidates(CellLocation::new(0, 1)), [4, 6, 9]);
+        assert_eq!(write(&saved.table, Some(progress)), SAVED);
+        assert!(saved.warnings.is_empty());
+
+        // Properties HoDoKu keeps of its own are skipped with a warning.
+        let other = hsol.join("\n").replace(
+            " </object>",
+            "  <void property=\"tabSteps\">\n   <int>2</int>\n  </void>\n </object>",
+        );
+        let other = read_save_file(&[other]).unwrap();
+        assert_eq!(other.warnings, ["ignoring HoDoKu property 'tabSteps'"]);
+
+        let without_sudoku: Vec<String> = hsol
+            .iter()
+            .filter(|x| !x.contains(":0000:"))
+            .cloned()
+            .collect();
+        assert!(read_save_file(&without_sudoku).is_err());
+        assert!(read_save_file(&[hsol.join("\n").replace("&amp;", "&bogus;")]).is_err());
+    }
+}
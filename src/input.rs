@@ -0,0 +1,228 @@
+use super::error::SudokuError;
+use super::hodoku;
+use super::json::JsonValue;
+use super::json_format;
+use super::sadman;
+use super::sudoku::fpuzzles;
+use super::sudoku::pencil_marks::PencilMarks;
+use super::sudoku::solver::SudokuSolver;
+use super::sudoku::{ParseMode, SudokuTable};
+
+pub struct ParsedPuzzle {
+    pub table: SudokuTable,
+    /// The player's progress saved with the puzzle, placed values and candidates, that solving
+    /// should start from. `table` holds the givens alone.
+    pub pencil_marks: Option<PencilMarks>,
+    pub warnings: Vec<String>,
+}
+
+impl ParsedPuzzle {
+    /// Checks that the givens keep the rules and can still be solved, and the player's values and
+    /// candidates saved with them too, as solving starts from them.
+    pub fn check(&self) -> Result<(), SudokuError> {
+        self.table.validate()?;
+        self.table.check_consistency()?;
+
+        if let Some(marks) = &self.pencil_marks {
+            marks.table().validate()?;
+            marks.check_consistency()?;
+        }
+
+        Ok(())
+    }
+
+    /// The grid the player sees: the saved progress when there is one, or else the givens.
+    pub fn current_table(&self) -> &SudokuTable {
+        self.pencil_marks
+            .as_ref()
+            .map_or(&self.table, |x| x.table())
+    }
+
+    pub fn solver(&self) -> SudokuSolver {
+        match &self.pencil_marks {
+            Some(marks) => SudokuSolver::from_pencil_marks(marks),
+            None => SudokuSolver::new(&self.table),
+        }
+    }
+}
+
+/// The files of other sudoku programs, told apart by their extension or their content.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FileFormat {
+    /// SadMan Sudoku's plain grid, with the player's grid in a `[State]` section.
+    Sdk,
+    /// SadMan Sudoku's grid with candidates.
+    Sdx,
+    /// A line of a HoDoKu library file, with `:`-separated fields. It has no file extension of
+    /// its own, so it is only told by its content.
+    HodokuLibrary,
+    /// HoDoKu's `.hsol` save file, with the metadata beside the library line.
+    Hsol,
+}
+
+impl FileFormat {
+    pub fn from_path(path: &str) -> Option<FileFormat> {
+        let (_, extension) = path.rsplit_once('.')?;
+
+        match extension.to_ascii_lowercase().as_str() {
+            "sdk" => Some(Self::Sdk),
+            "sdx" => Some(Self::Sdx),
+            "hsol" => Some(Self::Hsol),
+            _ => None,
+        }
+    }
+
+    pub fn sniff(lines: &[String]) -> Option<FileFormat> {
+        if hodoku::is_save_file(lines) {
+            return Some(Self::Hsol);
+        }
+
+        let lines: Vec<&str> = lines
+            .iter()
+            .map(|x| x.trim_start_matches('\u{feff}').trim())
+            .filter(|x| !x.is_empty())
+            .collect();
+        let is_header = |x: &&str| x.starts_with('#');
+
+        if lines.iter().any(|x| hodoku::is_puzzle_line(x)) {
+            return Some(Self::HodokuLibrary);
+        }
+
+        if lines.iter().any(|x| x.eq_ignore_ascii_case("[Puzzle]")) {
+            return Some(Self::Sdk);
+        }
+
+        let tokens: Vec<&str> = lines
+            .iter()
+            .filter(|x| !is_header(x))
+            .flat_map(|x| x.split_whitespace())
+            .collect();
+        let is_sdx_cell = |x: &&str| {
+            let digits = x.strip_prefix('u').unwrap_or(x);
+            !digits.is_empty() && digits.chars().all(|x| x.is_ascii_digit())
+        };
+
+        if tokens.len() == 81
+            && tokens.iter().all(is_sdx_cell)
+            && (tokens.iter().any(|x| x.starts_with('u')) || lines.iter().any(is_header))
+        {
+            return Some(Self::Sdx);
+        }
+
+        // SadMan headers are a `#` and a capital letter, such as `#A` for the author.
+        lines
+            .first()
+            .filter(|x| x.chars().nth(1).is_some_and(|x| x.is_ascii_uppercase()) && is_header(x))
+            .map(|_| Self::Sdk)
+    }
+}
+
+pub fn is_json(lines: &[String]) -> bool {
+    lines
+        .iter()
+        .map(|x| x.trim_start_matches(|x: char| x.is_whitespace() || x == '\u{feff}'))
+        .find(|x| !x.is_empty())
+        .is_some_and(|x| x.starts_with('{'))
+}
+
+/// The returned table is not validated yet.
+pub fn read_puzzle(lines: Vec<String>, mode: ParseMode) -> Result<ParsedPuzzle, SudokuError> {
+    if let Some(format) = FileFormat::sniff(&lines) {
+        return read_file(&lines, format);
+    }
+
+    if PencilMarks::is_pencil_marks(&lines) {
+        let marks = PencilMarks::parse(&lines.join("\n"))?;
+
+        return Ok(ParsedPuzzle {
+            table: marks.table().clone(),
+            pencil_marks: Some(marks),
+            warnings: vec![],
+        });
+    }
+
+    if !is_json(&lines) {
+        return Ok(ParsedPuzzle {
+            table: SudokuTable::parse_unchecked(lines.into_iter(), mode)?,
+            pencil_marks: None,
+            warnings: vec![],
+        });
+    }
+
+    let json_str = lines.join("\n");
+    let json_str = json_str.trim_start_matches('\u{feff}');
+    let value = JsonValue::parse(json_str).map_err(SudokuError::Import)?;
+
+    // A solving result carries its puzzle, so it can be fed back in.
+    let value = match value.get("puzzle") {
+        Some(puzzle) if json_format::is_puzzle(puzzle) => puzzle,
+        _ => &value,
+    };
+
+    if json_format::is_puzzle(value) {
+        return Ok(ParsedPuzzle {
+            table: json_format::puzzle_from_json(value, mode)?,
+            pencil_marks: None,
+            warnings: vec![],
+        });
+    }
+
+    let import = fpuzzles::import(json_str)?;
+    let warnings = import
+        .unsupported
+        .into_iter()
+        .map(|x| format!("ignoring unsupported f-puzzles feature '{}'", x))
+        .collect();
+
+    Ok(ParsedPuzzle {
+        table: import.table,
+        pencil_marks: None,
+        warnings,
+    })
+}
+
+/// Reads a puzzle saved by another sudoku program. The returned table is not validated yet.
+pub fn read_file(lines: &[String], format: FileFormat) -> Result<ParsedPuzzle, SudokuError> {
+    match format {
+        FileFormat::Sdk => sadman::read_sdk(lines),
+        FileFormat::Sdx => sadman::read_sdx(lines),
+        FileFormat::HodokuLibrary => hodoku::read(lines),
+        FileFormat::Hsol => hodoku::read_save_file(lines),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FileFormat;
+
+    fn sniff(text: &str) -> Option<FileFormat> {
+        FileFormat::sniff(&text.lines().map(String::from).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn file_formats() {
+        let grid =
+            "..1.....2....34....5...1..6.2.6....3.3.....5.7....8.9.9..4...3....71....8.....4..";
+        let sdx_row = "u3 46789 1 589 56789 5679 35789 478 2\n";
+
+        assert_eq!(
+            FileFormat::from_path("puzzles/Easy.SDK"),
+            Some(FileFormat::Sdk)
+        );
+        assert_eq!(FileFormat::from_path("saved.hsol"), Some(FileFormat::Hsol));
+        assert_eq!(FileFormat::from_path("puzzle.txt"), None);
+        assert_eq!(
+            sniff(&format!(":0000:x:{}:::", grid)),
+            Some(FileFormat::HodokuLibrary)
+        );
+        assert_eq!(
+            sniff("<?xml version=\"1.0\"?>\n<java class=\"java.beans.XMLDecoder\">"),
+            Some(FileFormat::Hsol)
+        );
+        assert_eq!(sniff("#AAuthor\n..1.....2"), Some(FileFormat::Sdk));
+        assert_eq!(sniff(&format!("[Puzzle]\n{}", grid)), Some(FileFormat::Sdk));
+        assert_eq!(sniff(&sdx_row.repeat(9)), Some(FileFormat::Sdx));
+        assert_eq!(sniff(grid), None);
+        assert_eq!(sniff(&sdx_row.replace('u', "").repeat(9)), None);
+    }
+}
@@ -0,0 +1,437 @@
+use std::fmt::Display;
+use std::iter::Peekable;
+use std::str::Chars;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum JsonValue {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(Vec<(String, JsonValue)>),
+}
+
+impl JsonValue {
+    pub fn parse(json_str: &str) -> Result<JsonValue, String> {
+        let mut parser = JsonParser {
+            chars: json_str.chars().peekable(),
+            position: 0,
+        };
+
+        let result = parser.parse_value(0)?;
+        parser.skip_whitespace();
+
+        match parser.chars.peek() {
+            None => Ok(result),
+            Some(_) => Err(parser.error("unexpected trailing characters")),
+        }
+    }
+
+    pub fn object(entries: Vec<(&str, JsonValue)>) -> JsonValue {
+        JsonValue::Object(
+            entries
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value))
+                .collect(),
+        )
+    }
+
+    pub fn get(&self, key: &str) -> Option<&JsonValue> {
+        match self {
+            JsonValue::Object(entries) => entries.iter().find(|x| x.0 == key).map(|x| &x.1),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            JsonValue::String(x) => Some(x),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            JsonValue::Number(x) => Some(*x),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            JsonValue::Bool(x) => Some(*x),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[JsonValue]> {
+        match self {
+            JsonValue::Array(x) => Some(x),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&[(String, JsonValue)]> {
+        match self {
+            JsonValue::Object(x) => Some(x),
+            _ => None,
+        }
+    }
+}
+
+impl Display for JsonValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Null => write!(f, "null"),
+            Self::Bool(x) => write!(f, "{}", x),
+            Self::Number(x) if x.fract() == 0.0 && x.abs() < 1e15 => write!(f, "{}", *x as i64),
+            Self::Number(x) if x.is_finite() => write!(f, "{}", x),
+            Self::Number(_) => write!(f, "null"),
+            Self::String(x) => write_string(f, x),
+            Self::Array(items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            }
+            Self::Object(entries) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write_string(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+fn write_string(f: &mut std::fmt::Formatter<'_>, value: &str) -> std::fmt::Result {
+    write!(f, "\"")?;
+
+    for char in value.chars() {
+        match char {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            x if (x as u32) < 0x20 => write!(f, "\\u{:04x}", x as u32)?,
+            x => write!(f, "{}", x)?,
+        }
+    }
+
+    write!(f, "\"")
+}
+
+impl From<&str> for JsonValue {
+    fn from(value: &str) -> Self {
+        JsonValue::String(value.to_string())
+    }
+}
+
+impl From<String> for JsonValue {
+    fn from(value: String) -> Self {
+        JsonValue::String(value)
+    }
+}
+
+impl From<bool> for JsonValue {
+    fn from(value: bool) -> Self {
+        JsonValue::Bool(value)
+    }
+}
+
+impl From<f64> for JsonValue {
+    fn from(value: f64) -> Self {
+        JsonValue::Number(value)
+    }
+}
+
+impl From<u64> for JsonValue {
+    fn from(value: u64) -> Self {
+        JsonValue::Number(value as f64)
+    }
+}
+
+impl From<usize> for JsonValue {
+    fn from(value: usize) -> Self {
+        JsonValue::Number(value as f64)
+    }
+}
+
+impl<T: Into<JsonValue>> From<Option<T>> for JsonValue {
+    fn from(value: Option<T>) -> Self {
+        match value {
+            Some(x) => x.into(),
+            None => JsonValue::Null,
+        }
+    }
+}
+
+impl<T: Into<JsonValue>> From<Vec<T>> for JsonValue {
+    fn from(value: Vec<T>) -> Self {
+        JsonValue::Array(value.into_iter().map(Into::into).collect())
+    }
+}
+
+/// How deeply arrays and objects may be nested, so that hostile input cannot overflow the stack.
+const MAX_DEPTH: usize = 128;
+
+struct JsonParser<'a> {
+    chars: Peekable<Chars<'a>>,
+    position: usize,
+}
+
+impl JsonParser<'_> {
+    fn error(&self, message: &str) -> String {
+        format!("Invalid JSON at character {}: {}", self.position, message)
+    }
+
+    fn next(&mut self) -> Option<char> {
+        self.position += 1;
+        self.chars.next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(' ' | '\t' | '\n' | '\r' | '\u{feff}') = self.chars.peek() {
+            self.next();
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        match self.next() {
+            Some(x) if x == expected => Ok(()),
+            _ => Err(self.error(&format!("expected '{}'", expected))),
+        }
+    }
+
+    fn expect_word(&mut self, word: &str, value: JsonValue) -> Result<JsonValue, String> {
+        for expected in word.chars() {
+            self.expect(expected)?;
+        }
+
+        Ok(value)
+    }
+
+    fn parse_value(&mut self, depth: usize) -> Result<JsonValue, String> {
+        self.skip_whitespace();
+
+        if depth >= MAX_DEPTH && matches!(self.chars.peek(), Some('{' | '[')) {
+            return Err(self.error("nesting too deep"));
+        }
+
+        match self.chars.peek() {
+            Some('{') => self.parse_object(depth + 1),
+            Some('[') => self.parse_array(depth + 1),
+            Some('"') => Ok(JsonValue::String(self.parse_string()?)),
+            Some('t') => self.expect_word("true", JsonValue::Bool(true)),
+            Some('f') => self.expect_word("false", JsonValue::Bool(false)),
+            Some('n') => self.expect_word("null", JsonValue::Null),
+            Some('-' | '0'..='9') => self.parse_number(),
+            Some(&x) => Err(self.error(&format!("unexpected character '{}'", x))),
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+
+    fn parse_object(&mut self, depth: usize) -> Result<JsonValue, String> {
+        let mut entries = vec![];
+        self.expect('{')?;
+        self.skip_whitespace();
+
+        if let Some('}') = self.chars.peek() {
+            self.next();
+            return Ok(JsonValue::Object(entries));
+        }
+
+        loop {
+            self.skip_whitespace();
+            let key = self.parse_string()?;
+            self.skip_whitespace();
+            self.expect(':')?;
+            entries.push((key, self.parse_value(depth)?));
+            self.skip_whitespace();
+
+            match self.next() {
+                Some(',') => continue,
+                Some('}') => return Ok(JsonValue::Object(entries)),
+                _ => return Err(self.error("expected ',' or '}'")),
+            }
+        }
+    }
+
+    fn parse_array(&mut self, depth: usize) -> Result<JsonValue, String> {
+        let mut items = vec![];
+        self.expect('[')?;
+        self.skip_whitespace();
+
+        if let Some(']') = self.chars.peek() {
+            self.next();
+            return Ok(JsonValue::Array(items));
+        }
+
+        loop {
+            items.push(self.parse_value(depth)?);
+            self.skip_whitespace();
+
+            match self.next() {
+                Some(',') => continue,
+                Some(']') => return Ok(JsonValue::Array(items)),
+                _ => return Err(self.error("expected ',' or ']'")),
+            }
+        }
+    }
+
+    fn parse_string(&mut self) -> Result<String, String> {
+        let mut result = String::new();
+        self.expect('"')?;
+
+        loop {
+            match self.next() {
+                Some('"') => return Ok(result),
+                Some('\\') => result.push(self.parse_escape()?),
+                Some(x) => result.push(x),
+                None => return Err(self.error("unterminated string")),
+            }
+        }
+    }
+
+    fn parse_escape(&mut self) -> Result<char, String> {
+        match self.next() {
+            Some('"') => Ok('"'),
+            Some('\\') => Ok('\\'),
+            Some('/') => Ok('/'),
+            Some('b') => Ok('\u{8}'),
+            Some('f') => Ok('\u{c}'),
+            Some('n') => Ok('\n'),
+            Some('r') => Ok('\r'),
+            Some('t') => Ok('\t'),
+            Some('u') => {
+                let high = self.parse_hex_code()?;
+
+                if (0xD800..0xDC00).contains(&high) {
+                    self.expect('\\')?;
+                    self.expect('u')?;
+                    let low = self.parse_hex_code()?;
+
+                    if !(0xDC00..0xE000).contains(&low) {
+                        return Err(self.error("illegal surrogate pair"));
+                    }
+
+                    let code = 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00);
+
+                    char::from_u32(code).ok_or_else(|| self.error("illegal surrogate pair"))
+                } else {
+                    char::from_u32(high).ok_or_else(|| self.error("illegal unicode escape"))
+                }
+            }
+            _ => Err(self.error("illegal escape sequence")),
+        }
+    }
+
+    fn parse_hex_code(&mut self) -> Result<u32, String> {
+        let mut code = 0u32;
+
+        for _ in 0..4 {
+            match self.next().and_then(|x| x.to_digit(16)) {
+                Some(digit) => code = code * 16 + digit,
+                None => return Err(self.error("illegal unicode escape")),
+            }
+        }
+
+        Ok(code)
+    }
+
+    fn parse_number(&mut self) -> Result<JsonValue, String> {
+        let mut number = String::new();
+
+        while let Some(x @ ('-' | '+' | '.' | 'e' | 'E' | '0'..='9')) = self.chars.peek() {
+            number.push(*x);
+            self.next();
+        }
+
+        match number.parse::<f64>() {
+            Ok(x) => Ok(JsonValue::Number(x)),
+            Err(_) => Err(self.error(&format!("illegal number '{}'", number))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::JsonValue;
+
+    #[test]
+    fn parse_nested_values() {
+        let value =
+            JsonValue::parse(r#" {"a": [1, -2.5e1, true, null], "b": {"c": "x\"é"}} "#).unwrap();
+
+        assert_eq!(
+            value.get("a").unwrap().as_array().unwrap(),
+            &[
+                JsonValue::Number(1.0),
+                JsonValue::Number(-25.0),
+                JsonValue::Bool(true),
+                JsonValue::Null
+            ]
+        );
+        assert_eq!(
+            value.get("b").unwrap().get("c").unwrap().as_str(),
+            Some("x\"é")
+        );
+    }
+
+    #[test]
+    fn write_and_parse_back() {
+        let value = JsonValue::object(vec![
+            ("grid", JsonValue::from("1.\"\\\n")),
+            ("count", JsonValue::from(2usize)),
+            ("elapsed", JsonValue::from(1.5)),
+            ("solutions", JsonValue::from(vec![true, false])),
+            ("title", JsonValue::from(None::<String>)),
+        ]);
+
+        assert_eq!(
+            value.to_string(),
+            r#"{"grid":"1.\"\\\n","count":2,"elapsed":1.5,"solutions":[true,false],"title":null}"#
+        );
+        assert_eq!(JsonValue::parse(&value.to_string()).unwrap(), value);
+    }
+
+    #[test]
+    fn nesting_limit() {
+        let nested = |depth: usize| format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+
+        assert!(JsonValue::parse(&nested(128)).is_ok());
+        assert!(JsonValue::parse(&nested(129))
+            .unwrap_err()
+            .ends_with("nesting too deep"));
+        assert!(JsonValue::parse(&nested(200_000)).is_err());
+    }
+
+    #[test]
+    fn surrogate_pairs() {
+        assert_eq!(
+            JsonValue::parse(r#""\uD83D\uDE00""#).unwrap().as_str(),
+            Some("\u{1F600}")
+        );
+        assert!(JsonValue::parse(r#""\uD800\u0041""#)
+            .unwrap_err()
+            .ends_with("illegal surrogate pair"));
+        assert!(JsonValue::parse(r#""\uD800\uD800""#).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "expected ',' or ']'")]
+    fn unterminated_array() {
+        JsonValue::parse("[1, 2").unwrap();
+    }
+}
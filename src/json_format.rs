@@ -0,0 +1,364 @@
+use std::time::Duration;
+
+use super::batch::{BatchSummary, PuzzleReport, PuzzleStatus};
+use super::error::SudokuError;
+use super::json::JsonValue;
+use super::sudoku::conflict::{Conflict, ConflictSource};
+use super::sudoku::consistency::Contradiction;
+use super::sudoku::document;
+use super::sudoku::solver::SolverStats;
+use super::sudoku::{CellLocation, ParseMode, SudokuTable};
+
+pub fn is_puzzle(value: &JsonValue) -> bool {
+    matches!(value.get("grid"), Some(JsonValue::String(_)))
+}
+
+pub fn puzzle_from_json(value: &JsonValue, mode: ParseMode) -> Result<SudokuTable, SudokuError> {
+    let invalid = |message: &str| SudokuError::Import(format!("Invalid JSON puzzle: {}", message));
+
+    let grid = value
+        .get("grid")
+        .and_then(|x| x.as_str())
+        .ok_or_else(|| invalid("'grid' should be a string"))?;
+    let mut lines = vec![];
+
+    if let Some(size) = value.get("size") {
+        match size.as_f64() {
+            Some(x) => lines.push(format!("size: {}", x)),
+            None => return Err(invalid("'size' should be a number")),
+        }
+    }
+
+    for key in ["title", "author", "description", "difficulty"] {
+        match value.get(key) {
+            Some(JsonValue::String(x)) => lines.push(format!("{}: {}", key, x.replace('\n', " "))),
+            Some(JsonValue::Null) | None => (),
+            Some(_) => return Err(invalid(&format!("'{}' should be a string", key))),
+        }
+    }
+
+    for (key, line_key) in [("comments", "comment"), ("rules", "")] {
+        let items = match value.get(key) {
+            Some(JsonValue::Array(x)) => x,
+            Some(_) => return Err(invalid(&format!("'{}' should be an array", key))),
+            None => continue,
+        };
+
+        for item in items {
+            match (item.as_str(), line_key) {
+                (Some(x), "") if x.contains(':') => lines.push(x.to_string()),
+                (Some(x), "") => return Err(invalid(&format!("illegal rule '{}'", x))),
+                (Some(x), _) => lines.push(format!("{}: {}", line_key, x.replace('\n', " "))),
+                (None, _) => return Err(invalid(&format!("'{}' should hold strings", key))),
+            }
+        }
+    }
+
+    lines.extend(grid.lines().map(String::from));
+
+    SudokuTable::parse_unchecked(lines.into_iter(), mode)
+}
+
+pub fn puzzle_to_json(table: &SudokuTable) -> Result<JsonValue, SudokuError> {
+    let metadata = table.metadata();
+
+    Ok(JsonValue::object(vec![
+        ("size", JsonValue::from(9usize)),
+        ("grid", JsonValue::from(table.to_single_line())),
+        ("rules", JsonValue::from(document::rule_lines(table)?)),
+        ("title", JsonValue::from(metadata.title.clone())),
+        ("author", JsonValue::from(metadata.author.clone())),
+        ("description", JsonValue::from(metadata.description.clone())),
+        ("difficulty", JsonValue::from(metadata.difficulty.clone())),
+        ("comments", JsonValue::from(metadata.comments.clone())),
+    ]))
+}
+
+/// The solving result, with the solutions found up to `limit`. `limited` tells that the search
+/// stopped at the limit, so there may be more solutions.
+pub fn solve_result_to_json(
+    puzzle: &SudokuTable,
+    solutions: &[SudokuTable],
+    limit: usize,
+    stats: SolverStats,
+    elapsed: Duration,
+) -> Result<JsonValue, SudokuError> {
+    let status = status_from_count(solutions.len());
+    let is_limited = solutions.len() >= limit;
+    let solutions: Vec<String> = solutions.iter().map(|x| x.to_single_line()).collect();
+
+    Ok(JsonValue::object(vec![
+        ("puzzle", puzzle_to_json(puzzle)?),
+        ("status", JsonValue::from(status.name())),
+        ("count", JsonValue::from(solutions.len())),
+        ("limited", JsonValue::from(is_limited)),
+        ("unique", JsonValue::from(solutions.len() == 1)),
+        ("solutions", JsonValue::from(solutions)),
+        ("stats", stats_to_json(stats, elapsed)),
+    ]))
+}
+
+pub fn solution_to_json(index: usize, solution: &SudokuTable, elapsed: Duration) -> JsonValue {
+    JsonValue::object(vec![
+        ("index", JsonValue::from(index)),
+        ("solution", JsonValue::from(solution.to_single_line())),
+        ("elapsed_us", JsonValue::from(elapsed.as_micros() as u64)),
+    ])
+}
+
+pub fn stream_summary_to_json(count: usize, stats: SolverStats, elapsed: Duration) -> JsonValue {
+    JsonValue::object(vec![(
+        "summary",
+        JsonValue::object(vec![
+            ("status", JsonValue::from(status_from_count(count).name())),
+            ("count", JsonValue::from(count)),
+            ("unique", JsonValue::from(count == 1)),
+            ("stats", stats_to_json(stats, elapsed)),
+        ]),
+    )])
+}
+
+fn status_from_count(count: usize) -> PuzzleStatus {
+    match count {
+        0 => PuzzleStatus::Unsolvable,
+        1 => PuzzleStatus::Unique,
+        _ => PuzzleStatus::Multiple,
+    }
+}
+
+pub fn stats_to_json(stats: SolverStats, elapsed: Duration) -> JsonValue {
+    JsonValue::object(vec![
+        ("nodes_explored", JsonValue::from(stats.nodes_explored)),
+        ("backtracks", JsonValue::from(stats.backtracks)),
+        ("elapsed_us", JsonValue::from(elapsed.as_micros() as u64)),
+    ])
+}
+
+pub fn report_to_json(report: &PuzzleReport) -> JsonValue {
+    let solution = report.solution.as_ref().map(|x| x.to_single_line());
+    let mut entries = vec![
+        ("index", JsonValue::from(report.index)),
+        ("line", JsonValue::from(report.line_number)),
+        ("puzzle", JsonValue::from(report.puzzle.as_str())),
+        ("difficulty", JsonValue::from(report.difficulty.clone())),
+        ("status", JsonValue::from(report.status.name())),
+        ("count", JsonValue::from(report.solution_count)),
+        ("solution", JsonValue::from(solution)),
+        ("stats", stats_to_json(report.stats, report.elapsed)),
+    ];
+
+    if let PuzzleStatus::Invalid(e) = &report.status {
+        entries.push(("error", error_to_json(e)));
+    }
+
+    JsonValue::object(entries)
+}
+
+pub fn summary_to_json(summary: &BatchSummary) -> JsonValue {
+    JsonValue::object(vec![(
+        "summary",
+        JsonValue::object(vec![
+            ("total", JsonValue::from(summary.total())),
+            ("unique", JsonValue::from(summary.unique)),
+            ("multiple", JsonValue::from(summary.multiple)),
+            ("unsolvable", JsonValue::from(summary.unsolvable)),
+            ("invalid", JsonValue::from(summary.invalid)),
+            (
+                "elapsed_us",
+                JsonValue::from(summary.elapsed.as_micros() as u64),
+            ),
+        ]),
+    )])
+}
+
+pub fn error_to_json(error: &SudokuError) -> JsonValue {
+    let mut entries = vec![
+        ("kind", JsonValue::from(error_kind(error))),
+        ("message", JsonValue::from(error.to_string())),
+    ];
+
+    match error {
+        SudokuError::WrongLineCount { found } => {
+            entries.push(("found", JsonValue::from(*found)));
+        }
+        SudokuError::WrongLineLength { line, length } => {
+            entries.push(("line", JsonValue::from(*line)));
+            entries.push(("length", JsonValue::from(*length)));
+        }
+        SudokuError::IllegalCharacter {
+            line,
+            column,
+            character,
+        } => {
+            entries.push(("line", JsonValue::from(*line)));
+            entries.push(("column", JsonValue::from(*column)));
+            entries.push(("character", JsonValue::from(character.to_string())));
+        }
+        SudokuError::RuleConflict { cells, conflicts } => {
+            entries.push(("cells", cells_to_json(cells)));
+            entries.push((
+                "conflicts",
+                JsonValue::Array(conflicts.iter().map(conflict_to_json).collect()),
+            ));
+        }
+        SudokuError::Contradiction { contradictions } => {
+            entries.push((
+                "contradictions",
+                JsonValue::Array(contradictions.iter().map(contradiction_to_json).collect()),
+            ));
+        }
+        SudokuError::InvalidRule { line, .. } => {
+            entries.push(("line", JsonValue::from(*line)));
+        }
+        SudokuError::NoCandidates { cell } => {
+            entries.push(("cells", cells_to_json(&[*cell])));
+        }
+        _ => (),
+    }
+
+    JsonValue::object(vec![("error", JsonValue::object(entries))])
+}
+
+fn error_kind(error: &SudokuError) -> &'static str {
+    match error {
+        SudokuError::Io(_) => "io",
+        SudokuError::NoInput => "no_input",
+        SudokuError::WrongLineCount { .. } => "wrong_line_count",
+        SudokuError::BlockTooLong { .. } => "block_too_long",
+        SudokuError::WrongLineLength { .. } => "wrong_line_length",
+        SudokuError::IllegalCharacter { .. } => "illegal_character",
+        SudokuError::RuleConflict { .. } => "rule_conflict",
+        SudokuError::Contradiction { .. } => "contradiction",
+        SudokuError::InvalidRule { .. } => "invalid_rule",
+        SudokuError::Import(_) => "import",
+        SudokuError::UnwritableConstraint => "unwritable_constraint",
+        SudokuError::NoCandidates { .. } => "no_candidates",
+    }
+}
+
+fn cells_to_json(cells: &[CellLocation]) -> JsonValue {
+    JsonValue::from(cells.iter().map(|x| x.to_string()).collect::<Vec<String>>())
+}
+
+fn house_to_json(source: &ConflictSource) -> JsonValue {
+    let (kind, index) = match source {
+        ConflictSource::Row(i) => ("row", i + 1),
+        ConflictSource::Column(i) => ("column", i + 1),
+        ConflictSource::Box(i) => ("box", i + 1),
+        ConflictSource::Region(i) => ("region", i + 1),
+        ConflictSource::ExtraHouse(i) => ("extra_house", i + 1),
+        ConflictSource::Constraint(name) => {
+            return JsonValue::object(vec![
+                ("kind", JsonValue::from("rule")),
+                ("name", JsonValue::from(name.as_str())),
+            ])
+        }
+    };
+
+    JsonValue::object(vec![
+        ("kind", JsonValue::from(kind)),
+        ("index", JsonValue::from(index)),
+    ])
+}
+
+fn conflict_to_json(conflict: &Conflict) -> JsonValue {
+    JsonValue::object(vec![
+        ("house", house_to_json(&conflict.source)),
+        ("digit", JsonValue::from(conflict.digit.map(|x| x as usize))),
+        ("cells", cells_to_json(&conflict.cells)),
+    ])
+}
+
+fn contradiction_to_json(contradiction: &Contradiction) -> JsonValue {
+    match contradiction {
+        Contradiction::DeadCell(cell) => JsonValue::object(vec![
+            ("kind", JsonValue::from("dead_cell")),
+            ("cells", cells_to_json(&[*cell])),
+        ]),
+        Contradiction::DeadDigit {
+            source,
+            digit,
+            cells,
+        } => JsonValue::object(vec![
+            ("kind", JsonValue::from("dead_digit")),
+            ("house", house_to_json(source)),
+            ("digit", JsonValue::from(*digit as usize)),
+            ("cells", cells_to_json(cells)),
+        ]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::json::JsonValue;
+    use super::super::sudoku::{ParseMode, SudokuTable};
+    use std::time::Duration;
+
+    use super::super::sudoku::solver::{SolverStats, SudokuSolver};
+    use super::{error_to_json, puzzle_from_json, puzzle_to_json};
+    use super::{solution_to_json, solve_result_to_json, stream_summary_to_json};
+
+    #[test]
+    fn puzzle_round_trip() {
+        let puzzle = r#"{
+            "grid": "XX1XXXXX2XXXX34XXXX5XXX1XX6X2X6XXXX3X3XXXXX5X7XXXX8X9X9XX4XXX3XXXX71XXXX8XXXXX4XX",
+            "title": "Test",
+            "comments": ["first", "second"],
+            "rules": ["cage: 12 r1c1 r1c2"]
+        }"#;
+
+        let table =
+            puzzle_from_json(&JsonValue::parse(puzzle).unwrap(), ParseMode::Strict).unwrap();
+
+        assert_eq!(
+            puzzle_to_json(&table).unwrap().to_string(),
+            r#"{"size":9,"grid":"..1.....2....34....5...1..6.2.6....3.3.....5.7....8.9.9..4...3....71....8.....4..","rules":["cage: 12 r1c1 r1c2"],"title":"Test","author":null,"description":null,"difficulty":null,"comments":["first","second"]}"#
+        );
+    }
+
+    #[test]
+    fn error_details() {
+        let grid =
+            "55XXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXX";
+        let table =
+            SudokuTable::parse_unchecked([String::from(grid)].into_iter(), ParseMode::Strict)
+                .unwrap();
+        let error = error_to_json(&table.validate().unwrap_err());
+        let error = error.get("error").unwrap();
+
+        assert_eq!(error.get("kind").unwrap().as_str(), Some("rule_conflict"));
+        assert_eq!(
+            error.get("conflicts").unwrap().to_string(),
+            r#"[{"house":{"kind":"row","index":1},"digit":5,"cells":["r1c1","r1c2"]},{"house":{"kind":"box","index":1},"digit":5,"cells":["r1c1","r1c2"]}]"#
+        );
+    }
+
+    #[test]
+    fn solution_lines() {
+        let grid =
+            "XX1XXXXX2XXXX34XXXX5XXX1XX6X2X6XXXX3X3XXXXX5X7XXXX8X9X9XX4XXX3XXXX71XXXX8XXXXX4XX";
+        let table =
+            SudokuTable::parse_unchecked([String::from(grid)].into_iter(), ParseMode::Strict)
+                .unwrap();
+        let solution = SudokuSolver::new(&table).next().unwrap();
+        let stats = SolverStats {
+            nodes_explored: 10,
+            backtracks: 4,
+        };
+
+        assert_eq!(
+            solution_to_json(1, &solution, Duration::from_micros(250)).to_string(),
+            r#"{"index":1,"solution":"391867542286534719457291386129645873638179254745328691972486135564713928813952467","elapsed_us":250}"#
+        );
+        assert_eq!(
+            stream_summary_to_json(1, stats, Duration::from_micros(300)).to_string(),
+            r#"{"summary":{"status":"unique","count":1,"unique":true,"stats":{"nodes_explored":10,"backtracks":4,"elapsed_us":300}}}"#
+        );
+        let empty =
+            SudokuTable::parse_unchecked([".".repeat(81)].into_iter(), ParseMode::Strict).unwrap();
+        let solutions: Vec<SudokuTable> = SudokuSolver::new(&empty).take(2).collect();
+        let result = solve_result_to_json(&empty, &solutions, 2, stats, Duration::ZERO).unwrap();
+        assert_eq!(result.get("count").and_then(|x| x.as_f64()), Some(2.0));
+        assert_eq!(result.get("limited").and_then(|x| x.as_bool()), Some(true));
+    }
+}
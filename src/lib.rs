@@ -1,4 +1,30 @@
 mod app;
+mod batch;
+mod booklet;
+mod csv;
+mod error;
+mod hodoku;
+mod input;
+mod json;
+mod json_format;
+mod png;
+mod sadman;
+mod scan;
 mod sudoku;
 
-pub use app::{App, AppConfig};
+pub use app::{App, AppConfig, OutputFormat};
+pub use batch::DEFAULT_SOLUTION_LIMIT;
+pub use booklet::DEFAULT_PUZZLES_PER_PAGE;
+pub use error::SudokuError;
+pub use scan::{CellScan, ScanResult};
+pub use sudoku::conflict::{Conflict, ConflictSource};
+pub use sudoku::consistency::Contradiction;
+pub use sudoku::constraint::Constraint;
+pub use sudoku::drawing::{Color, DrawOptions, Drawing, Point, Shape};
+pub use sudoku::metadata::Metadata;
+pub use sudoku::pencil_marks::{Hint, PencilMarks};
+pub use sudoku::raster::{Image, RasterOptions};
+pub use sudoku::render::{Canvas, Corner, Side};
+pub use sudoku::solver::{SolverStats, SudokuSolver};
+pub use sudoku::tikz;
+pub use sudoku::{CellLocation, MarkedTable, ParseMode, SudokuCell, SudokuTable};
@@ -1,16 +1,24 @@
 use std::env;
 use std::process::exit;
 
-use sudoku_solver::{App, AppConfig};
+use sudoku_solver::{App, AppConfig, CellLocation, OutputFormat};
 
 fn main() {
-    let app_config = parse_args();
+    let app_config = match parse_args() {
+        Ok(x) => x,
+        Err(message) => exit_with_error_message(&message),
+    };
+    let format = app_config.format;
     let app = App::new(app_config);
 
     let result = app.run();
 
     if let Err(e) = result {
-        exit_with_error_message(&e);
+        // In JSON modes, the app has already written the error on the standard output.
+        match format {
+            OutputFormat::Json | OutputFormat::Ndjson => exit(1),
+            _ => exit_with_error_message(&e.to_string()),
+        }
     }
 }
 
@@ -19,18 +27,91 @@ fn exit_with_error_message(message: &str) -> ! {
     exit(1)
 }
 
-fn parse_args() -> AppConfig {
-    let mut file_name: Option<String> = None;
-    let mut print_version = false;
+fn parse_args() -> Result<AppConfig, String> {
+    let mut config = AppConfig::default();
+    let mut args = env::args().skip(1);
 
-    for arg in env::args().skip(1) {
+    while let Some(arg) = args.next() {
         if arg == "--version" {
-            print_version = true;
+            config.print_version = true;
             break;
-        } else if file_name.is_none() {
-            file_name = Some(arg);
+        } else if arg == "--strict" {
+            config.strict = true;
+        } else if arg == "--batch" {
+            config.batch = true;
+        } else if arg == "--show-conflicts" {
+            config.show_conflicts = true;
+        } else if arg == "--show-solution" {
+            config.show_solution = true;
+        } else if arg == "--hint" {
+            config.hint = true;
+        } else if arg == "--pencil-marks" {
+            config.pencil_marks = true;
+        } else if arg == "--format" {
+            let name = args.next().unwrap_or_default();
+            config.format = OutputFormat::from_name(&name)
+                .ok_or_else(|| format!("Unknown output format '{}'", name))?;
+        } else if arg == "--limit" {
+            config.solution_limit = args
+                .next()
+                .and_then(|x| x.parse().ok())
+                .filter(|x| *x >= 2)
+                .ok_or_else(|| String::from("--limit expects a number of at least 2"))?;
+        } else if arg == "--highlight" {
+            config.highlighted = args
+                .next()
+                .unwrap_or_default()
+                .split(',')
+                .map(|x| x.trim().parse::<CellLocation>())
+                .collect::<Result<_, _>>()?;
+        } else if arg == "--cell-size" {
+            config.cell_size = args
+                .next()
+                .and_then(|x| x.parse().ok())
+                .filter(|x| (4.0..=1000.0).contains(x))
+                .ok_or_else(|| {
+                    String::from("--cell-size expects a number of pixels from 4 to 1000")
+                })?;
+        } else if arg == "--font-size" {
+            config.font_size = Some(
+                args.next()
+                    .and_then(|x| x.parse().ok())
+                    .filter(|x: &f64| *x > 0.0)
+                    .ok_or_else(|| {
+                        String::from("--font-size expects a positive number of pixels")
+                    })?,
+            );
+        } else if arg == "--per-page" {
+            config.per_page = args
+                .next()
+                .and_then(|x| x.parse().ok())
+                .filter(|x| *x >= 1)
+                .ok_or_else(|| String::from("--per-page expects a positive number"))?;
+        } else if config.file_name.is_none() {
+            config.file_name = Some(arg);
         }
     }
 
-    AppConfig::new(file_name, print_version)
+    if let (
+        true,
+        OutputFormat::Svg
+        | OutputFormat::Png
+        | OutputFormat::PencilMarks
+        | OutputFormat::Sdk
+        | OutputFormat::Sdx
+        | OutputFormat::HodokuLibrary
+        | OutputFormat::Hsol,
+    ) = (config.batch, config.format)
+    {
+        return Err(String::from(
+            "--format svg, png, pencil-marks, sdk, sdx, hodoku-library and hsol write a single \
+            puzzle, and cannot be used with --batch",
+        ));
+    }
+
+    if config.batch && config.hint {
+        return Err(String::from("--hint cannot be used with --batch"));
+    }
+
+    Ok(config)
 }
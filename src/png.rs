@@ -0,0 +1,676 @@
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+const TOO_MUCH_DATA: &str = "more image data than the image holds";
+
+const WINDOW_SIZE: usize = 32768;
+const HASH_BITS: u32 = 15;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+const MAX_CHAIN: usize = 64;
+
+const LENGTH_BASES: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA_BITS: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DISTANCE_BASES: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA_BITS: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+/// Encodes 8-bit RGB pixels, row by row, as a PNG file.
+pub fn encode(width: u32, height: u32, pixels: &[u8]) -> Vec<u8> {
+    let row_size = width as usize * 3;
+    let mut raw = Vec::with_capacity((row_size + 1) * height as usize);
+
+    // Each row starts with its filter type. Rows are left unfiltered, so that the long runs of
+    // background pixels compress well.
+    for row in pixels.chunks(row_size) {
+        raw.push(0);
+        raw.extend_from_slice(row);
+    }
+
+    let mut header = vec![];
+    header.extend(width.to_be_bytes());
+    header.extend(height.to_be_bytes());
+    // 8 bits per channel, truecolor, deflate, adaptive filtering, no interlacing.
+    header.extend([8, 2, 0, 0, 0]);
+
+    let mut png = SIGNATURE.to_vec();
+    write_chunk(&mut png, b"IHDR", &header);
+    write_chunk(&mut png, b"IDAT", &zlib(&raw));
+    write_chunk(&mut png, b"IEND", &[]);
+
+    png
+}
+
+fn write_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend((data.len() as u32).to_be_bytes());
+    png.extend(kind);
+    png.extend(data);
+
+    let crc = crc32(kind.iter().chain(data));
+    png.extend(crc.to_be_bytes());
+}
+
+fn crc32<'a>(bytes: impl IntoIterator<Item = &'a u8>) -> u32 {
+    let mut crc = !0u32;
+
+    for byte in bytes {
+        crc ^= *byte as u32;
+
+        for _ in 0..8 {
+            crc = match crc & 1 {
+                1 => (crc >> 1) ^ 0xedb8_8320,
+                _ => crc >> 1,
+            };
+        }
+    }
+
+    !crc
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+
+    for chunk in bytes.chunks(5552) {
+        for byte in chunk {
+            a += *byte as u32;
+            b += a;
+        }
+
+        a %= 65521;
+        b %= 65521;
+    }
+
+    (b << 16) | a
+}
+
+/// Wraps the data in a zlib stream, made of a single deflate block with the fixed codes.
+fn zlib(data: &[u8]) -> Vec<u8> {
+    let mut bits = BitWriter::default();
+
+    // The final block, compressed with the fixed Huffman codes.
+    bits.write(1, 1);
+    bits.write(1, 2);
+
+    let mut head = vec![usize::MAX; 1 << HASH_BITS];
+    let mut previous = vec![usize::MAX; WINDOW_SIZE];
+    let mut position = 0;
+
+    while position < data.len() {
+        let (length, distance) = longest_match(data, position, &head, &previous);
+
+        match length >= MIN_MATCH {
+            true => {
+                write_length(&mut bits, length);
+                write_distance(&mut bits, distance);
+            }
+            false => write_literal(&mut bits, data[position] as u16),
+        }
+
+        for i in position..position + length.max(1) {
+            if i + MIN_MATCH <= data.len() {
+                let hash = hash(&data[i..]);
+                previous[i % WINDOW_SIZE] = head[hash];
+                head[hash] = i;
+            }
+        }
+
+        position += length.max(1);
+    }
+
+    write_literal(&mut bits, 256);
+
+    let mut stream = vec![0x78, 0x01];
+    stream.extend(bits.finish());
+    stream.extend(adler32(data).to_be_bytes());
+    stream
+}
+
+fn hash(bytes: &[u8]) -> usize {
+    let value = (bytes[0] as u32) << 16 | (bytes[1] as u32) << 8 | bytes[2] as u32;
+    (value.wrapping_mul(2_654_435_761) >> (32 - HASH_BITS)) as usize
+}
+
+fn longest_match(
+    data: &[u8],
+    position: usize,
+    head: &[usize],
+    previous: &[usize],
+) -> (usize, usize) {
+    if position + MIN_MATCH > data.len() {
+        return (0, 0);
+    }
+
+    let max_length = MAX_MATCH.min(data.len() - position);
+    let mut best = (0, 0);
+    let mut candidate = head[hash(&data[position..])];
+
+    for _ in 0..MAX_CHAIN {
+        if candidate == usize::MAX || position - candidate > WINDOW_SIZE {
+            break;
+        }
+
+        let length = data[candidate..]
+            .iter()
+            .zip(&data[position..position + max_length])
+            .take_while(|(x, y)| x == y)
+            .count();
+
+        if length > best.0 {
+            best = (length, position - candidate);
+
+            if length == max_length {
+                break;
+            }
+        }
+
+        let next = previous[candidate % WINDOW_SIZE];
+        if next == usize::MAX || next >= candidate {
+            break;
+        }
+        candidate = next;
+    }
+
+    best
+}
+
+fn write_literal(bits: &mut BitWriter, symbol: u16) {
+    let (code, length) = match symbol {
+        0..=143 => (0x30 + symbol, 8),
+        144..=255 => (0x190 + symbol - 144, 9),
+        256..=279 => (symbol - 256, 7),
+        _ => (0xc0 + symbol - 280, 8),
+    };
+
+    bits.write_code(code as u32, length);
+}
+
+fn write_length(bits: &mut BitWriter, length: usize) {
+    let index = LENGTH_BASES
+        .iter()
+        .rposition(|x| *x as usize <= length)
+        .unwrap_or(0);
+
+    write_literal(bits, 257 + index as u16);
+    bits.write(
+        (length - LENGTH_BASES[index] as usize) as u32,
+        LENGTH_EXTRA_BITS[index],
+    );
+}
+
+fn write_distance(bits: &mut BitWriter, distance: usize) {
+    let index = DISTANCE_BASES
+        .iter()
+        .rposition(|x| *x as usize <= distance)
+        .unwrap_or(0);
+
+    bits.write_code(index as u32, 5);
+    bits.write(
+        (distance - DISTANCE_BASES[index] as usize) as u32,
+        DISTANCE_EXTRA_BITS[index],
+    );
+}
+
+/// Packs bits into bytes, starting from the least significant bit, as deflate does.
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    count: u8,
+}
+
+impl BitWriter {
+    fn write(&mut self, value: u32, count: u8) {
+        for i in 0..count {
+            self.buffer |= ((value >> i) & 1) << self.count;
+            self.count += 1;
+
+            if self.count == 8 {
+                self.bytes.push(self.buffer as u8);
+                self.buffer = 0;
+                self.count = 0;
+            }
+        }
+    }
+
+    /// Huffman codes are packed starting from their most significant bit.
+    fn write_code(&mut self, code: u32, length: u8) {
+        let reversed = code.reverse_bits() >> (32 - length as u32);
+        self.write(reversed, length);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+
+        self.bytes
+    }
+}
+
+/// Decodes a PNG file into 8-bit RGB pixels, row by row. Transparent pixels are laid over
+/// white, and 16-bit samples are reduced to 8 bits.
+pub fn decode(png: &[u8]) -> Result<(u32, u32, Vec<u8>), String> {
+    if !png.starts_with(&SIGNATURE) {
+        return Err(String::from("not a PNG file"));
+    }
+
+    let mut position = SIGNATURE.len();
+    let mut header = None;
+    let mut palette: &[u8] = &[];
+    let mut data = vec![];
+
+    while position + 8 <= png.len() {
+        let length = u32::from_be_bytes(png[position..position + 4].try_into().unwrap()) as usize;
+        let kind = &png[position + 4..position + 8];
+        let chunk = png
+            .get(position + 8..position + 8 + length)
+            .ok_or("truncated chunk")?;
+
+        match kind {
+            b"IHDR" if chunk.len() == 13 => header = Some(chunk),
+            b"PLTE" => palette = chunk,
+            b"IDAT" => data.extend_from_slice(chunk),
+            b"IEND" => break,
+            _ => (),
+        }
+
+        position += length + 12;
+    }
+
+    let header = header.ok_or("missing header")?;
+    let width = u32::from_be_bytes(header[0..4].try_into().unwrap());
+    let height = u32::from_be_bytes(header[4..8].try_into().unwrap());
+    let (depth, color_type, interlace) = (header[8], header[9], header[12]);
+    let channels = match color_type {
+        0 | 3 => 1,
+        2 => 3,
+        4 => 2,
+        6 => 4,
+        _ => return Err(format!("unknown color type {}", color_type)),
+    };
+
+    if interlace != 0 {
+        return Err(String::from("interlaced images are not supported"));
+    }
+    if !matches!(depth, 1 | 2 | 4 | 8 | 16) || (depth < 8 && channels > 1) {
+        return Err(format!("unsupported bit depth {}", depth));
+    }
+    if width == 0 || height == 0 || width as u64 * height as u64 > 1 << 28 {
+        return Err(String::from("unsupported image size"));
+    }
+
+    let bits_per_pixel = channels * depth as usize;
+    let row_size = (width as usize * bits_per_pixel).div_ceil(8);
+    // Each row starts with its filter type.
+    let image_size = (row_size + 1) * height as usize;
+    let raw = inflate(data.get(2..).ok_or("missing image data")?, image_size)?;
+    let rows = unfilter(&raw, row_size, bits_per_pixel.div_ceil(8), height as usize)?;
+
+    let mut pixels = Vec::with_capacity(width as usize * height as usize * 3);
+
+    for row in rows.chunks(row_size) {
+        for x in 0..width as usize {
+            let sample = |channel: usize| -> u16 {
+                let bit = (x * channels + channel) * depth as usize;
+
+                match depth {
+                    16 => row[bit / 8] as u16,
+                    8 => row[bit / 8] as u16,
+                    _ => {
+                        (row[bit / 8] >> (8 - depth as usize - bit % 8)) as u16 & ((1 << depth) - 1)
+                    }
+                }
+            };
+            let scale = |value: u16| match depth {
+                1 | 2 | 4 => (value * 255 / ((1 << depth) - 1)) as u8,
+                _ => value as u8,
+            };
+
+            let (rgb, alpha) = match color_type {
+                0 => ([scale(sample(0)); 3], 255),
+                2 => ([sample(0) as u8, sample(1) as u8, sample(2) as u8], 255),
+                3 => {
+                    let index = sample(0) as usize * 3;
+                    let color = palette
+                        .get(index..index + 3)
+                        .ok_or("missing palette entry")?;
+                    ([color[0], color[1], color[2]], 255)
+                }
+                4 => ([sample(0) as u8; 3], sample(1) as u8),
+                _ => (
+                    [sample(0) as u8, sample(1) as u8, sample(2) as u8],
+                    sample(3) as u8,
+                ),
+            };
+
+            for value in rgb {
+                let value = value as u32 * alpha as u32 + 255 * (255 - alpha as u32);
+                pixels.push((value / 255) as u8);
+            }
+        }
+    }
+
+    Ok((width, height, pixels))
+}
+
+fn unfilter(
+    raw: &[u8],
+    row_size: usize,
+    pixel_size: usize,
+    height: usize,
+) -> Result<Vec<u8>, String> {
+    if raw.len() < (row_size + 1) * height {
+        return Err(String::from("truncated image data"));
+    }
+
+    let mut rows = vec![0; row_size * height];
+
+    for y in 0..height {
+        let start = y * (row_size + 1);
+        let filter = *raw.get(start).ok_or("truncated image data")?;
+        let line = raw
+            .get(start + 1..start + 1 + row_size)
+            .ok_or("truncated image data")?;
+        let (done, current) = rows.split_at_mut(y * row_size);
+        let previous = match y {
+            0 => None,
+            _ => Some(&done[(y - 1) * row_size..]),
+        };
+        let current = &mut current[..row_size];
+
+        for x in 0..row_size {
+            let left = match x >= pixel_size {
+                true => current[x - pixel_size] as i16,
+                false => 0,
+            };
+            let up = previous.map_or(0, |row| row[x] as i16);
+            let up_left = match x >= pixel_size {
+                true => previous.map_or(0, |row| row[x - pixel_size] as i16),
+                false => 0,
+            };
+            let prediction = match filter {
+                0 => 0,
+                1 => left,
+                2 => up,
+                3 => (left + up) / 2,
+                4 => paeth(left, up, up_left),
+                _ => return Err(format!("unknown filter type {}", filter)),
+            };
+
+            current[x] = line[x].wrapping_add(prediction as u8);
+        }
+    }
+
+    Ok(rows)
+}
+
+fn paeth(left: i16, up: i16, up_left: i16) -> i16 {
+    let estimate = left + up - up_left;
+    let (a, b, c) = (
+        (estimate - left).abs(),
+        (estimate - up).abs(),
+        (estimate - up_left).abs(),
+    );
+
+    match (a <= b && a <= c, b <= c) {
+        (true, _) => left,
+        (false, true) => up,
+        (false, false) => up_left,
+    }
+}
+
+/// Decompresses a raw deflate stream, which must not hold more than `limit` bytes.
+fn inflate(data: &[u8], limit: usize) -> Result<Vec<u8>, String> {
+    let mut bits = BitReader { data, position: 0 };
+    let mut output = vec![];
+
+    loop {
+        let is_final = bits.read(1)? == 1;
+
+        match bits.read(2)? {
+            0 => {
+                let start = bits.position.div_ceil(8);
+                let header = data.get(start..start + 4).ok_or("truncated stored block")?;
+                let length = u16::from_le_bytes([header[0], header[1]]) as usize;
+                let block = data
+                    .get(start + 4..start + 4 + length)
+                    .ok_or("truncated stored block")?;
+
+                if output.len() + block.len() > limit {
+                    return Err(String::from(TOO_MUCH_DATA));
+                }
+
+                output.extend_from_slice(block);
+                bits.position = (start + 4 + length) * 8;
+            }
+            1 => {
+                let mut lengths = [8; 288];
+                lengths[144..256].fill(9);
+                lengths[256..280].fill(7);
+
+                inflate_block(
+                    &mut bits,
+                    &mut output,
+                    limit,
+                    &Huffman::new(&lengths),
+                    &Huffman::new(&[5; 30]),
+                )?;
+            }
+            2 => {
+                let (literals, distances) = read_dynamic_codes(&mut bits)?;
+                inflate_block(&mut bits, &mut output, limit, &literals, &distances)?;
+            }
+            _ => return Err(String::from("invalid block type")),
+        }
+
+        if is_final {
+            return Ok(output);
+        }
+    }
+}
+
+fn read_dynamic_codes(bits: &mut BitReader) -> Result<(Huffman, Huffman), String> {
+    const ORDER: [usize; 19] = [
+        16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+    ];
+
+    let literal_count = bits.read(5)? as usize + 257;
+    let distance_count = bits.read(5)? as usize + 1;
+    let code_length_count = bits.read(4)? as usize + 4;
+
+    let mut code_lengths = [0; 19];
+    for i in ORDER.iter().take(code_length_count) {
+        code_lengths[*i] = bits.read(3)? as u8;
+    }
+
+    let code_lengths = Huffman::new(&code_lengths);
+    let mut lengths = vec![];
+
+    while lengths.len() < literal_count + distance_count {
+        let (value, repeat) = match code_lengths.decode(bits)? {
+            symbol @ 0..=15 => (symbol as u8, 1),
+            16 => (
+                *lengths
+                    .last()
+                    .ok_or("repeated length without a previous one")?,
+                3 + bits.read(2)?,
+            ),
+            17 => (0, 3 + bits.read(3)?),
+            _ => (0, 11 + bits.read(7)?),
+        };
+
+        lengths.extend(std::iter::repeat_n(value, repeat as usize));
+    }
+
+    if lengths.len() > literal_count + distance_count {
+        return Err(String::from("too many code lengths"));
+    }
+
+    Ok((
+        Huffman::new(&lengths[..literal_count]),
+        Huffman::new(&lengths[literal_count..]),
+    ))
+}
+
+fn inflate_block(
+    bits: &mut BitReader,
+    output: &mut Vec<u8>,
+    limit: usize,
+    literals: &Huffman,
+    distances: &Huffman,
+) -> Result<(), String> {
+    loop {
+        let symbol = literals.decode(bits)? as usize;
+
+        match symbol {
+            0..=255 if output.len() >= limit => return Err(String::from(TOO_MUCH_DATA)),
+            0..=255 => output.push(symbol as u8),
+            256 => return Ok(()),
+            257..=285 => {
+                let index = symbol - 257;
+                let length =
+                    LENGTH_BASES[index] as usize + bits.read(LENGTH_EXTRA_BITS[index])? as usize;
+                let index = distances.decode(bits)? as usize;
+                let distance = *DISTANCE_BASES.get(index).ok_or("invalid distance code")? as usize
+                    + bits.read(DISTANCE_EXTRA_BITS[index])? as usize;
+
+                if distance > output.len() {
+                    return Err(String::from("distance too far back"));
+                }
+                if output.len() + length > limit {
+                    return Err(String::from(TOO_MUCH_DATA));
+                }
+
+                for _ in 0..length {
+                    output.push(output[output.len() - distance]);
+                }
+            }
+            _ => return Err(String::from("invalid length code")),
+        }
+    }
+}
+
+/// A canonical Huffman code, decoded a bit at a time.
+struct Huffman {
+    /// The number of codes of each length.
+    counts: [u16; 16],
+    /// The symbols, ordered by code.
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Huffman {
+        let mut counts = [0; 16];
+        for length in lengths {
+            counts[*length as usize] += 1;
+        }
+        counts[0] = 0;
+
+        let mut symbols: Vec<u16> = (0..lengths.len() as u16)
+            .filter(|x| lengths[*x as usize] > 0)
+            .collect();
+        symbols.sort_by_key(|x| lengths[*x as usize]);
+
+        Huffman { counts, symbols }
+    }
+
+    fn decode(&self, bits: &mut BitReader) -> Result<u16, String> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+
+        for count in self.counts.iter().skip(1) {
+            code |= bits.read(1)? as i32;
+            let count = *count as i32;
+
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+
+        Err(String::from("invalid Huffman code"))
+    }
+}
+
+/// Reads bits starting from the least significant bit of each byte, as deflate packs them.
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl BitReader<'_> {
+    fn read(&mut self, count: u8) -> Result<u32, String> {
+        let mut value = 0;
+
+        for i in 0..count {
+            let byte = self
+                .data
+                .get(self.position / 8)
+                .ok_or("unexpected end of image data")?;
+            value |= (((byte >> (self.position % 8)) & 1) as u32) << i;
+            self.position += 1;
+        }
+
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{adler32, crc32, decode, encode, inflate};
+
+    #[test]
+    fn checksums() {
+        assert_eq!(crc32(b"IEND"), 0xae42_6082);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+    }
+
+    #[test]
+    fn chunks() {
+        let pixels = [255; 40 * 30 * 3];
+        let png = encode(40, 30, &pixels);
+
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR\0\0\0\x28\0\0\0\x1e\x08\x02"));
+        assert!(png.ends_with(b"\0\0\0\0IEND\xae\x42\x60\x82"));
+        // The white rows are compressed as back references.
+        assert!(png.len() < 200);
+    }
+
+    #[test]
+    fn round_trip() {
+        let pixels: Vec<u8> = (0..17 * 9 * 3).map(|x| (x * 7 % 256) as u8).collect();
+        let (width, height, decoded) = decode(&encode(17, 9, &pixels)).unwrap();
+
+        assert_eq!((width, height), (17, 9));
+        assert_eq!(decoded, pixels);
+    }
+
+    #[test]
+    fn dynamic_codes() {
+        // zlib output for repeated text, compressed with dynamic Huffman codes.
+        let stream = [
+            0x78, 0xda, 0xb5, 0x8a, 0xd9, 0x11, 0x40, 0x30, 0x14, 0x45, 0x5b, 0xb9, 0x2d, 0x24,
+            0x41, 0x18, 0xd5, 0x20, 0x11, 0xfb, 0x23, 0xf1, 0x6c, 0xd5, 0x33, 0x66, 0x94, 0xe0,
+            0xf3, 0x2c, 0x6b, 0x63, 0xb1, 0x70, 0x5b, 0xf5, 0x28, 0x3d, 0xed, 0x13, 0x6a, 0x3a,
+            0xd0, 0xf1, 0x38, 0x07, 0xd0, 0x66, 0x3d, 0xd6, 0x27, 0x0f, 0xc5, 0x75, 0xc2, 0x90,
+            0xcb, 0x5f, 0xfa, 0x67, 0x0e, 0x6c, 0xa8, 0x67, 0x08, 0xa9, 0xa2, 0x38, 0xd1, 0x69,
+            0xf6, 0x89, 0x2c, 0xd5, 0x49, 0x1c, 0x29, 0x29, 0xe0, 0x7c, 0x6b, 0x6e, 0x47, 0x4e,
+            0x3c, 0x13,
+        ];
+        let expected = "the quick brown fox jumps over the lazy dog; ".repeat(3)
+            + "sudoku 123456789 sudoku 987654321 grid";
+
+        assert_eq!(inflate(&stream[2..], 200).unwrap(), expected.as_bytes());
+        assert!(inflate(&stream[2..], expected.len() - 1).is_err());
+    }
+}
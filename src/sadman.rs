@@ -0,0 +1,367 @@
+use super::error::SudokuError;
+use super::input::ParsedPuzzle;
+use super::sudoku::metadata::Metadata;
+use super::sudoku::pencil_marks::PencilMarks;
+use super::sudoku::{CellLocation, ParseMode, SudokuCell, SudokuTable};
+
+/// Reads a SadMan Sudoku `.sdk` file: `#` header lines, the givens after `[Puzzle]`, and the
+/// player's grid, givens included, after `[State]`.
+pub fn read_sdk(lines: &[String]) -> Result<ParsedPuzzle, SudokuError> {
+    let mut warnings = vec![];
+    let mut metadata = Metadata::default();
+    let (mut puzzle, mut state) = (vec![], vec![]);
+    let mut in_state = false;
+
+    for line in lines
+        .iter()
+        .map(|x| x.trim_start_matches('\u{feff}').trim())
+    {
+        if line.is_empty() {
+            continue;
+        } else if line.starts_with('#') {
+            read_header_line(line, &mut metadata, &mut warnings);
+        } else if line.eq_ignore_ascii_case("[Puzzle]") {
+            in_state = false;
+        } else if line.eq_ignore_ascii_case("[State]") {
+            in_state = true;
+        } else if in_state {
+            state.push(line.to_string());
+        } else {
+            puzzle.push(line.to_string());
+        }
+    }
+
+    let mut table = SudokuTable::parse_unchecked(puzzle.into_iter(), ParseMode::Strict)?;
+    *table.metadata_mut() = metadata;
+
+    let pencil_marks = match state.is_empty() {
+        true => None,
+        false => {
+            let state = SudokuTable::parse_unchecked(state.into_iter(), ParseMode::Strict)
+                .map_err(|e| SudokuError::Import(format!("Invalid [State] grid: {}", e)))?;
+            Some(PencilMarks::from_table(&with_givens(&state, &table)))
+        }
+    };
+
+    Ok(ParsedPuzzle {
+        table,
+        pencil_marks,
+        warnings,
+    })
+}
+
+/// Reads a SadMan Sudoku `.sdx` file: `#` header lines, then a row per line with a token per
+/// cell. A given is a digit, a player's value is a digit after `u`, and an empty cell lists its
+/// candidates, a lone one written twice.
+pub fn read_sdx(lines: &[String]) -> Result<ParsedPuzzle, SudokuError> {
+    let mut warnings = vec![];
+    let mut metadata = Metadata::default();
+    let mut tokens = vec![];
+
+    for line in lines
+        .iter()
+        .map(|x| x.trim_start_matches('\u{feff}').trim())
+    {
+        match line.starts_with('#') {
+            true => read_header_line(line, &mut metadata, &mut warnings),
+            false => tokens.extend(line.split_whitespace()),
+        }
+    }
+
+    if tokens.len() != 81 {
+        return Err(SudokuError::Import(format!(
+            "An sdx grid has 81 cells, found {}",
+            tokens.len()
+        )));
+    }
+
+    let mut givens = String::new();
+    let mut state = String::new();
+    let mut candidates = vec![];
+
+    for (i, token) in tokens.iter().enumerate() {
+        let cell = CellLocation::new(i / 9, i % 9);
+        let invalid = || SudokuError::Import(format!("Invalid sdx cell '{}' in {}", token, cell));
+        let digits = token.strip_prefix('u').unwrap_or(token);
+
+        if digits.is_empty() || !digits.chars().all(|x| x.is_ascii_digit()) {
+            return Err(invalid());
+        }
+
+        match (token.starts_with('u'), digits.len()) {
+            (false, 1) => {
+                givens.push_str(digits);
+                state.push_str(digits);
+            }
+            (true, 1) => {
+                givens.push('.');
+                state.push_str(digits);
+            }
+            (false, _) => {
+                givens.push('.');
+                state.push('.');
+
+                let digits: Vec<u8> = digits.bytes().map(|x| x - b'0').collect();
+                if digits.contains(&0) {
+                    return Err(invalid());
+                }
+                candidates.push((cell, digits));
+            }
+            (true, _) => return Err(invalid()),
+        }
+    }
+
+    let mut table = SudokuTable::parse_unchecked([givens].into_iter(), ParseMode::Strict)?;
+    *table.metadata_mut() = metadata;
+    let state = SudokuTable::parse_unchecked([state].into_iter(), ParseMode::Strict)?;
+    let mut pencil_marks = PencilMarks::from_table(&state);
+
+    for (cell, digits) in candidates {
+        pencil_marks.set_candidates(cell, &digits);
+    }
+
+    Ok(ParsedPuzzle {
+        table,
+        pencil_marks: Some(pencil_marks),
+        warnings,
+    })
+}
+
+pub fn write_sdk(table: &SudokuTable, progress: Option<&PencilMarks>) -> String {
+    let mut lines = header_lines(table.metadata());
+    let grid = |table: &SudokuTable| {
+        table
+            .to_single_line()
+            .as_bytes()
+            .chunks(9)
+            .map(|x| String::from_utf8_lossy(x).into_owned())
+            .collect::<Vec<String>>()
+    };
+
+    lines.push(String::from("[Puzzle]"));
+    lines.extend(grid(table));
+
+    if let Some(progress) = progress.filter(|x| has_player_values(table, x)) {
+        lines.push(String::from("[State]"));
+        lines.extend(grid(progress.table()));
+    }
+
+    lines.iter().map(|x| format!("{}\r\n", x)).collect()
+}
+
+/// Writes the puzzle as an `.sdx` file. A lone candidate is written twice, as a single digit
+/// would be read back as a given.
+pub fn write_sdx(
+    table: &SudokuTable,
+    progress: Option<&PencilMarks>,
+) -> Result<String, SudokuError> {
+    let computed;
+    let progress = match progress {
+        Some(x) => x,
+        None => {
+            computed = PencilMarks::from_table(table);
+            &computed
+        }
+    };
+    let mut lines = header_lines(table.metadata());
+
+    for row in 0..9 {
+        let tokens: Vec<String> = (0..9)
+            .map(|col| {
+                let cell = CellLocation::new(row, col);
+
+                match (
+                    table.contents()[row][col],
+                    progress.table().contents()[row][col],
+                ) {
+                    (SudokuCell::Filled(value), _) => Ok(value.to_string()),
+                    (SudokuCell::Empty, SudokuCell::Filled(value)) => Ok(format!("u{}", value)),
+                    (SudokuCell::Empty, SudokuCell::Empty) => {
+                        match &progress.candidates(cell)[..] {
+                            [] => Err(SudokuError::NoCandidates { cell }),
+                            [digit] => Ok(format!("{}{}", digit, digit)),
+                            digits => Ok(digits.iter().map(|x| x.to_string()).collect()),
+                        }
+                    }
+                }
+            })
+            .collect::<Result<_, _>>()?;
+
+        lines.push(tokens.join(" "));
+    }
+
+    Ok(lines.iter().map(|x| format!("{}\r\n", x)).collect())
+}
+
+/// Whether the player has placed values of their own, beyond the givens.
+fn has_player_values(table: &SudokuTable, progress: &PencilMarks) -> bool {
+    table
+        .contents()
+        .iter()
+        .flatten()
+        .zip(progress.table().contents().iter().flatten())
+        .any(|(given, placed)| given != placed)
+}
+
+/// The player's grid, with the givens put back in case they were left out.
+fn with_givens(state: &SudokuTable, givens: &SudokuTable) -> SudokuTable {
+    let mut result = state.clone();
+
+    for (row, cols) in givens.contents().iter().enumerate() {
+        for (col, cell) in cols.iter().enumerate() {
+            if let SudokuCell::Filled(_) = cell {
+                result.contents_mut()[row][col] = *cell;
+            }
+        }
+    }
+
+    result
+}
+
+fn read_header_line(line: &str, metadata: &mut Metadata, warnings: &mut Vec<String>) {
+    let mut chars = line[1..].chars();
+    let tag = chars.next().unwrap_or(' ');
+    let value = chars.as_str().trim().to_string();
+
+    match tag {
+        'A' => metadata.author = Some(value),
+        'D' => metadata.description = Some(value),
+        'C' => metadata.comments.push(value),
+        'L' => metadata.difficulty = Some(value),
+        'B' => metadata.comments.push(format!("published: {}", value)),
+        'S' => metadata.comments.push(format!("source: {}", value)),
+        'U' => metadata.comments.push(format!("source URL: {}", value)),
+        _ => warnings.push(format!("ignoring unknown SadMan header line '{}'", line)),
+    }
+}
+
+fn header_lines(metadata: &Metadata) -> Vec<String> {
+    let single_line = |x: &str| x.lines().collect::<Vec<&str>>().join(" ");
+    let mut lines = vec![];
+
+    if let Some(author) = &metadata.author {
+        lines.push(format!("#A{}", single_line(author)));
+    }
+
+    if let Some(description) = &metadata.description {
+        lines.push(format!("#D{}", single_line(description)));
+    }
+
+    for comment in &metadata.comments {
+        lines.push(format!("#C{}", single_line(comment)));
+    }
+
+    if let Some(difficulty) = &metadata.difficulty {
+        lines.push(format!("#L{}", single_line(difficulty)));
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::error::SudokuError;
+    use super::super::sudoku::pencil_marks::PencilMarks;
+    use super::super::sudoku::{CellLocation, ParseMode, SudokuCell, SudokuTable};
+    use super::{read_sdk, read_sdx, write_sdk, write_sdx};
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(String::from).collect()
+    }
+
+    const SDK: &str = "#ASadMan Software\r\n\
+        #DA random puzzle\r\n\
+        #CJust start plugging in the numbers\r\n\
+        #LVery Easy\r\n\
+        [Puzzle]\r\n\
+        ..1.....2\r\n\
+        ....34...\r\n\
+        .5...1..6\r\n\
+        .2.6....3\r\n\
+        .3.....5.\r\n\
+        7....8.9.\r\n\
+        9..4...3.\r\n\
+        ...71....\r\n\
+        8.....4..\r\n\
+        [State]\r\n\
+        3.1.....2\r\n\
+        ....34...\r\n\
+        .5...1..6\r\n\
+        .2.6....3\r\n\
+        .3.....5.\r\n\
+        7....8.9.\r\n\
+        9..4...3.\r\n\
+        ...71....\r\n\
+        8.....4..\r\n";
+
+    #[test]
+    fn sdk_files() {
+        let parsed = read_sdk(&lines(SDK)).unwrap();
+        let metadata = parsed.table.metadata();
+        let progress = parsed.pencil_marks.as_ref().unwrap();
+
+        assert_eq!(metadata.author.as_deref(), Some("SadMan Software"));
+        assert_eq!(metadata.description.as_deref(), Some("A random puzzle"));
+        assert_eq!(metadata.comments, ["Just start plugging in the numbers"]);
+        assert_eq!(metadata.difficulty.as_deref(), Some("Very Easy"));
+        assert_eq!(parsed.table.contents()[0][0], SudokuCell::Empty);
+        assert_eq!(progress.table().contents()[0][0], SudokuCell::Filled(3));
+        assert_eq!(write_sdk(&parsed.table, Some(progress)), SDK);
+
+        // Without a [State] section, there is nothing to start from but the givens.
+        let puzzle = read_sdk(&lines(&SDK[..SDK.find("[State]").unwrap()])).unwrap();
+        assert!(puzzle.pencil_marks.is_none());
+        assert!(!write_sdk(&puzzle.table, None).contains("[State]"));
+    }
+
+    #[test]
+    fn sdx_files() {
+        let parsed = read_sdk(&lines(SDK)).unwrap();
+        let sdx = write_sdx(&parsed.table, parsed.pencil_marks.as_ref()).unwrap();
+        let first_row = sdx.lines().nth(4).unwrap();
+
+        assert!(first_row.starts_with("u3 46789 1 589 "));
+        assert!(first_row.ends_with(" 2"));
+
+        let mut sdx = read_sdx(&lines(&sdx)).unwrap();
+        let progress = sdx.pencil_marks.as_mut().unwrap();
+        assert_eq!(sdx.table.metadata(), parsed.table.metadata());
+        assert_eq!(progress.table().contents()[0][0], SudokuCell::Filled(3));
+
+        let cell = CellLocation::new(0, 1);
+        assert_eq!(progress.candidates(cell), [4, 6, 7, 8, 9]);
+
+        progress.set_candidates(cell, &[4, 6]);
+        let edited = write_sdx(&sdx.table, sdx.pencil_marks.as_ref()).unwrap();
+        assert!(edited.contains("u3 46 1 589 "));
+        assert!(read_sdx(&lines("1 2 3")).is_err());
+        assert!(read_sdx(&lines(&edited.replace("u3", "u34"))).is_err());
+    }
+
+    #[test]
+    fn sdx_naked_singles() {
+        let solution =
+            "391867542286534719457291386129645873638179254745328691972486135564713928813952467";
+        let table = SudokuTable::parse_unchecked(
+            [format!(".{}", &solution[1..]), String::from("author: Me")].into_iter(),
+            ParseMode::Strict,
+        )
+        .unwrap();
+        let sdx = write_sdx(&table, None).unwrap();
+
+        assert!(sdx.lines().nth(1).unwrap().starts_with("33 9 1 "));
+
+        let parsed = read_sdx(&lines(&sdx)).unwrap();
+        let progress = parsed.pencil_marks.as_ref().unwrap();
+        assert_eq!(parsed.table.contents()[0][0], SudokuCell::Empty);
+        assert_eq!(progress.candidates(CellLocation::new(0, 0)), [3]);
+        assert_eq!(parsed.table.metadata().author.as_deref(), Some("Me"));
+
+        let mut marks = PencilMarks::from_table(&table);
+        marks.set_candidates(CellLocation::new(0, 0), &[]);
+        assert!(matches!(
+            write_sdx(&table, Some(&marks)),
+            Err(SudokuError::NoCandidates { cell }) if cell == CellLocation::new(0, 0)
+        ));
+    }
+}
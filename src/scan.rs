@@ -0,0 +1,271 @@
+use std::fmt::Display;
+
+use super::error::SudokuError;
+use super::sudoku::{CellLocation, ParseMode, SudokuTable};
+use digits::{Shape, Templates};
+use grid::Grid;
+use image::{GrayImage, InkMask};
+
+mod digits;
+mod grid;
+mod image;
+
+pub use image::is_image;
+
+/// Cells read with less confidence than this are reported.
+pub const LOW_CONFIDENCE: f64 = 0.6;
+/// The part of a cell, on each side, left out so that grid lines are not read as ink.
+const CELL_INSET: f64 = 0.12;
+/// Ink covering less of a cell than this, or lower than this part of it, is taken as noise.
+const MIN_INK: f64 = 0.02;
+const MIN_DIGIT_HEIGHT: f64 = 0.3;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CellScan {
+    pub digit: Option<u8>,
+    /// From 0 to 1. For a digit, how closely it matches the built-in digit; for an empty cell,
+    /// how little stray ink it holds.
+    pub confidence: f64,
+}
+
+/// A puzzle read from a picture of its grid.
+pub struct ScanResult {
+    pub table: SudokuTable,
+    pub cells: [[CellScan; 9]; 9],
+}
+
+impl ScanResult {
+    /// Reads a PNG or PGM image of an upright grid, such as a scanned page or a screenshot.
+    pub fn from_image(data: &[u8]) -> Result<ScanResult, SudokuError> {
+        let image = GrayImage::decode(data)
+            .map_err(|e| SudokuError::Import(format!("Cannot read image: {}", e)))?;
+        let mask = image.ink();
+        let grid = Grid::find(&image, &mask).ok_or_else(|| {
+            SudokuError::Import(String::from("Cannot find a sudoku grid in the image"))
+        })?;
+        let templates = Templates::new();
+        let mut cells = [[CellScan {
+            digit: None,
+            confidence: 1.0,
+        }; 9]; 9];
+
+        for (row, cols) in cells.iter_mut().enumerate() {
+            for (col, cell) in cols.iter_mut().enumerate() {
+                *cell = Self::read_cell(&mask, &grid, row, col, &templates);
+            }
+        }
+
+        let lines = cells.iter().map(|cols| {
+            cols.iter()
+                .map(|x| x.digit.map_or('X', |x| (b'0' + x) as char))
+                .collect::<String>()
+        });
+        let table = SudokuTable::parse_unchecked(lines, ParseMode::Strict)?;
+
+        Ok(ScanResult { table, cells })
+    }
+
+    fn read_cell(
+        mask: &InkMask,
+        grid: &Grid,
+        row: usize,
+        col: usize,
+        templates: &Templates,
+    ) -> CellScan {
+        let region = grid.cell(row, col, CELL_INSET);
+
+        if region.width() == 0 || region.height() == 0 {
+            return CellScan {
+                digit: None,
+                confidence: 0.0,
+            };
+        }
+
+        let area = (region.width() * region.height()) as f64;
+        let ink = mask.count(region) as f64 / area;
+        let empty = CellScan {
+            digit: None,
+            confidence: (1.0 - ink / MIN_INK / 2.0).clamp(0.0, 1.0),
+        };
+
+        match mask.largest_component(region) {
+            Some((bounds, _))
+                if ink >= MIN_INK
+                    && bounds.height() as f64 >= region.height() as f64 * MIN_DIGIT_HEIGHT =>
+            {
+                let (digit, score) = templates.best_match(&Shape::new(mask, bounds));
+
+                CellScan {
+                    digit: Some(digit),
+                    confidence: score.clamp(0.0, 1.0),
+                }
+            }
+            _ => empty,
+        }
+    }
+
+    /// The cells read with a confidence below [`LOW_CONFIDENCE`].
+    pub fn uncertain_cells(&self) -> Vec<(CellLocation, CellScan)> {
+        let mut result = vec![];
+
+        for (row, cols) in self.cells.iter().enumerate() {
+            for (col, cell) in cols.iter().enumerate() {
+                if cell.confidence < LOW_CONFIDENCE {
+                    result.push((CellLocation::new(row, col), *cell));
+                }
+            }
+        }
+
+        result
+    }
+}
+
+/// Shows each cell as it was read, with its confidence in percent.
+impl Display for ScanResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (row, cols) in self.cells.iter().enumerate() {
+            if row > 0 && row % 3 == 0 {
+                writeln!(f)?;
+            }
+
+            for (col, cell) in cols.iter().enumerate() {
+                let separator = match col {
+                    0 => "",
+                    3 | 6 => "  |  ",
+                    _ => "  ",
+                };
+                let digit = cell.digit.map_or('.', |x| (b'0' + x) as char);
+
+                write!(
+                    f,
+                    "{}{} {:>3}",
+                    separator,
+                    digit,
+                    (cell.confidence * 100.0).round()
+                )?;
+            }
+
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::sudoku::drawing::DrawOptions;
+    use super::super::sudoku::raster::RasterOptions;
+    use super::super::sudoku::{ParseMode, SudokuTable};
+    use super::ScanResult;
+
+    const PUZZLE: &str = "XX1XXXXX2\n\
+    XXXX34XXX\n\
+    X5XXX1XX6\n\
+    X2X6XXXX3\n\
+    X3XXXXX5X\n\
+    7XXXX8X9X\n\
+    9XX4XXX3X\n\
+    XXX71XXXX\n\
+    8XXXXX4XX\n";
+
+    #[test]
+    fn rendered_grids() {
+        let table =
+            SudokuTable::parse_unchecked(PUZZLE.lines().map(String::from), ParseMode::Strict)
+                .unwrap();
+
+        for cell_size in [20.0, 40.0, 64.0] {
+            let options = RasterOptions {
+                cell_size,
+                font_size: None,
+            };
+            let png = table.to_png(&DrawOptions::default(), &options);
+            let scan = ScanResult::from_image(&png).unwrap();
+
+            assert_eq!(scan.table.to_single_line(), table.to_single_line());
+            assert!(scan.uncertain_cells().is_empty());
+        }
+    }
+
+    /// A grid drawn as a scanner would see it: digits from a 5 by 7 pixel font unlike the
+    /// renderers' strokes, on gray paper with noise.
+    fn noisy_bitmap_scan(puzzle: &str) -> Vec<u8> {
+        const FONT: [[u8; 7]; 9] = [
+            [4, 12, 4, 4, 4, 4, 14],
+            [14, 17, 1, 2, 4, 8, 31],
+            [31, 2, 4, 2, 1, 17, 14],
+            [2, 6, 10, 18, 31, 2, 2],
+            [31, 16, 30, 1, 1, 17, 14],
+            [6, 8, 16, 30, 17, 17, 14],
+            [31, 1, 2, 4, 8, 8, 8],
+            [14, 17, 17, 14, 17, 17, 14],
+            [14, 17, 17, 15, 1, 2, 12],
+        ];
+        let (cell, margin, dot) = (40, 20, 4);
+        let size = cell * 9 + margin * 2;
+        let mut ink = vec![false; size * size];
+        let mut fill = |left: usize, top: usize, width: usize, height: usize| {
+            for y in top..top + height {
+                ink[y * size + left..y * size + left + width].fill(true);
+            }
+        };
+
+        for i in 0..10 {
+            let (position, width) = (margin + i * cell, if i % 3 == 0 { 3 } else { 1 });
+            fill(position - width / 2, margin, width, cell * 9);
+            fill(margin, position - width / 2, cell * 9, width);
+        }
+
+        for (i, c) in puzzle.chars().filter(|x| *x != '\n').enumerate() {
+            let Some(digit) = c.to_digit(10) else {
+                continue;
+            };
+            let left = margin + i % 9 * cell + (cell - 5 * dot) / 2 - 1;
+            let top = margin + i / 9 * cell + (cell - 7 * dot) / 2 - 1;
+
+            for (y, bits) in FONT[digit as usize - 1].iter().enumerate() {
+                for x in (0..5).filter(|x| bits & 16 >> x != 0) {
+                    // Dots overlap, so that diagonal steps stay connected.
+                    fill(left + x * dot, top + y * dot, dot + 2, dot + 2);
+                }
+            }
+        }
+
+        let mut seed = 12345u32;
+        let mut random = move || {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            (seed >> 16) % 80
+        };
+        let pixels = ink.iter().map(|x| match (*x, random()) {
+            (_, 0) => 90,
+            (true, noise) => 10 + noise as u8,
+            (false, noise) => 175 + noise as u8,
+        });
+
+        format!("P5 {} {} 255\n", size, size)
+            .into_bytes()
+            .into_iter()
+            .chain(pixels)
+            .collect()
+    }
+
+    #[test]
+    fn other_typeface_with_noise() {
+        let scan = ScanResult::from_image(&noisy_bitmap_scan(PUZZLE)).unwrap();
+
+        assert_eq!(
+            scan.table.to_single_line(),
+            PUZZLE.replace('\n', "").replace('X', ".")
+        );
+    }
+
+    #[test]
+    fn no_grid() {
+        let blank = b"P2 60 60 1\n".iter().copied();
+        let image: Vec<u8> = blank.chain(b"1 ".repeat(3600)).collect();
+
+        assert!(ScanResult::from_image(&image).is_err());
+        assert!(ScanResult::from_image(b"P5 60 60 255\n").is_err());
+    }
+}
@@ -0,0 +1,162 @@
+use crate::sudoku::drawing::{Color, Point};
+use crate::sudoku::raster::Image;
+
+use super::image::{GrayImage, InkMask, Rect};
+
+const TEMPLATE_WIDTH: usize = 16;
+const TEMPLATE_HEIGHT: usize = 24;
+/// The size templates are drawn at, in pixels, before they are scaled down.
+const DRAWING_SIZE: f64 = 64.0;
+/// How much a template's score drops for each loop it has more or less than the shape. Loops
+/// tell apart digits that look alike in another typeface, such as a 6 and an 8.
+const HOLE_PENALTY: f64 = 0.25;
+/// Enclosed paper smaller than this part of the shape's bounds is a speck, not a loop.
+const MIN_HOLE_SIZE: f64 = 0.01;
+
+/// A digit, or ink in a cell, scaled to fit the template box while keeping its proportions.
+pub struct Shape {
+    values: Vec<f64>,
+    holes: usize,
+}
+
+/// The built-in digits, drawn in the regular and the bold weights of the renderers' font.
+pub struct Templates {
+    digits: Vec<(u8, Shape)>,
+}
+
+impl Shape {
+    pub fn new(mask: &InkMask, bounds: Rect) -> Shape {
+        let scale = (TEMPLATE_WIDTH as f64 / bounds.width() as f64)
+            .min(TEMPLATE_HEIGHT as f64 / bounds.height() as f64);
+        let offset_x = (TEMPLATE_WIDTH as f64 - bounds.width() as f64 * scale) / 2.0;
+        let offset_y = (TEMPLATE_HEIGHT as f64 - bounds.height() as f64 * scale) / 2.0;
+        let mut values = vec![0.0; TEMPLATE_WIDTH * TEMPLATE_HEIGHT];
+
+        // Each template pixel averages 3 by 3 samples of the ink, which also smooths over small
+        // differences between fonts.
+        for (i, value) in values.iter_mut().enumerate() {
+            let (x, y) = ((i % TEMPLATE_WIDTH) as f64, (i / TEMPLATE_WIDTH) as f64);
+            let mut ink = 0;
+
+            for sample in 0..9 {
+                let sample_x = x + (sample % 3) as f64 / 3.0 + 1.0 / 6.0;
+                let sample_y = y + (sample / 3) as f64 / 3.0 + 1.0 / 6.0;
+                let source_x = bounds.left as f64 + (sample_x - offset_x) / scale;
+                let source_y = bounds.top as f64 + (sample_y - offset_y) / scale;
+
+                if source_x >= bounds.left as f64
+                    && source_y >= bounds.top as f64
+                    && source_x < bounds.right as f64
+                    && source_y < bounds.bottom as f64
+                    && mask.get(source_x as usize, source_y as usize)
+                {
+                    ink += 1;
+                }
+            }
+
+            *value = ink as f64 / 9.0;
+        }
+
+        let area = (bounds.width() * bounds.height()) as f64;
+        let holes = mask.holes(bounds, (area * MIN_HOLE_SIZE).ceil() as usize);
+
+        Shape { values, holes }
+    }
+
+    /// How alike the shapes are, from -1 to 1 (normalized cross-correlation).
+    pub fn correlation(&self, other: &Shape) -> f64 {
+        let mean = |x: &[f64]| x.iter().sum::<f64>() / x.len() as f64;
+        let (mean_a, mean_b) = (mean(&self.values), mean(&other.values));
+        let (mut product, mut norm_a, mut norm_b) = (0.0, 0.0, 0.0);
+
+        for (a, b) in self.values.iter().zip(&other.values) {
+            let (a, b) = (a - mean_a, b - mean_b);
+            product += a * b;
+            norm_a += a * a;
+            norm_b += b * b;
+        }
+
+        match norm_a * norm_b {
+            0.0 => 0.0,
+            norm => product / norm.sqrt(),
+        }
+    }
+}
+
+impl Templates {
+    pub fn new() -> Templates {
+        let mut digits = vec![];
+
+        for bold in [false, true] {
+            for digit in 1..=9 {
+                let mut image = Image::new(DRAWING_SIZE as u32, DRAWING_SIZE as u32, Color::WHITE);
+                let center = Point::new(DRAWING_SIZE / 2.0, DRAWING_SIZE / 2.0);
+                image.text(
+                    &digit.to_string(),
+                    center,
+                    DRAWING_SIZE * 0.8,
+                    bold,
+                    Color::BLACK,
+                );
+
+                let gray = GrayImage {
+                    width: image.width as usize,
+                    height: image.height as usize,
+                    pixels: image.pixels.chunks(3).map(|x| x[0]).collect(),
+                };
+                let mask = gray.ink();
+                let whole = Rect {
+                    left: 0,
+                    top: 0,
+                    right: mask.width,
+                    bottom: mask.height,
+                };
+
+                if let Some((bounds, _)) = mask.largest_component(whole) {
+                    digits.push((digit, Shape::new(&mask, bounds)));
+                }
+            }
+        }
+
+        Templates { digits }
+    }
+
+    /// The digit whose template is the most like the shape, and how alike they are.
+    pub fn best_match(&self, shape: &Shape) -> (u8, f64) {
+        self.digits
+            .iter()
+            .map(|(digit, template)| {
+                let holes = template.holes.abs_diff(shape.holes) as f64;
+                (*digit, template.correlation(shape) - holes * HOLE_PENALTY)
+            })
+            .fold((0, f64::MIN), |best, x| match x.1 > best.1 {
+                true => x,
+                false => best,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Templates;
+
+    #[test]
+    fn templates_match_themselves() {
+        let templates = Templates::new();
+
+        assert_eq!(templates.digits.len(), 18);
+        assert_eq!(
+            templates.digits[..9]
+                .iter()
+                .map(|(_, x)| x.holes)
+                .collect::<Vec<_>>(),
+            [0, 0, 0, 1, 0, 1, 0, 2, 1]
+        );
+
+        for (digit, shape) in &templates.digits {
+            let (found, score) = templates.best_match(shape);
+            assert_eq!(found, *digit);
+            assert!(score > 0.999);
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use super::image::{GrayImage, InkMask, Rect};
+
+/// The smallest grid that can be read, in pixels.
+const MIN_GRID_SIZE: usize = 45;
+/// How far a line may be from its expected place, as a part of a cell.
+const LINE_TOLERANCE: f64 = 0.3;
+/// How much darker than the paper around it a line must be, on average along its length.
+const MIN_LINE_CONTRAST: f64 = 0.12;
+/// How many of the 8 inner lines must be found each way, for the image to be taken as a grid.
+const MIN_INNER_LINES: usize = 6;
+
+/// The positions of the 10 vertical and the 10 horizontal lines of a grid, in pixels.
+#[derive(Debug)]
+pub struct Grid {
+    pub columns: [f64; 10],
+    pub rows: [f64; 10],
+}
+
+impl Grid {
+    /// Finds an upright grid, as the largest connected group of ink in the image. Its lines are
+    /// looked for around the place they would have in an evenly divided grid.
+    pub fn find(image: &GrayImage, mask: &InkMask) -> Option<Grid> {
+        let whole = Rect {
+            left: 0,
+            top: 0,
+            right: mask.width,
+            bottom: mask.height,
+        };
+        let (bounds, _) = mask.largest_component(whole)?;
+        let (width, height) = (bounds.width(), bounds.height());
+
+        if width.min(height) < MIN_GRID_SIZE || width.max(height) * 4 > width.min(height) * 5 {
+            return None;
+        }
+
+        let column_darkness = |x: usize| {
+            (bounds.top..bounds.bottom)
+                .map(|y| image.darkness(x, y))
+                .sum::<f64>()
+                / height as f64
+        };
+        let row_darkness = |y: usize| {
+            (bounds.left..bounds.right)
+                .map(|x| image.darkness(x, y))
+                .sum::<f64>()
+                / width as f64
+        };
+
+        Some(Grid {
+            columns: find_lines(bounds.left, bounds.right, column_darkness)?,
+            rows: find_lines(bounds.top, bounds.bottom, row_darkness)?,
+        })
+    }
+
+    /// The inside of a cell, leaving out its borders.
+    pub fn cell(&self, row: usize, col: usize, inset: f64) -> Rect {
+        let (left, right) = (self.columns[col], self.columns[col + 1]);
+        let (top, bottom) = (self.rows[row], self.rows[row + 1]);
+        let (dx, dy) = ((right - left) * inset, (bottom - top) * inset);
+
+        Rect {
+            left: (left + dx).ceil() as usize,
+            top: (top + dy).ceil() as usize,
+            right: (right - dx).floor() as usize,
+            bottom: (bottom - dy).floor() as usize,
+        }
+    }
+}
+
+/// The outer lines are the edges of the grid. Each inner line is the darkest run of pixels
+/// near its expected place, or that place itself when no line stands out. Returns `None` when
+/// too few lines stand out.
+fn find_lines(start: usize, end: usize, darkness: impl Fn(usize) -> f64) -> Option<[f64; 10]> {
+    let cell = (end - start) as f64 / 9.0;
+    let mut lines = [0.0; 10];
+    let mut inner_lines = 0;
+
+    for (i, line) in lines.iter_mut().enumerate() {
+        let expected = start as f64 + cell * i as f64;
+        let low = (expected - cell * LINE_TOLERANCE).max(start as f64) as usize;
+        let high = ((expected + cell * LINE_TOLERANCE) as usize).min(end - 1);
+        let values: Vec<(usize, f64)> = (low..=high).map(|x| (x, darkness(x))).collect();
+        let darkest = values.iter().map(|(_, x)| *x).fold(0.0, f64::max);
+        let lightest = values.iter().map(|(_, x)| *x).fold(1.0, f64::min);
+        let is_found = darkest - lightest >= MIN_LINE_CONTRAST;
+
+        if is_found && (1..9).contains(&i) {
+            inner_lines += 1;
+        }
+
+        *line = match is_found {
+            true => {
+                // Thick lines are several pixels wide, so the middle of the darkest ones is used.
+                let dark: Vec<usize> = values
+                    .iter()
+                    .filter(|(_, x)| *x >= darkest * 0.8)
+                    .map(|(x, _)| *x)
+                    .collect();
+                dark.iter().sum::<usize>() as f64 / dark.len() as f64 + 0.5
+            }
+            false => expected,
+        };
+    }
+
+    (inner_lines >= MIN_INNER_LINES).then_some(lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::find_lines;
+
+    #[test]
+    fn lines_near_their_expected_place() {
+        // A grid 90 pixels wide, with its third line drawn 2 pixels late and its fifth line
+        // missing.
+        let darkness = |x: usize| match x {
+            0 | 1 | 12 | 22 | 23 | 30 | 50 | 60 | 61 | 70 | 80 | 88 | 89 => 1.0,
+            _ => 0.05,
+        };
+        let lines = find_lines(0, 90, darkness).unwrap();
+
+        assert_eq!(lines[0], 1.0);
+        assert_eq!(lines[2], 23.0);
+        assert_eq!(lines[4], 40.0);
+        assert_eq!(lines[6], 61.0);
+        assert_eq!(lines[9], 89.0);
+
+        // An evenly dark area has no lines.
+        assert!(find_lines(0, 90, |_| 0.8).is_none());
+    }
+}
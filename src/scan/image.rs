@@ -0,0 +1,354 @@
+use std::collections::VecDeque;
+
+use crate::png;
+
+/// An 8-bit grayscale image, row by row.
+pub struct GrayImage {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u8>,
+}
+
+/// A rectangle of pixels, from `left` and `top` up to `right` and `bottom`, not included.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rect {
+    pub left: usize,
+    pub top: usize,
+    pub right: usize,
+    pub bottom: usize,
+}
+
+impl Rect {
+    pub fn width(&self) -> usize {
+        self.right - self.left
+    }
+
+    pub fn height(&self) -> usize {
+        self.bottom - self.top
+    }
+}
+
+/// The dark pixels of an image.
+pub struct InkMask {
+    pub width: usize,
+    pub height: usize,
+    ink: Vec<bool>,
+}
+
+/// Whether the data starts like a PNG, or a binary or plain PGM file.
+pub fn is_image(data: &[u8]) -> bool {
+    data.starts_with(b"\x89PNG")
+        || (data.len() > 2
+            && (data.starts_with(b"P2") || data.starts_with(b"P5"))
+            && data[2].is_ascii_whitespace())
+}
+
+impl GrayImage {
+    pub fn decode(data: &[u8]) -> Result<GrayImage, String> {
+        if data.starts_with(b"P2") || data.starts_with(b"P5") {
+            return Self::read_pgm(data);
+        }
+
+        let (width, height, rgb) = png::decode(data)?;
+        let pixels = rgb
+            .chunks(3)
+            .map(|x| ((x[0] as u32 * 299 + x[1] as u32 * 587 + x[2] as u32 * 114) / 1000) as u8)
+            .collect();
+
+        Ok(GrayImage {
+            width: width as usize,
+            height: height as usize,
+            pixels,
+        })
+    }
+
+    fn read_pgm(data: &[u8]) -> Result<GrayImage, String> {
+        let is_binary = data[1] == b'5';
+        let mut position = 2;
+        let mut header = [0; 3];
+
+        for value in header.iter_mut() {
+            *value = read_pgm_number(data, &mut position).ok_or("invalid PGM header")?;
+        }
+
+        let [width, height, max_value] = header;
+        if width == 0 || height == 0 || !(1..=65535).contains(&max_value) {
+            return Err(String::from("invalid PGM header"));
+        }
+
+        let count = width.checked_mul(height).ok_or("invalid PGM header")?;
+        let samples: Vec<usize> = match is_binary {
+            // A single whitespace character separates the header from the samples.
+            true => {
+                let sample_size = if max_value > 255 { 2 } else { 1 };
+                let start = position + 1;
+                let end = count
+                    .checked_mul(sample_size)
+                    .and_then(|x| x.checked_add(start))
+                    .ok_or("invalid PGM header")?;
+                let bytes = data.get(start..end).ok_or("truncated PGM data")?;
+
+                bytes
+                    .chunks(sample_size)
+                    .map(|x| x.iter().fold(0, |value, byte| value << 8 | *byte as usize))
+                    .collect()
+            }
+            false => (0..count)
+                .map(|_| read_pgm_number(data, &mut position))
+                .collect::<Option<_>>()
+                .ok_or("truncated PGM data")?,
+        };
+
+        Ok(GrayImage {
+            width,
+            height,
+            pixels: samples
+                .into_iter()
+                .map(|x| (x.min(max_value) * 255 / max_value) as u8)
+                .collect(),
+        })
+    }
+
+    /// Separates ink from paper, with the threshold that best splits the histogram in two
+    /// (Otsu's method).
+    pub fn ink(&self) -> InkMask {
+        let mut histogram = [0usize; 256];
+        for pixel in &self.pixels {
+            histogram[*pixel as usize] += 1;
+        }
+
+        let total = self.pixels.len() as f64;
+        let sum: f64 = histogram
+            .iter()
+            .enumerate()
+            .map(|(i, x)| i as f64 * *x as f64)
+            .sum();
+        let (mut below, mut below_sum) = (0.0, 0.0);
+        let (mut threshold, mut best) = (128, 0.0);
+
+        for (i, count) in histogram.iter().enumerate() {
+            below += *count as f64;
+            below_sum += i as f64 * *count as f64;
+
+            if below == 0.0 || below == total {
+                continue;
+            }
+
+            let difference = below_sum / below - (sum - below_sum) / (total - below);
+            let variance = below * (total - below) * difference * difference;
+
+            if variance > best {
+                best = variance;
+                threshold = i;
+            }
+        }
+
+        InkMask {
+            width: self.width,
+            height: self.height,
+            ink: self
+                .pixels
+                .iter()
+                .map(|x| (*x as usize) <= threshold)
+                .collect(),
+        }
+    }
+
+    /// How dark the pixel is, from 0 for white to 1 for black.
+    pub fn darkness(&self, x: usize, y: usize) -> f64 {
+        1.0 - self.pixels[y * self.width + x] as f64 / 255.0
+    }
+}
+
+impl InkMask {
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        self.ink[y * self.width + x]
+    }
+
+    pub fn count(&self, region: Rect) -> usize {
+        (region.top..region.bottom)
+            .map(|y| {
+                (region.left..region.right)
+                    .filter(|x| self.get(*x, y))
+                    .count()
+            })
+            .sum()
+    }
+
+    /// The number of areas of paper in the region that ink closes in on every side, such as the
+    /// two loops of an 8. Specks of paper smaller than `min_size` pixels are not counted.
+    pub fn holes(&self, region: Rect, min_size: usize) -> usize {
+        let mut seen = vec![false; region.width() * region.height()];
+        let edges: Vec<(usize, usize)> = (region.left..region.right)
+            .flat_map(|x| [(x, region.top), (x, region.bottom - 1)])
+            .chain(
+                (region.top..region.bottom).flat_map(|y| [(region.left, y), (region.right - 1, y)]),
+            )
+            .collect();
+
+        // The paper reached from the edges is filled first, so that what is left is enclosed.
+        self.fill_paper(region, &mut seen, edges);
+
+        let mut result = 0;
+        for y in region.top..region.bottom {
+            for x in region.left..region.right {
+                if self.fill_paper(region, &mut seen, vec![(x, y)]) >= min_size {
+                    result += 1;
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Marks the paper touching the starting pixels as seen, and returns its number of pixels.
+    fn fill_paper(&self, region: Rect, seen: &mut [bool], start: Vec<(usize, usize)>) -> usize {
+        let index = |x: usize, y: usize| (y - region.top) * region.width() + x - region.left;
+        let mut queue = VecDeque::from(start);
+        let mut size = 0;
+
+        while let Some((x, y)) = queue.pop_front() {
+            if seen[index(x, y)] || self.get(x, y) {
+                continue;
+            }
+
+            seen[index(x, y)] = true;
+            size += 1;
+
+            let neighbors = [
+                (x > region.left).then(|| (x - 1, y)),
+                (x + 1 < region.right).then_some((x + 1, y)),
+                (y > region.top).then(|| (x, y - 1)),
+                (y + 1 < region.bottom).then_some((x, y + 1)),
+            ];
+            queue.extend(neighbors.into_iter().flatten());
+        }
+
+        size
+    }
+
+    /// The bounds of the largest group of touching ink pixels in the region, and its number of
+    /// pixels.
+    pub fn largest_component(&self, region: Rect) -> Option<(Rect, usize)> {
+        let mut seen = vec![false; region.width() * region.height()];
+        let index = |x: usize, y: usize| (y - region.top) * region.width() + x - region.left;
+        let mut best: Option<(Rect, usize)> = None;
+
+        for y in region.top..region.bottom {
+            for x in region.left..region.right {
+                if seen[index(x, y)] || !self.get(x, y) {
+                    continue;
+                }
+
+                let mut bounds = Rect {
+                    left: x,
+                    top: y,
+                    right: x + 1,
+                    bottom: y + 1,
+                };
+                let mut size = 0;
+                let mut queue = VecDeque::from([(x, y)]);
+                seen[index(x, y)] = true;
+
+                while let Some((x, y)) = queue.pop_front() {
+                    size += 1;
+                    bounds.left = bounds.left.min(x);
+                    bounds.top = bounds.top.min(y);
+                    bounds.right = bounds.right.max(x + 1);
+                    bounds.bottom = bounds.bottom.max(y + 1);
+
+                    let neighbors = [
+                        (x > region.left).then(|| (x - 1, y)),
+                        (x + 1 < region.right).then_some((x + 1, y)),
+                        (y > region.top).then(|| (x, y - 1)),
+                        (y + 1 < region.bottom).then_some((x, y + 1)),
+                    ];
+
+                    for (x, y) in neighbors.into_iter().flatten() {
+                        if !seen[index(x, y)] && self.get(x, y) {
+                            seen[index(x, y)] = true;
+                            queue.push_back((x, y));
+                        }
+                    }
+                }
+
+                if best.is_none_or(|(_, x)| size > x) {
+                    best = Some((bounds, size));
+                }
+            }
+        }
+
+        best
+    }
+}
+
+fn read_pgm_number(data: &[u8], position: &mut usize) -> Option<usize> {
+    loop {
+        match data.get(*position)? {
+            b'#' => {
+                while *data.get(*position)? != b'\n' {
+                    *position += 1;
+                }
+            }
+            x if x.is_ascii_whitespace() => *position += 1,
+            _ => break,
+        }
+    }
+
+    let start = *position;
+    while data.get(*position).is_some_and(u8::is_ascii_digit) {
+        *position += 1;
+    }
+
+    std::str::from_utf8(&data[start..*position])
+        .ok()?
+        .parse()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_image, GrayImage, Rect};
+
+    #[test]
+    fn pgm_files() {
+        let plain = GrayImage::decode(b"P2\n# a comment\n3 2\n15\n0 15 15\n15 0 15\n").unwrap();
+        let binary = GrayImage::decode(b"P5 3 2 255\n\x00\xff\xff\xff\x00\xff").unwrap();
+
+        assert!(is_image(b"P5 3 2 255\n"));
+        assert!(!is_image(b"P5XXXXXXX\n"));
+        assert_eq!((plain.width, plain.height), (3, 2));
+        assert_eq!(plain.pixels, binary.pixels);
+        assert_eq!(plain.pixels, [0, 255, 255, 255, 0, 255]);
+
+        let huge = format!("P5 {} {} 65535\n", usize::MAX / 2, 2);
+        assert!(GrayImage::decode(huge.as_bytes()).is_err());
+        assert!(GrayImage::decode(b"P5 4000 4000 255\n\x00").is_err());
+    }
+
+    #[test]
+    fn components() {
+        let image = GrayImage::decode(b"P2 5 3 1\n0 0 1 1 0\n1 0 1 1 1\n1 1 1 0 1\n").unwrap();
+        let mask = image.ink();
+        let whole = Rect {
+            left: 0,
+            top: 0,
+            right: 5,
+            bottom: 3,
+        };
+
+        assert_eq!(mask.count(whole), 5);
+        assert_eq!(
+            mask.largest_component(whole),
+            Some((
+                Rect {
+                    left: 0,
+                    top: 0,
+                    right: 2,
+                    bottom: 2
+                },
+                3
+            ))
+        );
+    }
+}
@@ -1,13 +1,107 @@
 use std::fmt::Display;
-
+use std::rc::Rc;
+use std::str::FromStr;
+
+pub mod arrow;
+pub mod cage;
+pub mod conflict;
+pub mod consistency;
+pub mod constraint;
+pub mod document;
+pub mod drawing;
+pub mod fpuzzles;
+pub mod house;
+pub mod inequality;
+pub mod kropki;
+pub mod lenient;
+pub mod line;
+pub mod little_killer;
+pub mod metadata;
+pub mod parity;
+pub mod pencil_marks;
+pub mod raster;
+pub mod render;
+pub mod sandwich;
+pub mod skyscraper;
 pub mod solver;
+pub mod svg;
+pub mod tikz;
+
+use super::error::SudokuError;
+use super::png;
+use conflict::Conflict;
+use consistency::Contradiction;
+use constraint::Constraint;
+use drawing::{DrawOptions, Drawing};
+use house::{House, HousePreset};
+use metadata::Metadata;
+use raster::RasterOptions;
+use render::Canvas;
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct CellLocation {
     row: usize,
     col: usize,
 }
 
+impl CellLocation {
+    /// A cell from its zero-based row and column.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `row` or `col` is 9 or more. Cells read from input should be parsed
+    /// with `FromStr` instead, which reports an error.
+    pub fn new(row: usize, col: usize) -> CellLocation {
+        assert!(
+            row < 9 && col < 9,
+            "cell r{}c{} is outside the table",
+            row + 1,
+            col + 1
+        );
+
+        CellLocation { row, col }
+    }
+
+    pub fn row(&self) -> usize {
+        self.row
+    }
+
+    pub fn col(&self) -> usize {
+        self.col
+    }
+}
+
+impl FromStr for CellLocation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid_cell = || format!("Invalid input: illegal cell '{}'", s);
+
+        let (row, col) = s
+            .to_ascii_lowercase()
+            .strip_prefix('r')
+            .and_then(|x| {
+                x.split_once('c')
+                    .map(|(row, col)| (row.parse::<usize>(), col.parse::<usize>()))
+            })
+            .ok_or_else(invalid_cell)?;
+
+        match (row, col) {
+            (Ok(row @ 1..=9), Ok(col @ 1..=9)) => Ok(CellLocation {
+                row: row - 1,
+                col: col - 1,
+            }),
+            _ => Err(invalid_cell()),
+        }
+    }
+}
+
+impl Display for CellLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "r{}c{}", self.row + 1, self.col + 1)
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum SudokuCell {
     Empty,
@@ -26,64 +120,199 @@ impl Display for SudokuCell {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ParseMode {
+    Strict,
+    Lenient,
+}
+
 #[derive(Clone)]
 pub struct SudokuTable {
     contents: Vec<Vec<SudokuCell>>,
+    region_map: [[usize; 9]; 9],
+    regions: Vec<House>,
+    extra_houses: Vec<House>,
+    house_presets: Vec<HousePreset>,
+    constraints: Vec<Rc<dyn Constraint>>,
+    metadata: Metadata,
 }
 
 impl SudokuTable {
     const TABLE_SIZE: usize = 9;
+    const CELL_COUNT: usize = Self::TABLE_SIZE * Self::TABLE_SIZE;
 
-    pub fn from_string<T: Iterator<Item = String>>(table_str: T) -> Result<SudokuTable, String> {
-        let contents: Result<Vec<Vec<SudokuCell>>, _> = table_str
-            .map(Self::extract_row_from_line)
-            .enumerate()
-            .map(|(i, x)| match i >= Self::TABLE_SIZE {
-                true => Err(String::from("Invalid input: expected 9 lines, found more")),
-                false => x,
-            })
-            .collect();
+    pub fn from_string<T: Iterator<Item = String>>(
+        table_str: T,
+    ) -> Result<SudokuTable, SudokuError> {
+        Self::from_string_with_mode(table_str, ParseMode::Strict)
+    }
 
-        let result = SudokuTable {
-            contents: contents?,
+    pub fn from_string_with_mode<T: Iterator<Item = String>>(
+        table_str: T,
+        mode: ParseMode,
+    ) -> Result<SudokuTable, SudokuError> {
+        let result = Self::parse_unchecked(table_str, mode)?;
+        result.validate()?;
+        result.check_consistency()?;
+
+        Ok(result)
+    }
+
+    pub fn parse_unchecked<T: Iterator<Item = String>>(
+        table_str: T,
+        mode: ParseMode,
+    ) -> Result<SudokuTable, SudokuError> {
+        match mode {
+            ParseMode::Strict => Self::parse_lines(table_str.zip(1..)),
+            ParseMode::Lenient => {
+                Self::parse_lines(lenient::normalize_lines(table_str).into_iter())
+            }
+        }
+    }
+
+    fn parse_lines<T: Iterator<Item = (String, usize)>>(
+        table_str: T,
+    ) -> Result<SudokuTable, SudokuError> {
+        let mut result = SudokuTable {
+            contents: Vec::with_capacity(Self::TABLE_SIZE),
+            region_map: [[0; 9]; 9],
+            regions: vec![],
+            extra_houses: vec![],
+            house_presets: vec![],
+            constraints: vec![],
+            metadata: Metadata::default(),
         };
+        result.set_region_map(Self::box_region_map()).unwrap();
+        let mut extra_lines = 0;
+
+        for (line, line_number) in table_str {
+            if line.contains(':') {
+                document::add_from_line(&mut result, &line).map_err(|message| {
+                    SudokuError::InvalidRule {
+                        line: line_number,
+                        message,
+                    }
+                })?;
+            } else if result.contents.is_empty() && line.chars().count() == Self::CELL_COUNT {
+                result.contents = Self::extract_rows_from_single_line(&line, line_number)?;
+            } else if result.contents.len() < Self::TABLE_SIZE {
+                result
+                    .contents
+                    .push(Self::extract_row_from_line(&line, line_number)?);
+            } else if !line.trim().is_empty() {
+                extra_lines += 1;
+            }
+        }
 
-        if result.contents.len() < Self::TABLE_SIZE {
-            Err(String::from(format!(
-                "Invalid input: expected 9 lines, found {}",
-                result.contents.len()
-            )))
-        } else if !result.is_valid_sudoku() {
-            Err(String::from("Invalid input: illegal table"))
+        if extra_lines > 0 {
+            Err(SudokuError::WrongLineCount {
+                found: Self::TABLE_SIZE + extra_lines,
+            })
+        } else if result.contents.len() < Self::TABLE_SIZE {
+            Err(SudokuError::WrongLineCount {
+                found: result.contents.len(),
+            })
         } else {
             Ok(result)
         }
     }
 
-    fn extract_row_from_line(line: String) -> Result<Vec<SudokuCell>, String> {
-        if line.len() != 9 {
-            return Err(String::from(
-                "Invalid input: line should have exactly 9 characters",
-            ));
+    fn extract_row_from_line(
+        line: &str,
+        line_number: usize,
+    ) -> Result<Vec<SudokuCell>, SudokuError> {
+        let length = line.chars().count();
+
+        if length != Self::TABLE_SIZE {
+            return Err(SudokuError::WrongLineLength {
+                line: line_number,
+                length,
+            });
         }
 
-        let mut result = Vec::with_capacity(Self::TABLE_SIZE);
+        line.chars()
+            .zip(1..)
+            .map(|(char, column)| Self::extract_cell_from_char(char, line_number, column))
+            .collect()
+    }
 
-        for char in line.chars() {
-            let extracted_cell = match char {
-                '1'..='9' => SudokuCell::Filled(char.to_digit(10).unwrap() as u8),
-                'X' => SudokuCell::Empty,
-                _ => return Err(format!("Invalid input: illegal character '{}'", char)),
-            };
+    fn extract_rows_from_single_line(
+        line: &str,
+        line_number: usize,
+    ) -> Result<Vec<Vec<SudokuCell>>, SudokuError> {
+        let cells = line
+            .chars()
+            .zip(1..)
+            .map(|(char, column)| Self::extract_cell_from_char(char, line_number, column))
+            .collect::<Result<Vec<SudokuCell>, SudokuError>>()?;
+
+        Ok(cells
+            .chunks(Self::TABLE_SIZE)
+            .map(|row| row.to_vec())
+            .collect())
+    }
 
-            result.push(extracted_cell);
+    fn extract_cell_from_char(
+        char: char,
+        line: usize,
+        column: usize,
+    ) -> Result<SudokuCell, SudokuError> {
+        match char {
+            '1'..='9' => Ok(SudokuCell::Filled(char.to_digit(10).unwrap() as u8)),
+            'X' | '.' | '0' => Ok(SudokuCell::Empty),
+            _ => Err(SudokuError::IllegalCharacter {
+                line,
+                column,
+                character: char,
+            }),
         }
+    }
 
-        Ok(result)
+    fn box_region_map() -> [[usize; 9]; 9] {
+        let mut result = [[0; 9]; 9];
+
+        for (i, row) in result.iter_mut().enumerate() {
+            for (j, region) in row.iter_mut().enumerate() {
+                *region = 3 * (i / 3) + j / 3;
+            }
+        }
+
+        result
     }
 
-    fn is_valid_sudoku(&self) -> bool {
-        self.are_rows_valid() && self.are_cols_valid() && self.are_3_by_3_cells_valid()
+    fn set_region_map(&mut self, region_map: [[usize; 9]; 9]) -> Result<(), String> {
+        let mut regions = vec![vec![]; Self::TABLE_SIZE];
+
+        for (i, row) in region_map.iter().enumerate() {
+            for (j, region) in row.iter().enumerate() {
+                regions[*region].push(CellLocation { row: i, col: j });
+            }
+        }
+
+        let mut houses = Vec::with_capacity(Self::TABLE_SIZE);
+        for (i, cells) in regions.into_iter().enumerate() {
+            let house: House = cells.try_into().map_err(|cells: Vec<CellLocation>| {
+                format!(
+                    "Invalid input: region {} should have exactly 9 cells, found {}",
+                    i + 1,
+                    cells.len()
+                )
+            })?;
+            houses.push(house);
+        }
+
+        self.region_map = region_map;
+        self.regions = houses;
+
+        Ok(())
+    }
+
+    pub fn is_valid_sudoku(&self) -> bool {
+        self.are_rows_valid()
+            && self.are_cols_valid()
+            && self.are_regions_valid()
+            && self.are_extra_houses_valid()
+            && self.are_constraints_satisfiable()
     }
 
     fn are_rows_valid(&self) -> bool {
@@ -121,35 +350,77 @@ impl SudokuTable {
         true
     }
 
-    fn are_3_by_3_cells_valid(&self) -> bool {
-        for i in 0usize..3 {
-            for j in 0usize..3 {
-                if !Self::are_distinct_digits(&self.get_3_by_3_cell(i, j)) {
-                    return false;
-                }
+    fn are_regions_valid(&self) -> bool {
+        for region in &self.regions {
+            if !Self::are_distinct_digits(&self.get_region(region)) {
+                return false;
             }
         }
 
         true
     }
 
-    fn get_3_by_3_cell(&self, row: usize, col: usize) -> Vec<u8> {
+    fn get_region(&self, region: &House) -> Vec<u8> {
         let mut result = vec![];
 
-        for i in 0usize..3 {
-            for j in 0usize..3 {
-                let table_i = (3 * row) + i;
-                let table_j = (3 * col) + j;
-
-                if let SudokuCell::Filled(x) = self.contents[table_i][table_j] {
-                    result.push(x);
-                }
+        for cell in region {
+            if let SudokuCell::Filled(x) = self.contents[cell.row][cell.col] {
+                result.push(x);
             }
         }
 
         result
     }
 
+    fn are_extra_houses_valid(&self) -> bool {
+        self.extra_houses
+            .iter()
+            .all(|x| Self::are_distinct_digits(&self.get_region(x)))
+    }
+
+    fn are_constraints_satisfiable(&self) -> bool {
+        self.constraints.iter().all(|x| x.is_satisfiable(self))
+    }
+
+    pub fn validate(&self) -> Result<(), SudokuError> {
+        if self.is_valid_sudoku() {
+            return Ok(());
+        }
+
+        let conflicts = self.conflicts();
+        let mut cells: Vec<CellLocation> = vec![];
+
+        for cell in conflicts.iter().flat_map(|x| &x.cells) {
+            if !cells.contains(cell) {
+                cells.push(*cell);
+            }
+        }
+        cells.sort_by_key(|cell| (cell.row, cell.col));
+
+        Err(SudokuError::RuleConflict { cells, conflicts })
+    }
+
+    pub fn check_consistency(&self) -> Result<(), SudokuError> {
+        let contradictions = self.contradictions();
+
+        match contradictions.is_empty() {
+            true => Ok(()),
+            false => Err(SudokuError::Contradiction { contradictions }),
+        }
+    }
+
+    pub fn contradictions(&self) -> Vec<Contradiction> {
+        consistency::find_contradictions(self)
+    }
+
+    pub fn conflicts(&self) -> Vec<Conflict> {
+        conflict::find_conflicts(self)
+    }
+
+    pub fn display_marked<'a>(&'a self, cells: &'a [CellLocation]) -> MarkedTable<'a> {
+        MarkedTable { table: self, cells }
+    }
+
     fn are_distinct_digits(digits: &[u8]) -> bool {
         let digit_exists: &mut [bool] = &mut [false; 9];
 
@@ -174,23 +445,94 @@ impl SudokuTable {
         &mut self.contents
     }
 
-    fn write_top_row(f: &mut std::fmt::Formatter<'_>, values: &[SudokuCell]) -> std::fmt::Result {
+    pub fn region_of(&self, cell: CellLocation) -> usize {
+        self.region_map[cell.row][cell.col]
+    }
+
+    pub fn regions(&self) -> &[House] {
+        &self.regions
+    }
+
+    pub fn has_box_regions(&self) -> bool {
+        self.region_map == Self::box_region_map()
+    }
+
+    pub fn extra_houses(&self) -> &[House] {
+        &self.extra_houses
+    }
+
+    pub fn house_presets(&self) -> &[HousePreset] {
+        &self.house_presets
+    }
+
+    pub fn constraints(&self) -> &[Rc<dyn Constraint>] {
+        &self.constraints
+    }
+
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    pub fn metadata_mut(&mut self) -> &mut Metadata {
+        &mut self.metadata
+    }
+
+    pub fn to_document(&self) -> Result<String, SudokuError> {
+        document::write(self)
+    }
+
+    pub fn to_svg(&self, options: &DrawOptions) -> String {
+        svg::write(&Drawing::new(self, options), svg::CELL_SIZE)
+    }
+
+    /// Draws the table as a PNG image.
+    pub fn to_png(&self, options: &DrawOptions, raster_options: &RasterOptions) -> Vec<u8> {
+        let image = raster::render(&Drawing::new(self, options), raster_options);
+        png::encode(image.width, image.height, &image.pixels)
+    }
+
+    /// Draws the table as a TikZ picture, to be included in a LaTeX document.
+    pub fn to_tikz(&self, options: &DrawOptions) -> String {
+        tikz::write(&[Drawing::new(self, options)], tikz::CELL_SIZE)
+    }
+
+    pub fn to_single_line(&self) -> String {
+        self.contents
+            .iter()
+            .flatten()
+            .map(|cell| match cell {
+                SudokuCell::Filled(x) => std::char::from_digit(*x as u32, 10).unwrap(),
+                SudokuCell::Empty => '.',
+            })
+            .collect()
+    }
+
+    pub fn add_constraint(&mut self, constraint: impl Constraint + 'static) {
+        self.constraints.push(Rc::new(constraint));
+    }
+
+    fn write_grid(&self, f: &mut impl std::fmt::Write) -> std::fmt::Result {
+        for i in 0usize..3 {
+            let row_start = 3 * i;
+            Self::write_top_row(f, &self.contents[row_start])?;
+            Self::write_middle_row(f, &self.contents[row_start + 1])?;
+            Self::write_bottom_row(f, &self.contents[row_start + 2])?;
+        }
+
+        Ok(())
+    }
+
+    fn write_top_row(f: &mut impl std::fmt::Write, values: &[SudokuCell]) -> std::fmt::Result {
         writeln!(f, "┌───┬───┬───┐ ┌───┬───┬───┐ ┌───┬───┬───┐")?;
         Self::write_middle_row(f, values)
     }
 
-    fn write_middle_row(
-        f: &mut std::fmt::Formatter<'_>,
-        values: &[SudokuCell],
-    ) -> std::fmt::Result {
+    fn write_middle_row(f: &mut impl std::fmt::Write, values: &[SudokuCell]) -> std::fmt::Result {
         Self::write_row_of_nums(f, values)?;
         writeln!(f, "├───┼───┼───┤ ├───┼───┼───┤ ├───┼───┼───┤")
     }
 
-    fn write_row_of_nums(
-        f: &mut std::fmt::Formatter<'_>,
-        values: &[SudokuCell],
-    ) -> std::fmt::Result {
+    fn write_row_of_nums(f: &mut impl std::fmt::Write, values: &[SudokuCell]) -> std::fmt::Result {
         writeln!(
             f,
             "│ {} │ {} │ {} │ │ {} │ {} │ {} │ │ {} │ {} │ {} │",
@@ -206,10 +548,7 @@ impl SudokuTable {
         )
     }
 
-    fn write_bottom_row(
-        f: &mut std::fmt::Formatter<'_>,
-        values: &[SudokuCell],
-    ) -> std::fmt::Result {
+    fn write_bottom_row(f: &mut impl std::fmt::Write, values: &[SudokuCell]) -> std::fmt::Result {
         Self::write_row_of_nums(f, values)?;
         writeln!(f, "└───┴───┴───┘ └───┴───┴───┘ └───┴───┴───┘")
     }
@@ -217,20 +556,35 @@ impl SudokuTable {
 
 impl Display for SudokuTable {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for i in 0usize..3 {
-            let row_start = 3 * i;
-            Self::write_top_row(f, &self.contents[row_start])?;
-            Self::write_middle_row(f, &self.contents[row_start + 1])?;
-            Self::write_bottom_row(f, &self.contents[row_start + 2])?;
+        write!(f, "{}", self.display_marked(&[]))
+    }
+}
+
+pub struct MarkedTable<'a> {
+    table: &'a SudokuTable,
+    cells: &'a [CellLocation],
+}
+
+impl Display for MarkedTable<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut canvas = Canvas::new(self.table)?;
+
+        for constraint in &self.table.constraints {
+            constraint.render(&mut canvas);
         }
 
-        Ok(())
+        for cell in self.cells {
+            canvas.mark_cell(*cell);
+        }
+
+        write!(f, "{}", canvas)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::SudokuTable;
+    use super::{CellLocation, SudokuTable};
+    use crate::error::SudokuError;
 
     #[test]
     fn correct_table_string() {
@@ -244,8 +598,9 @@ mod tests {
         XXXXXXX74\n\
         XX52X63XX\n";
 
-        let SudokuTable { contents: table } =
-            SudokuTable::from_string(correct_table_string.lines().map(String::from)).unwrap();
+        let SudokuTable {
+            contents: table, ..
+        } = SudokuTable::from_string(correct_table_string.lines().map(String::from)).unwrap();
 
         assert_eq!(table.len(), 9);
         for row in &table {
@@ -254,7 +609,19 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "found 8")]
+    fn single_line_table_string() {
+        let table_string =
+            "3.65.84..52.......087....31..3.1..8.9..863..5.5..9.6..13....25.XXXXXXX74..52.63..";
+
+        let table = SudokuTable::from_string(std::iter::once(String::from(table_string))).unwrap();
+
+        assert_eq!(
+            table.to_single_line(),
+            "3.65.84..52........87....31..3.1..8.9..863..5.5..9.6..13....25........74..52.63.."
+        );
+    }
+
+    #[test]
     fn incorrect_table_string_too_few_lines() {
         let incorrect_table_string = "3X65X84XX\n\
         52XXXXXXX\n\
@@ -265,11 +632,15 @@ mod tests {
         13XXXX25X\n\
         XX52X63XX\n";
 
-        SudokuTable::from_string(incorrect_table_string.lines().map(String::from)).unwrap();
+        let result = SudokuTable::from_string(incorrect_table_string.lines().map(String::from));
+
+        assert!(matches!(
+            result,
+            Err(SudokuError::WrongLineCount { found: 8 })
+        ));
     }
 
     #[test]
-    #[should_panic(expected = "found more")]
     fn incorrect_table_string_too_many_lines() {
         let incorrect_table_string = "3X65X84XX\n\
         52XXXXXXX\n\
@@ -282,11 +653,23 @@ mod tests {
         XX52X63XX\n
         7XXXXXXXX\n";
 
-        SudokuTable::from_string(incorrect_table_string.lines().map(String::from)).unwrap();
+        let result = SudokuTable::from_string(incorrect_table_string.lines().map(String::from));
+
+        assert!(matches!(
+            result,
+            Err(SudokuError::WrongLineCount { found: 10 })
+        ));
+
+        let lines = incorrect_table_string.lines().chain(["123456789"; 40]);
+        let result = SudokuTable::from_string(lines.map(String::from));
+
+        assert!(matches!(
+            result,
+            Err(SudokuError::WrongLineCount { found: 50 })
+        ));
     }
 
     #[test]
-    #[should_panic(expected = "have exactly 9 characters")]
     fn incorrect_table_string_invalid_line_length() {
         let incorrect_table_string = "3X65X84XX\n\
         52XXXXXXX\n\
@@ -298,11 +681,15 @@ mod tests {
         XXXXXXX7\n\
         XX52X63XX\n";
 
-        SudokuTable::from_string(incorrect_table_string.lines().map(String::from)).unwrap();
+        let result = SudokuTable::from_string(incorrect_table_string.lines().map(String::from));
+
+        assert!(matches!(
+            result,
+            Err(SudokuError::WrongLineLength { line: 8, length: 8 })
+        ));
     }
 
     #[test]
-    #[should_panic(expected = "illegal table")]
     fn incorrect_table_string_invalid_col() {
         let incorrect_table_string = "3X65X84XX\n\
         52XXXXXXX\n\
@@ -314,7 +701,56 @@ mod tests {
         XXXXXXX74\n\
         5X52X63XX\n";
 
-        SudokuTable::from_string(incorrect_table_string.lines().map(String::from)).unwrap();
+        let result = SudokuTable::from_string(incorrect_table_string.lines().map(String::from));
+
+        match result {
+            Err(SudokuError::RuleConflict { cells, .. }) => assert_eq!(
+                cells,
+                vec![
+                    CellLocation::new(1, 0),
+                    CellLocation::new(8, 0),
+                    CellLocation::new(8, 2)
+                ]
+            ),
+            _ => panic!("expected a rule conflict"),
+        }
+    }
+
+    #[test]
+    fn incorrect_table_string_illegal_character() {
+        let incorrect_table_string = "3X65X84XX\n\
+        52XXXXXXX\n\
+        X87XXXX31\n\
+        XX3X1XX8X\n\
+        9XX8?3XX5\n";
+
+        let result = SudokuTable::from_string(incorrect_table_string.lines().map(String::from));
+
+        assert_eq!(
+            result.err().unwrap().to_string(),
+            "Invalid input: illegal character '?' at line 5, column 5"
+        );
+    }
+
+    #[test]
+    fn display_marked_cells() {
+        let input_table = "391867542\n\
+        286534719\n\
+        457291386\n\
+        129645873\n\
+        638179254\n\
+        745328691\n\
+        972486135\n\
+        564713928\n\
+        813952467\n";
+
+        let table = SudokuTable::from_string(input_table.lines().map(String::from)).unwrap();
+        let marked = table.display_marked(&[CellLocation::new(0, 1)]).to_string();
+
+        assert_eq!(
+            marked.lines().nth(1).unwrap(),
+            "│ 3 │>9<│ 1 │ │ 8 │ 6 │ 7 │ │ 5 │ 4 │ 2 │"
+        );
     }
 
     #[test]
@@ -355,4 +791,46 @@ mod tests {
 
         assert_eq!(format!("{}", sudoku_table).trim(), correct_display.trim());
     }
+
+    #[test]
+    fn display_sandwich_clues() {
+        let input_table = "391867542\n\
+        286534719\n\
+        457291386\n\
+        129645873\n\
+        638179254\n\
+        745328691\n\
+        972486135\n\
+        564713928\n\
+        813952467\n\
+        sandwich-rows: X X X 2 7 X 27 3 3\n\
+        sandwich-cols: 13 35 X 14 21 5 0 20 13\n";
+
+        let sudoku_table = SudokuTable::from_string(input_table.lines().map(String::from)).unwrap();
+        let display = format!("{}", sudoku_table);
+        let lines: Vec<&str> = display.lines().collect();
+
+        assert_eq!(lines[0], "    13  35        14  21   5     0  20  13");
+        assert_eq!(lines[1], "   ┌───┬───┬───┐ ┌───┬───┬───┐ ┌───┬───┬───┐");
+        assert_eq!(lines[2], "   │ 3 │ 9 │ 1 │ │ 8 │ 6 │ 7 │ │ 5 │ 4 │ 2 │");
+        assert_eq!(lines[16], "27 │ 9 │ 7 │ 2 │ │ 4 │ 8 │ 6 │ │ 1 │ 3 │ 5 │");
+    }
+
+    #[test]
+    fn incorrect_sandwich_clue() {
+        let incorrect_table_string = "391867542\n\
+        286534719\n\
+        457291386\n\
+        129645873\n\
+        638179254\n\
+        745328691\n\
+        972486135\n\
+        564713928\n\
+        813952467\n\
+        sandwich-rows: 1 X X X X X X X X\n";
+
+        let result = SudokuTable::from_string(incorrect_table_string.lines().map(String::from));
+
+        assert!(matches!(result, Err(SudokuError::RuleConflict { .. })));
+    }
 }
@@ -0,0 +1,177 @@
+use super::constraint::{self, Constraint};
+use super::drawing::{Color, Drawing, Point, Shape};
+use super::{CellLocation, SudokuCell, SudokuTable};
+
+pub struct Arrow {
+    circle: Vec<CellLocation>,
+    shaft: Vec<CellLocation>,
+}
+
+impl Arrow {
+    pub fn from_str(arrow_str: &str) -> Result<Arrow, String> {
+        let (circle_str, shaft_str) = match arrow_str.split_once("->") {
+            Some(x) => x,
+            None => {
+                return Err(format!(
+                    "Invalid input: arrow should be written as '<circle> -> <shaft>', found '{}'",
+                    arrow_str
+                ))
+            }
+        };
+
+        let circle = Self::cells_from_str(circle_str)?;
+        let shaft = Self::cells_from_str(shaft_str)?;
+
+        if circle.is_empty() || circle.len() > 2 || shaft.is_empty() {
+            return Err(format!(
+                "Invalid input: arrow should have a circle of 1 or 2 cells and a shaft, found '{}'",
+                arrow_str
+            ));
+        }
+
+        let cells: Vec<&CellLocation> = circle.iter().chain(shaft.iter()).collect();
+        for (i, cell) in cells.iter().enumerate() {
+            if cells[..i].contains(cell) {
+                return Err(format!("Invalid input: cell {} repeated in arrow", cell));
+            }
+        }
+
+        if let [first, second] = circle[..] {
+            if first.row.abs_diff(second.row) > 1 || first.col.abs_diff(second.col) > 1 {
+                return Err(format!(
+                    "Invalid input: cells {} and {} of arrow circle are not adjacent",
+                    first, second
+                ));
+            }
+        }
+
+        Ok(Arrow { circle, shaft })
+    }
+
+    fn cells_from_str(cells_str: &str) -> Result<Vec<CellLocation>, String> {
+        cells_str
+            .split_whitespace()
+            .map(|x| x.parse::<CellLocation>())
+            .collect()
+    }
+
+    fn circle_bounds(&self, table: &SudokuTable) -> (u32, u32) {
+        self.circle.iter().fold((0, 0), |(least, most), cell| {
+            match table.contents()[cell.row][cell.col] {
+                SudokuCell::Filled(x) => (10 * least + x as u32, 10 * most + x as u32),
+                SudokuCell::Empty => (10 * least + 1, 10 * most + 9),
+            }
+        })
+    }
+
+    fn shaft_bounds(&self, table: &SudokuTable) -> (u32, u32) {
+        self.shaft.iter().fold((0, 0), |(least, most), cell| {
+            match table.contents()[cell.row][cell.col] {
+                SudokuCell::Filled(x) => (least + x as u32, most + x as u32),
+                SudokuCell::Empty => (least + 1, most + 9),
+            }
+        })
+    }
+}
+
+impl Constraint for Arrow {
+    fn cells(&self) -> Vec<CellLocation> {
+        self.circle
+            .iter()
+            .chain(self.shaft.iter())
+            .copied()
+            .collect()
+    }
+
+    fn is_satisfiable(&self, table: &SudokuTable) -> bool {
+        let (circle_least, circle_most) = self.circle_bounds(table);
+        let (shaft_least, shaft_most) = self.shaft_bounds(table);
+
+        circle_least <= shaft_most && shaft_least <= circle_most
+    }
+
+    fn draw(&self, drawing: &mut Drawing) {
+        let circle: Vec<Point> = self.circle.iter().map(|x| Point::cell_center(*x)).collect();
+        let width = Drawing::THIN_LINE * 2.0;
+
+        match circle.as_slice() {
+            [center] => drawing.push_under(Shape::Circle {
+                center: *center,
+                radius: 0.4,
+                fill: Some(Color::WHITE),
+                stroke: Some((width, Color::GRAY)),
+            }),
+            // A two cell circle is drawn as a pill, a wide line covered by a narrower one.
+            _ => {
+                for (width, color) in [(0.8 + width, Color::GRAY), (0.8 - width, Color::WHITE)] {
+                    drawing.push_under(Shape::Polyline {
+                        points: circle.clone(),
+                        width,
+                        color,
+                    });
+                }
+            }
+        }
+
+        let last_circle = *circle.last().unwrap();
+        let shaft: Vec<Point> = self.shaft.iter().map(|x| Point::cell_center(*x)).collect();
+        let mut points = vec![last_circle.towards(shaft[0], 0.4)];
+        points.extend(shaft);
+
+        drawing.arrow(points, width, Color::GRAY);
+    }
+
+    fn to_line(&self) -> Option<String> {
+        Some(format!(
+            "arrow: {} -> {}",
+            constraint::cells_to_string(&self.circle),
+            constraint::cells_to_string(&self.shaft)
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::constraint::Constraint;
+    use super::super::SudokuTable;
+    use super::Arrow;
+
+    #[test]
+    fn invalid_arrows() {
+        let error = |arrow_str: &str| Arrow::from_str(arrow_str).err().unwrap();
+
+        assert!(error("r1c1 -> r1c1 r1c2").contains("cell r1c1 repeated in arrow"));
+        assert!(error("r1c1 r1c2 -> r1c3 r1c2").contains("cell r1c2 repeated in arrow"));
+        assert!(error("r1c1 -> r1c2 r1c3 r1c3").contains("cell r1c3 repeated in arrow"));
+        assert!(error("r1c1 r1c3 -> r2c2").contains("are not adjacent"));
+        assert!(error("r1c1 -> ").starts_with("Invalid input:"));
+        assert!(Arrow::from_str("r1c1 r2c2 -> r3c3 r4c3").is_ok());
+    }
+
+    #[test]
+    fn arrow_sum() {
+        let input_table = "391867542\n\
+        286534719\n\
+        457291386\n\
+        129645873\n\
+        638179254\n\
+        745328691\n\
+        972486135\n\
+        564713928\n\
+        81395246X\n";
+
+        let table = SudokuTable::from_string(input_table.lines().map(String::from)).unwrap();
+
+        assert!(Arrow::from_str("r1c2 -> r1c1 r2c1 r3c1")
+            .unwrap()
+            .is_satisfiable(&table));
+        assert!(!Arrow::from_str("r1c2 -> r1c1 r2c1")
+            .unwrap()
+            .is_satisfiable(&table));
+        assert!(
+            Arrow::from_str("r9c9 r9c8 -> r1c1 r1c2 r1c4 r1c5 r1c6 r1c7")
+                .unwrap()
+                .is_satisfiable(&table)
+        );
+    }
+}
@@ -0,0 +1,129 @@
+use super::constraint::{self, Constraint};
+use super::drawing::Drawing;
+use super::{CellLocation, SudokuCell, SudokuTable};
+
+pub struct KillerCage {
+    sum: Option<u32>,
+    cells: Vec<CellLocation>,
+}
+
+impl KillerCage {
+    pub fn new(sum: Option<u32>, cells: Vec<CellLocation>) -> Result<KillerCage, String> {
+        if cells.is_empty() || cells.len() > 9 {
+            return Err(format!(
+                "Invalid input: a cage should have 1 to 9 cells, found {}",
+                cells.len()
+            ));
+        }
+
+        for (i, cell) in cells.iter().enumerate() {
+            if cells[..i].contains(cell) {
+                return Err(format!("Invalid input: cell {} repeated in cage", cell));
+            }
+        }
+
+        Ok(KillerCage { sum, cells })
+    }
+
+    pub fn from_str(cage_str: &str) -> Result<KillerCage, String> {
+        let mut tokens = cage_str.split_whitespace();
+
+        let sum = match tokens.next() {
+            Some("X") => None,
+            Some(token) => match token.parse::<u32>() {
+                Ok(x) if x > 0 => Some(x),
+                _ => return Err(format!("Invalid input: illegal cage sum '{}'", token)),
+            },
+            None => return Err(String::from("Invalid input: empty cage")),
+        };
+
+        let cells = tokens
+            .map(|x| x.parse::<CellLocation>())
+            .collect::<Result<Vec<CellLocation>, String>>()?;
+
+        Self::new(sum, cells)
+    }
+}
+
+impl Constraint for KillerCage {
+    fn cells(&self) -> Vec<CellLocation> {
+        self.cells.clone()
+    }
+
+    fn is_satisfiable(&self, table: &SudokuTable) -> bool {
+        let mut present = [false; 10];
+        let mut filled_sum = 0u32;
+        let mut empty_count = 0usize;
+
+        for cell in &self.cells {
+            match table.contents()[cell.row][cell.col] {
+                SudokuCell::Filled(x) if present[x as usize] => return false,
+                SudokuCell::Filled(x) => {
+                    present[x as usize] = true;
+                    filled_sum += x as u32;
+                }
+                SudokuCell::Empty => empty_count += 1,
+            }
+        }
+
+        let sum = match self.sum {
+            Some(x) => x,
+            None => return true,
+        };
+
+        let available: Vec<u32> = (1u32..=9).filter(|x| !present[*x as usize]).collect();
+        let least_sum: u32 = available.iter().take(empty_count).sum();
+        let most_sum: u32 = available.iter().rev().take(empty_count).sum();
+
+        filled_sum + least_sum <= sum && sum <= filled_sum + most_sum
+    }
+
+    fn draw(&self, drawing: &mut Drawing) {
+        drawing.cage(&self.cells, self.sum.map(|x| x.to_string()));
+    }
+
+    fn to_line(&self) -> Option<String> {
+        let sum = match self.sum {
+            Some(x) => x.to_string(),
+            None => String::from("X"),
+        };
+
+        Some(format!(
+            "cage: {} {}",
+            sum,
+            constraint::cells_to_string(&self.cells)
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::constraint::Constraint;
+    use super::super::SudokuTable;
+    use super::KillerCage;
+
+    #[test]
+    fn cage_sum() {
+        let input_table = "391867542\n\
+        286534719\n\
+        457291386\n\
+        129645873\n\
+        638179254\n\
+        745328691\n\
+        972486135\n\
+        564713928\n\
+        81395246X\n";
+
+        let table = SudokuTable::from_string(input_table.lines().map(String::from)).unwrap();
+
+        assert!(KillerCage::from_str("12 r1c1 r1c2")
+            .unwrap()
+            .is_satisfiable(&table));
+        assert!(KillerCage::from_str("13 r9c8 r9c9")
+            .unwrap()
+            .is_satisfiable(&table));
+        assert!(!KillerCage::from_str("5 r9c8 r9c9")
+            .unwrap()
+            .is_satisfiable(&table));
+    }
+}
@@ -0,0 +1,169 @@
+use std::fmt::Display;
+
+use super::constraint;
+use super::{CellLocation, SudokuCell, SudokuTable};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConflictSource {
+    Row(usize),
+    Column(usize),
+    Box(usize),
+    Region(usize),
+    ExtraHouse(usize),
+    Constraint(String),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Conflict {
+    pub source: ConflictSource,
+    pub digit: Option<u8>,
+    pub cells: Vec<CellLocation>,
+}
+
+impl Display for ConflictSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConflictSource::Row(i) => write!(f, "row {}", i + 1),
+            ConflictSource::Column(i) => write!(f, "column {}", i + 1),
+            ConflictSource::Box(i) => write!(f, "box {}", i + 1),
+            ConflictSource::Region(i) => write!(f, "region {}", i + 1),
+            ConflictSource::ExtraHouse(i) => write!(f, "extra house {}", i + 1),
+            ConflictSource::Constraint(name) => write!(f, "{} rule", name),
+        }
+    }
+}
+
+impl Display for Conflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let cells = constraint::cells_to_string(&self.cells);
+
+        if let ConflictSource::Constraint(_) = self.source {
+            return write!(f, "{} is broken by {}", self.source, cells);
+        }
+
+        write!(f, "{}", self.source)?;
+
+        match self.digit {
+            Some(digit) => write!(f, ": digit {} is repeated in {}", digit, cells),
+            None => write!(f, ": conflict in {}", cells),
+        }
+    }
+}
+
+pub fn houses(table: &SudokuTable) -> Vec<(ConflictSource, Vec<CellLocation>)> {
+    let mut result = vec![];
+
+    for i in 0..9 {
+        let row = (0..9).map(|j| CellLocation::new(i, j)).collect();
+        result.push((ConflictSource::Row(i), row));
+    }
+
+    for i in 0..9 {
+        let col = (0..9).map(|j| CellLocation::new(j, i)).collect();
+        result.push((ConflictSource::Column(i), col));
+    }
+
+    for (i, region) in table.regions().iter().enumerate() {
+        let source = match table.has_box_regions() {
+            true => ConflictSource::Box(i),
+            false => ConflictSource::Region(i),
+        };
+
+        result.push((source, region.to_vec()));
+    }
+
+    for (i, house) in table.extra_houses().iter().enumerate() {
+        result.push((ConflictSource::ExtraHouse(i), house.to_vec()));
+    }
+
+    result
+}
+
+pub fn find_conflicts(table: &SudokuTable) -> Vec<Conflict> {
+    let mut result = vec![];
+
+    for (source, house) in houses(table) {
+        add_repeated_digits(table, source, &house, &mut result);
+    }
+
+    for constraint in table.constraints() {
+        if constraint.is_satisfiable(table) {
+            continue;
+        }
+
+        let name = constraint
+            .to_line()
+            .and_then(|x| x.split_once(':').map(|(key, _)| key.to_string()))
+            .unwrap_or_else(|| String::from("custom"));
+        let cells = constraint
+            .cells()
+            .into_iter()
+            .filter(|x| table.contents()[x.row][x.col] != SudokuCell::Empty)
+            .collect();
+
+        result.push(Conflict {
+            source: ConflictSource::Constraint(name),
+            digit: None,
+            cells,
+        });
+    }
+
+    result
+}
+
+fn add_repeated_digits(
+    table: &SudokuTable,
+    source: ConflictSource,
+    house: &[CellLocation],
+    result: &mut Vec<Conflict>,
+) {
+    for digit in 1..=9 {
+        let cells: Vec<CellLocation> = house
+            .iter()
+            .filter(|x| table.contents()[x.row][x.col] == SudokuCell::Filled(digit))
+            .copied()
+            .collect();
+
+        if cells.len() > 1 {
+            result.push(Conflict {
+                source: source.clone(),
+                digit: Some(digit),
+                cells,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{ParseMode, SudokuTable};
+
+    #[test]
+    fn every_violated_house() {
+        let input_table = "5X65X84XX\n\
+        52XXXXXXX\n\
+        X87XXXX31\n\
+        XX3X1XX8X\n\
+        9XX863XX5\n\
+        X5XX9X6XX\n\
+        13XXXX25X\n\
+        XXXXXXX74\n\
+        XX52X63XX\n\
+        cage: 10 r9c3 r9c4\n";
+
+        let table =
+            SudokuTable::parse_unchecked(input_table.lines().map(String::from), ParseMode::Strict)
+                .unwrap();
+        let conflicts: Vec<String> = table.conflicts().iter().map(|x| x.to_string()).collect();
+
+        assert_eq!(
+            conflicts,
+            vec![
+                "row 1: digit 5 is repeated in r1c1 r1c4",
+                "column 1: digit 5 is repeated in r1c1 r2c1",
+                "box 1: digit 5 is repeated in r1c1 r2c1",
+                "cage rule is broken by r9c3 r9c4",
+            ]
+        );
+    }
+}
@@ -0,0 +1,135 @@
+use std::fmt::Display;
+
+use super::conflict::{self, ConflictSource};
+use super::constraint;
+use super::solver::SudokuSolver;
+use super::{CellLocation, SudokuCell, SudokuTable};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Contradiction {
+    DeadCell(CellLocation),
+    DeadDigit {
+        source: ConflictSource,
+        digit: u8,
+        cells: Vec<CellLocation>,
+    },
+}
+
+impl Contradiction {
+    pub fn cells(&self) -> &[CellLocation] {
+        match self {
+            Self::DeadCell(cell) => std::slice::from_ref(cell),
+            Self::DeadDigit { cells, .. } => cells,
+        }
+    }
+}
+
+impl Display for Contradiction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::DeadCell(cell) => write!(f, "{} has no candidates left", cell),
+            Self::DeadDigit {
+                source,
+                digit,
+                cells,
+            } => write!(
+                f,
+                "{}: digit {} has no place left among {}",
+                source,
+                digit,
+                constraint::cells_to_string(cells)
+            ),
+        }
+    }
+}
+
+pub fn find_contradictions(table: &SudokuTable) -> Vec<Contradiction> {
+    find_contradictions_with(table, SudokuSolver::new(table))
+}
+
+/// Finds the contradictions among the candidates that `solver` leaves in the empty cells of
+/// `table`, such as those of a player's pencil marks.
+pub fn find_contradictions_with(
+    table: &SudokuTable,
+    mut solver: SudokuSolver,
+) -> Vec<Contradiction> {
+    let mut candidates = vec![vec![]; 81];
+    let mut result = vec![];
+
+    for row in 0..9 {
+        for col in 0..9 {
+            if table.contents()[row][col] != SudokuCell::Empty {
+                continue;
+            }
+
+            let cell = CellLocation::new(row, col);
+            candidates[row * 9 + col] = solver.candidates(cell);
+
+            if candidates[row * 9 + col].is_empty() {
+                result.push(Contradiction::DeadCell(cell));
+            }
+        }
+    }
+
+    for (source, house) in conflict::houses(table) {
+        let empty_cells: Vec<CellLocation> = house
+            .iter()
+            .filter(|x| table.contents()[x.row][x.col] == SudokuCell::Empty)
+            .copied()
+            .collect();
+
+        for digit in 1..=9 {
+            let is_placed = house
+                .iter()
+                .any(|x| table.contents()[x.row][x.col] == SudokuCell::Filled(digit));
+            let has_place = empty_cells
+                .iter()
+                .any(|x| candidates[x.row * 9 + x.col].contains(&digit));
+
+            if !is_placed && !has_place {
+                result.push(Contradiction::DeadDigit {
+                    source: source.clone(),
+                    digit,
+                    cells: empty_cells.clone(),
+                });
+            }
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{ParseMode, SudokuTable};
+
+    #[test]
+    fn dead_cell_and_dead_digit() {
+        let input_table = "12345678X\n\
+        XXXXXXXX9\n\
+        XXXXXXXXX\n\
+        XXXXXXXXX\n\
+        XXXXXXXXX\n\
+        XXXXXXXXX\n\
+        XXXXXXXXX\n\
+        XXXXXXXXX\n\
+        XXXXXXXXX\n";
+
+        let table =
+            SudokuTable::parse_unchecked(input_table.lines().map(String::from), ParseMode::Strict)
+                .unwrap();
+        let contradictions: Vec<String> = table
+            .contradictions()
+            .iter()
+            .map(|x| x.to_string())
+            .collect();
+
+        assert_eq!(
+            contradictions,
+            vec![
+                "r1c9 has no candidates left",
+                "row 1: digit 9 has no place left among r1c9",
+            ]
+        );
+    }
+}
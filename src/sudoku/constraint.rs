@@ -0,0 +1,164 @@
+use super::drawing::Drawing;
+use super::render::{Canvas, Corner, Side};
+use super::{CellLocation, SudokuTable};
+
+/// A rule checked alongside the rows, columns and boxes of a table.
+///
+/// Constraints are registered with [`SudokuTable::add_constraint`], and are then
+/// enforced by both the table's validation and [`SudokuSolver`](super::solver::SudokuSolver).
+pub trait Constraint {
+    /// The cells whose values the rule depends on.
+    ///
+    /// The solver only checks the rule when one of these cells is filled in, so every cell the
+    /// rule depends on has to be listed. A rule that lists no cells is checked whenever any cell
+    /// is filled in.
+    fn cells(&self) -> Vec<CellLocation>;
+
+    /// Returns `false` if the table, in which some cells may still be empty, can no
+    /// longer satisfy the rule.
+    fn is_satisfiable(&self, table: &SudokuTable) -> bool;
+
+    /// Removes from `candidates` the values that `cell` cannot take in `table`.
+    ///
+    /// The solver still checks every remaining candidate with `is_satisfiable`, so
+    /// this is only an optimization.
+    fn prune(&self, _table: &SudokuTable, _cell: CellLocation, _candidates: &mut Vec<u8>) {}
+
+    /// The places outside the grid, as a side and a row or column index, where the rule writes
+    /// a clue. Two rules cannot share one.
+    fn outside_clues(&self) -> Vec<(Side, usize)> {
+        vec![]
+    }
+
+    /// The corners outside the grid where the rule writes a clue. Two rules cannot share one.
+    fn corner_clues(&self) -> Vec<Corner> {
+        vec![]
+    }
+
+    /// Draws the rule's clues onto the rendered table.
+    fn render(&self, _canvas: &mut Canvas) {}
+
+    /// Draws the rule's clues for the graphical renderers, such as SVG.
+    fn draw(&self, _drawing: &mut Drawing) {}
+
+    /// Describes the rule as a `name: value` line of the puzzle file format, if it has one.
+    fn to_line(&self) -> Option<String> {
+        None
+    }
+}
+
+pub fn outside_clues_from_str(
+    clues_str: &str,
+    max_clue: u8,
+    variant: &str,
+) -> Result<[Option<u8>; 9], String> {
+    let tokens: Vec<&str> = clues_str.split_whitespace().collect();
+
+    if tokens.len() != 9 {
+        return Err(format!(
+            "Invalid input: expected 9 {} clues, found {}",
+            variant,
+            tokens.len()
+        ));
+    }
+
+    let mut clues = [None; 9];
+
+    for (i, token) in tokens.into_iter().enumerate() {
+        clues[i] = match token {
+            "X" => None,
+            _ => match token.parse::<u8>() {
+                Ok(x) if x <= max_clue => Some(x),
+                _ => {
+                    return Err(format!(
+                        "Invalid input: illegal {} clue '{}'",
+                        variant, token
+                    ))
+                }
+            },
+        };
+    }
+
+    Ok(clues)
+}
+
+pub fn outside_clues_to_string(clues: &[Option<u8>; 9]) -> String {
+    clues
+        .iter()
+        .map(|x| match x {
+            Some(x) => x.to_string(),
+            None => String::from("X"),
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+pub fn cells_to_string(cells: &[CellLocation]) -> String {
+    cells
+        .iter()
+        .map(|x| x.to_string())
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::solver::SudokuSolver;
+    use super::super::{CellLocation, SudokuCell, SudokuTable};
+    use super::Constraint;
+
+    struct DistinctDiagonal;
+
+    impl Constraint for DistinctDiagonal {
+        fn cells(&self) -> Vec<CellLocation> {
+            (0usize..9).map(|i| CellLocation::new(i, i)).collect()
+        }
+
+        fn is_satisfiable(&self, table: &SudokuTable) -> bool {
+            let mut digits: Vec<u8> = self
+                .cells()
+                .into_iter()
+                .filter_map(|cell| match table.contents()[cell.row()][cell.col()] {
+                    SudokuCell::Filled(x) => Some(x),
+                    SudokuCell::Empty => None,
+                })
+                .collect();
+            let count = digits.len();
+            digits.sort();
+            digits.dedup();
+
+            digits.len() == count
+        }
+    }
+
+    #[test]
+    fn custom_constraint() {
+        let input_puzzle = "XXXXXXXXX\n".repeat(9);
+
+        let mut table = SudokuTable::from_string(input_puzzle.lines().map(String::from)).unwrap();
+        table.add_constraint(DistinctDiagonal);
+
+        let solution = SudokuSolver::new(&table).next().unwrap();
+
+        assert!(solution.is_valid_sudoku());
+        assert!(DistinctDiagonal.is_satisfiable(&solution));
+    }
+
+    #[test]
+    fn custom_constraint_validation() {
+        let input_table = "391867542\n\
+        286534719\n\
+        457291386\n\
+        129645873\n\
+        638179254\n\
+        745328691\n\
+        972486135\n\
+        564713928\n\
+        813952467\n";
+
+        let mut table = SudokuTable::from_string(input_table.lines().map(String::from)).unwrap();
+        table.add_constraint(DistinctDiagonal);
+
+        assert!(!table.is_valid_sudoku());
+    }
+}
@@ -0,0 +1,309 @@
+use std::rc::Rc;
+
+use super::arrow::Arrow;
+use super::cage::KillerCage;
+use super::constraint::{self, Constraint};
+use super::house::{self, House, HousePreset};
+use super::inequality::Inequalities;
+use super::kropki::{DotColor, KropkiDots};
+use super::line::{LineConstraint, LineKind};
+use super::little_killer::LittleKiller;
+use super::parity::ParityMask;
+use super::render::Side;
+use super::sandwich::SandwichClues;
+use super::skyscraper::SkyscraperClues;
+use super::{SudokuCell, SudokuTable};
+use crate::error::SudokuError;
+
+pub fn add_from_line(table: &mut SudokuTable, line: &str) -> Result<(), String> {
+    let (key, value) = match line.split_once(':') {
+        Some((key, value)) => (key.trim(), value.trim()),
+        None => return Err(format!("Invalid input: malformed line '{}'", line)),
+    };
+
+    if table.metadata.add_from_line(key, value) {
+        return Ok(());
+    }
+
+    if let Some(preset) = HousePreset::from_key(key) {
+        expect_no_value(value)?;
+        if !table.house_presets.contains(&preset) {
+            table.house_presets.push(preset);
+            table.extra_houses.extend(preset.houses());
+        }
+
+        return Ok(());
+    }
+
+    let constraint: Rc<dyn Constraint> = match key {
+        "size" => return check_size(value),
+        "regions" => return table.set_region_map(region_map_from_str(value)?),
+        "house" => {
+            table.extra_houses.push(house::house_from_str(value)?);
+            return Ok(());
+        }
+        "sandwich-rows" => Rc::new(SandwichClues::rows_from_str(value)?),
+        "sandwich-cols" => Rc::new(SandwichClues::cols_from_str(value)?),
+        "skyscrapers-top" => Rc::new(SkyscraperClues::from_str(Side::Top, value)?),
+        "skyscrapers-bottom" => Rc::new(SkyscraperClues::from_str(Side::Bottom, value)?),
+        "skyscrapers-left" => Rc::new(SkyscraperClues::from_str(Side::Left, value)?),
+        "skyscrapers-right" => Rc::new(SkyscraperClues::from_str(Side::Right, value)?),
+        "little-killer" => Rc::new(LittleKiller::from_str(value)?),
+        "parity" => Rc::new(ParityMask::from_str(value)?),
+        "greater-than" => Rc::new(Inequalities::from_str(value)?),
+        "whisper" => Rc::new(LineConstraint::from_str(LineKind::GermanWhispers, value)?),
+        "renban" => Rc::new(LineConstraint::from_str(LineKind::Renban, value)?),
+        "palindrome" => Rc::new(LineConstraint::from_str(LineKind::Palindrome, value)?),
+        "region-sum" => Rc::new(LineConstraint::from_str(LineKind::RegionSum, value)?),
+        "between" => Rc::new(LineConstraint::from_str(LineKind::Between, value)?),
+        "thermo" => Rc::new(LineConstraint::from_str(LineKind::Thermometer, value)?),
+        "arrow" => Rc::new(Arrow::from_str(value)?),
+        "cage" => Rc::new(KillerCage::from_str(value)?),
+        "kropki-white" => Rc::new(KropkiDots::from_str(DotColor::White, value)?),
+        "kropki-black" => Rc::new(KropkiDots::from_str(DotColor::Black, value)?),
+        _ => return Err(format!("Invalid input: unknown variant '{}'", key)),
+    };
+
+    for (side, index) in constraint.outside_clues() {
+        let is_taken = table
+            .constraints
+            .iter()
+            .any(|x| x.outside_clues().contains(&(side, index)));
+
+        if is_taken {
+            let place = match side {
+                Side::Top => "above column",
+                Side::Bottom => "below column",
+                Side::Left => "left of row",
+                Side::Right => "right of row",
+            };
+            return Err(format!(
+                "Invalid input: the clue {} {} of '{}' is already given by another rule",
+                place,
+                index + 1,
+                key
+            ));
+        }
+    }
+
+    for corner in constraint.corner_clues() {
+        if table
+            .constraints
+            .iter()
+            .any(|x| x.corner_clues().contains(&corner))
+        {
+            return Err(format!(
+                "Invalid input: the corner clue of '{}' is already given by another rule",
+                key
+            ));
+        }
+    }
+
+    table.constraints.push(constraint);
+
+    Ok(())
+}
+
+fn check_size(value: &str) -> Result<(), String> {
+    match value {
+        "9" | "9x9" => Ok(()),
+        _ => Err(format!(
+            "Invalid input: unsupported grid size '{}', only 9x9 grids are supported",
+            value
+        )),
+    }
+}
+
+fn expect_no_value(value: &str) -> Result<(), String> {
+    match value.is_empty() {
+        true => Ok(()),
+        false => Err(format!("Invalid input: unexpected value '{}'", value)),
+    }
+}
+
+fn region_map_from_str(regions_str: &str) -> Result<[[usize; 9]; 9], String> {
+    let rows: Vec<&str> = regions_str.split_whitespace().collect();
+
+    if rows.len() != 9 {
+        return Err(format!(
+            "Invalid input: expected 9 region map rows, found {}",
+            rows.len()
+        ));
+    }
+
+    let mut result = [[0; 9]; 9];
+
+    for (i, row) in rows.into_iter().enumerate() {
+        if row.chars().count() != 9 {
+            return Err(String::from(
+                "Invalid input: region map row should have exactly 9 characters",
+            ));
+        }
+
+        for (j, char) in row.chars().enumerate() {
+            result[i][j] = match char {
+                '1'..='9' => char.to_digit(10).unwrap() as usize - 1,
+                _ => {
+                    return Err(format!(
+                        "Invalid input: illegal region map character '{}'",
+                        char
+                    ))
+                }
+            };
+        }
+    }
+
+    Ok(result)
+}
+
+pub fn write(table: &SudokuTable) -> Result<String, SudokuError> {
+    let mut lines = table.metadata().to_lines();
+    lines.push(String::from("size: 9"));
+
+    for row in table.contents() {
+        lines.push(
+            row.iter()
+                .map(|cell| match cell {
+                    SudokuCell::Filled(x) => std::char::from_digit(*x as u32, 10).unwrap(),
+                    SudokuCell::Empty => 'X',
+                })
+                .collect(),
+        );
+    }
+
+    lines.extend(rule_lines(table)?);
+
+    Ok(lines.join("\n") + "\n")
+}
+
+pub fn rule_lines(table: &SudokuTable) -> Result<Vec<String>, SudokuError> {
+    let mut lines = vec![];
+
+    if !table.has_box_regions() {
+        let rows: Vec<String> = table
+            .region_map
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|x| std::char::from_digit(*x as u32 + 1, 10).unwrap())
+                    .collect()
+            })
+            .collect();
+        lines.push(format!("regions: {}", rows.join(" ")));
+    }
+
+    let mut preset_houses: Vec<House> = vec![];
+    for preset in table.house_presets() {
+        lines.push(format!("{}:", preset.key()));
+        preset_houses.extend(preset.houses());
+    }
+
+    for house in table.extra_houses() {
+        if !preset_houses.contains(house) {
+            lines.push(format!("house: {}", constraint::cells_to_string(house)));
+        }
+    }
+
+    for constraint in table.constraints() {
+        match constraint.to_line() {
+            Some(line) => lines.push(line),
+            None => return Err(SudokuError::UnwritableConstraint),
+        }
+    }
+
+    Ok(lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::SudokuTable;
+
+    #[test]
+    fn round_trip() {
+        let document = "title: Test puzzle\n\
+        comment: written by hand\n\
+        size: 9\n\
+        XX1XXXXX2\n\
+        XXXX34XXX\n\
+        X5XXX1XX6\n\
+        X2X6XXXX3\n\
+        X3XXXXX5X\n\
+        7XXXX8X9X\n\
+        9XX4XXX3X\n\
+        XXXXXXXXX\n\
+        XXXXXXXXX\n\
+        regions: 111222333 111222333 111223233 444555666 444555666 444555666 777888999 777888999 777888999\n\
+        house: r1c1 r2c2 r3c3 r4c4 r5c5 r6c6 r7c7 r8c8 r9c9\n\
+        sandwich-rows: 0 X X 2 7 X 27 3 3\n\
+        skyscrapers-bottom: 2 X 5 X 3 X X X 3\n\
+        little-killer: r1c2 SE 47\n\
+        parity: OXXXXXXXX XXXXXXXXX XXXXXXXXX XXXXXXXXX XXXXXXXXX XXXXXXXXX XXXXXXXXX XXXXXXXXX XXXXXXXXX\n\
+        greater-than: r9c4>r9c3\n\
+        whisper: r7c1 r8c2\n\
+        cage: 12 r1c1 r1c2\n\
+        kropki-black: r8c1-r8c2\n";
+
+        let table = SudokuTable::from_string(document.lines().map(String::from)).unwrap();
+
+        assert_eq!(table.to_document().unwrap(), document);
+    }
+
+    #[test]
+    fn house_presets_round_trip() {
+        let rules = "windoku:\n\
+        disjoint-groups:\n\
+        house: r1c1 r1c9 r9c1 r9c9 r5c5 r2c5 r8c5 r5c2 r5c8\n";
+        let document = format!("size: 9\n{}{}", "XXXXXXXXX\n".repeat(9), rules);
+
+        let table = SudokuTable::from_string(document.lines().map(String::from)).unwrap();
+        assert_eq!(table.extra_houses().len(), 14);
+        assert_eq!(table.to_document().unwrap(), document);
+
+        let repeated = format!("{}windoku:\n", document);
+        let table = SudokuTable::from_string(repeated.lines().map(String::from)).unwrap();
+        assert_eq!(table.extra_houses().len(), 14);
+        assert_eq!(table.to_document().unwrap(), document);
+    }
+
+    #[test]
+    #[should_panic(expected = "region 2 should have exactly 9 cells")]
+    fn invalid_region_map() {
+        let document = "XXXXXXXXX\n".repeat(9)
+            + "regions: 111222333 111222333 111222233 444555666 444555666 444555666 \
+            777888999 777888999 777888999\n";
+
+        SudokuTable::from_string(document.lines().map(String::from)).unwrap();
+    }
+
+    #[test]
+    fn clashing_outside_clues() {
+        let grid = "XXXXXXXXX\n".repeat(9);
+        let parse = |rules: &str| {
+            let document = format!("{}{}", grid, rules);
+            SudokuTable::from_string(document.lines().map(String::from))
+        };
+
+        assert!(
+            parse("sandwich-rows: X X X 5 X X X X X\nskyscrapers-left: X X 3 X X X X X X\n")
+                .is_ok()
+        );
+        assert!(parse("sandwich-cols: X 5 X X X X X X X\nlittle-killer: r1c1 SE 20\n").is_ok());
+
+        let error =
+            parse("sandwich-rows: X X X 5 X X X X X\nskyscrapers-left: X X X 3 X X X X X\n")
+                .err()
+                .unwrap();
+        assert!(error
+            .to_string()
+            .contains("the clue left of row 4 of 'skyscrapers-left' is already given"));
+        assert!(parse("sandwich-cols: X 5 X X X X X X X\nlittle-killer: r1c3 SE 20\n").is_err());
+        assert!(parse("little-killer: r1c1 SE 45\nlittle-killer: r9c9 NW 45\n").is_ok());
+
+        let error = parse("little-killer: r1c1 SE 45\nlittle-killer: r1c1 SE 40\n")
+            .err()
+            .unwrap();
+        assert!(error
+            .to_string()
+            .contains("the corner clue of 'little-killer' is already given"));
+    }
+}
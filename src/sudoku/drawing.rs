@@ -0,0 +1,597 @@
+use super::pencil_marks::PencilMarks;
+use super::render::{Corner, Side};
+use super::solver::SudokuSolver;
+use super::{CellLocation, SudokuCell, SudokuTable};
+
+/// A position on the drawing, in cells: the grid spans from `(0, 0)` to `(9, 9)`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Point {
+    pub fn new(x: f64, y: f64) -> Point {
+        Point { x, y }
+    }
+
+    pub fn cell_center(cell: CellLocation) -> Point {
+        Point::new(cell.col as f64 + 0.5, cell.row as f64 + 0.5)
+    }
+
+    pub fn between(first: CellLocation, second: CellLocation) -> Point {
+        let first = Self::cell_center(first);
+        let second = Self::cell_center(second);
+
+        Point::new((first.x + second.x) / 2.0, (first.y + second.y) / 2.0)
+    }
+
+    /// Moves the point `distance` cells towards `target`.
+    pub fn towards(self, target: Point, distance: f64) -> Point {
+        let (dx, dy) = (target.x - self.x, target.y - self.y);
+        let length = (dx * dx + dy * dy).sqrt();
+
+        if length == 0.0 {
+            return self;
+        }
+
+        Point::new(
+            self.x + dx * distance / length,
+            self.y + dy * distance / length,
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Color(pub u8, pub u8, pub u8);
+
+impl Color {
+    pub const BLACK: Color = Color(0, 0, 0);
+    pub const WHITE: Color = Color(255, 255, 255);
+    pub const GRAY: Color = Color(120, 120, 120);
+    pub const LIGHT_GRAY: Color = Color(200, 200, 200);
+    pub const SHADE: Color = Color(232, 232, 232);
+    pub const BLUE: Color = Color(30, 90, 200);
+    pub const HIGHLIGHT: Color = Color(255, 230, 140);
+
+    pub fn to_hex(self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+}
+
+/// Sizes and widths are in cells, like positions.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Shape {
+    Line {
+        from: Point,
+        to: Point,
+        width: f64,
+        color: Color,
+        dashed: bool,
+    },
+    /// Drawn with round caps and joins.
+    Polyline {
+        points: Vec<Point>,
+        width: f64,
+        color: Color,
+    },
+    Circle {
+        center: Point,
+        radius: f64,
+        fill: Option<Color>,
+        stroke: Option<(f64, Color)>,
+    },
+    Rect {
+        corner: Point,
+        width: f64,
+        height: f64,
+        fill: Color,
+    },
+    /// Centered on `center`, both ways. `size` is the font size.
+    Text {
+        center: Point,
+        text: String,
+        size: f64,
+        color: Color,
+        bold: bool,
+    },
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct DrawOptions<'a> {
+    /// Its digits are drawn in the empty cells of the table, in the solved digit style.
+    pub solution: Option<&'a SudokuTable>,
+    /// Draws the candidates of the empty cells.
+    pub pencil_marks: bool,
+    /// The player's progress read with the puzzle. Their values are drawn in the solved digit
+    /// style, and the candidates they left are drawn instead of those the givens allow.
+    pub progress: Option<&'a PencilMarks>,
+    /// Cells filled with the highlight color, under the grid lines.
+    pub highlighted: &'a [CellLocation],
+}
+
+/// The layout of a table, shared by the renderers.
+///
+/// Shapes are drawn in order: those pushed with [`Drawing::push_under`] first, then the grid
+/// lines, then those pushed with [`Drawing::push`].
+pub struct Drawing {
+    under: Vec<Shape>,
+    grid: Vec<Shape>,
+    over: Vec<Shape>,
+    has_outside_clues: bool,
+}
+
+impl Drawing {
+    pub const THIN_LINE: f64 = 0.02;
+    pub const THICK_LINE: f64 = 0.06;
+    pub const DIGIT_SIZE: f64 = 0.7;
+    pub const CLUE_SIZE: f64 = 0.45;
+    pub const SMALL_SIZE: f64 = 0.24;
+
+    const CAGE_INSET: f64 = 0.08;
+
+    pub fn new(table: &SudokuTable, options: &DrawOptions) -> Drawing {
+        let mut drawing = Drawing {
+            under: vec![],
+            grid: vec![],
+            over: vec![],
+            has_outside_clues: false,
+        };
+
+        drawing.shade_extra_houses(table);
+        drawing.highlight_cells(options.highlighted);
+        drawing.draw_grid_lines(table);
+
+        for constraint in table.constraints() {
+            constraint.draw(&mut drawing);
+        }
+
+        drawing.draw_digits(table, options);
+
+        drawing
+    }
+
+    pub fn push(&mut self, shape: Shape) {
+        self.over.push(shape);
+    }
+
+    pub fn push_under(&mut self, shape: Shape) {
+        self.under.push(shape);
+    }
+
+    pub fn shapes(&self) -> impl Iterator<Item = &Shape> {
+        self.under
+            .iter()
+            .chain(self.grid.iter())
+            .chain(self.over.iter())
+    }
+
+    /// The room left around the grid, in cells. Outside clues need a whole cell.
+    pub fn margin(&self) -> f64 {
+        match self.has_outside_clues {
+            true => 1.0,
+            false => 0.25,
+        }
+    }
+
+    pub fn outside_clue(&mut self, side: Side, index: usize, label: String) {
+        let index = index as f64 + 0.5;
+        let center = match side {
+            Side::Top => Point::new(index, -0.5),
+            Side::Bottom => Point::new(index, 9.5),
+            Side::Left => Point::new(-0.5, index),
+            Side::Right => Point::new(9.5, index),
+        };
+
+        self.outside_text(center, label, Self::CLUE_SIZE);
+    }
+
+    pub fn corner_clue(&mut self, corner: Corner, label: String) {
+        let center = match corner {
+            Corner::TopLeft => Point::new(-0.5, -0.5),
+            Corner::TopRight => Point::new(9.5, -0.5),
+            Corner::BottomLeft => Point::new(-0.5, 9.5),
+            Corner::BottomRight => Point::new(9.5, 9.5),
+        };
+
+        self.outside_text(center, label, Self::CLUE_SIZE);
+    }
+
+    /// Draws text around the grid, and makes room for it.
+    pub fn outside_text(&mut self, center: Point, text: String, size: f64) {
+        self.has_outside_clues = true;
+        self.push(Shape::Text {
+            center,
+            text,
+            size,
+            color: Color::BLACK,
+            bold: false,
+        });
+    }
+
+    pub fn arrow(&mut self, points: Vec<Point>, width: f64, color: Color) {
+        let head = match points.as_slice() {
+            [.., before, last] => [-0.5f64, 0.5].map(|angle| {
+                let (dx, dy) = (before.x - last.x, before.y - last.y);
+                let length = (dx * dx + dy * dy).sqrt();
+                let (sin, cos) = angle.sin_cos();
+
+                Point::new(
+                    last.x + 0.2 * (dx * cos - dy * sin) / length,
+                    last.y + 0.2 * (dx * sin + dy * cos) / length,
+                )
+            }),
+            _ => return,
+        };
+        let last = *points.last().unwrap();
+
+        self.push_under(Shape::Polyline {
+            points,
+            width,
+            color,
+        });
+        self.push_under(Shape::Polyline {
+            points: vec![head[0], last, head[1]],
+            width,
+            color,
+        });
+    }
+
+    /// Draws a killer cage outline, inside the edges of its cells.
+    pub fn cage(&mut self, cells: &[CellLocation], label: Option<String>) {
+        let contains = |row: isize, col: isize| {
+            cells
+                .iter()
+                .any(|x| x.row as isize == row && x.col as isize == col)
+        };
+        let inset = Self::CAGE_INSET;
+
+        for cell in cells {
+            let (row, col) = (cell.row as isize, cell.col as isize);
+
+            // Each side is given as its outward normal, and the tangent along it.
+            for ((normal_row, normal_col), (tangent_row, tangent_col)) in [
+                ((-1, 0), (0, 1)),
+                ((1, 0), (0, 1)),
+                ((0, -1), (1, 0)),
+                ((0, 1), (1, 0)),
+            ] {
+                if contains(row + normal_row, col + normal_col) {
+                    continue;
+                }
+
+                let end = |sign: isize| {
+                    let (next_row, next_col) = (row + sign * tangent_row, col + sign * tangent_col);
+                    let extension = match (
+                        contains(next_row, next_col),
+                        contains(next_row + normal_row, next_col + normal_col),
+                    ) {
+                        (true, true) => inset,
+                        (true, false) => 0.0,
+                        (false, _) => -inset,
+                    };
+                    let along = sign as f64 * (0.5 + extension);
+                    let across = 0.5 - inset;
+
+                    Point::new(
+                        col as f64 + 0.5 + along * tangent_col as f64 + across * normal_col as f64,
+                        row as f64 + 0.5 + along * tangent_row as f64 + across * normal_row as f64,
+                    )
+                };
+
+                self.push(Shape::Line {
+                    from: end(-1),
+                    to: end(1),
+                    width: Self::THIN_LINE,
+                    color: Color::BLACK,
+                    dashed: true,
+                });
+            }
+        }
+
+        if let (Some(label), Some(first)) = (label, cells.iter().min_by_key(|x| (x.row, x.col))) {
+            self.push(Shape::Text {
+                center: Point::new(first.col as f64 + 0.2, first.row as f64 + 0.2),
+                text: label,
+                size: Self::SMALL_SIZE,
+                color: Color::BLACK,
+                bold: false,
+            });
+        }
+    }
+
+    fn shade_extra_houses(&mut self, table: &SudokuTable) {
+        let mut shaded = [[false; 9]; 9];
+
+        for cell in table.extra_houses().iter().flatten() {
+            shaded[cell.row][cell.col] = true;
+        }
+
+        // Houses covering the whole grid, such as disjoint groups, are left unshaded.
+        if shaded.iter().flatten().all(|x| *x) {
+            return;
+        }
+
+        for (row, cols) in shaded.iter().enumerate() {
+            for (col, _) in cols.iter().enumerate().filter(|(_, x)| **x) {
+                self.push_under(Shape::Rect {
+                    corner: Point::new(col as f64, row as f64),
+                    width: 1.0,
+                    height: 1.0,
+                    fill: Color::SHADE,
+                });
+            }
+        }
+    }
+
+    fn highlight_cells(&mut self, cells: &[CellLocation]) {
+        for cell in cells {
+            self.push_under(Shape::Rect {
+                corner: Point::new(cell.col as f64, cell.row as f64),
+                width: 1.0,
+                height: 1.0,
+                fill: Color::HIGHLIGHT,
+            });
+        }
+    }
+
+    fn draw_grid_lines(&mut self, table: &SudokuTable) {
+        for i in 1..9 {
+            let i = i as f64;
+            self.grid_line(Point::new(0.0, i), Point::new(9.0, i), Self::THIN_LINE);
+            self.grid_line(Point::new(i, 0.0), Point::new(i, 9.0), Self::THIN_LINE);
+        }
+
+        // Thick lines run along region borders, in runs of consecutive cell edges.
+        for line in 1..9 {
+            for horizontal in [true, false] {
+                let mut start = None;
+
+                for i in 0..=9 {
+                    let is_border = i < 9 && {
+                        let (first, second) = match horizontal {
+                            true => (CellLocation::new(line - 1, i), CellLocation::new(line, i)),
+                            false => (CellLocation::new(i, line - 1), CellLocation::new(i, line)),
+                        };
+                        table.region_of(first) != table.region_of(second)
+                    };
+
+                    match (is_border, start) {
+                        (true, None) => start = Some(i),
+                        (false, Some(first)) => {
+                            let (line, first, last) = (line as f64, first as f64, i as f64);
+                            let (from, to) = match horizontal {
+                                true => (Point::new(first, line), Point::new(last, line)),
+                                false => (Point::new(line, first), Point::new(line, last)),
+                            };
+
+                            self.grid_line(from, to, Self::THICK_LINE);
+                            start = None;
+                        }
+                        _ => (),
+                    }
+                }
+            }
+        }
+
+        let corners = [(0.0, 0.0), (9.0, 0.0), (9.0, 9.0), (0.0, 9.0), (0.0, 0.0)];
+        for pair in corners.windows(2) {
+            let (from, to) = (pair[0], pair[1]);
+            self.grid_line(
+                Point::new(from.0, from.1),
+                Point::new(to.0, to.1),
+                Self::THICK_LINE,
+            );
+        }
+    }
+
+    fn grid_line(&mut self, from: Point, to: Point, width: f64) {
+        self.grid.push(Shape::Line {
+            from,
+            to,
+            width,
+            color: Color::BLACK,
+            dashed: false,
+        });
+    }
+
+    fn draw_digits(&mut self, table: &SudokuTable, options: &DrawOptions) {
+        let mut solver =
+            (options.pencil_marks && options.progress.is_none()).then(|| SudokuSolver::new(table));
+
+        for row in 0..9 {
+            for col in 0..9 {
+                let cell = CellLocation::new(row, col);
+                let placed = options
+                    .progress
+                    .map(|x| x.table().contents()[row][col])
+                    .unwrap_or(SudokuCell::Empty);
+                let solved = options
+                    .solution
+                    .map(|x| x.contents()[row][col])
+                    .unwrap_or(SudokuCell::Empty);
+
+                match (table.contents()[row][col], placed, solved) {
+                    (SudokuCell::Filled(x), _, _) => self.digit(cell, x, Color::BLACK, true),
+                    (SudokuCell::Empty, SudokuCell::Filled(x), _)
+                    | (SudokuCell::Empty, SudokuCell::Empty, SudokuCell::Filled(x)) => {
+                        self.digit(cell, x, Color::BLUE, false)
+                    }
+                    (SudokuCell::Empty, SudokuCell::Empty, SudokuCell::Empty) => {
+                        let candidates = match (options.progress, solver.as_mut()) {
+                            (Some(progress), _) if options.pencil_marks => {
+                                progress.candidates(cell)
+                            }
+                            (None, Some(solver)) => solver.candidates(cell),
+                            _ => vec![],
+                        };
+
+                        for candidate in candidates {
+                            self.pencil_mark(cell, candidate);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn digit(&mut self, cell: CellLocation, digit: u8, color: Color, bold: bool) {
+        self.push(Shape::Text {
+            center: Point::cell_center(cell),
+            text: digit.to_string(),
+            size: Self::DIGIT_SIZE,
+            color,
+            bold,
+        });
+    }
+
+    fn pencil_mark(&mut self, cell: CellLocation, digit: u8) {
+        let position = (digit - 1) as f64;
+
+        self.push(Shape::Text {
+            center: Point::new(
+                cell.col as f64 + 0.2 + 0.3 * (position % 3.0),
+                cell.row as f64 + 0.2 + 0.3 * (position / 3.0).floor(),
+            ),
+            text: digit.to_string(),
+            size: Self::SMALL_SIZE,
+            color: Color::GRAY,
+            bold: false,
+        });
+    }
+}
+
+/// Writes a number for a vector format, with at most `decimals` decimals and without trailing
+/// zeros.
+pub fn number(x: f64, decimals: usize) -> String {
+    let result = format!("{:.*}", decimals, x);
+    let result = result.trim_end_matches('0').trim_end_matches('.');
+
+    match result {
+        "-0" => String::from("0"),
+        _ => result.to_string(),
+    }
+}
+
+/// Escapes text for SVG and HTML, in element content and quoted attributes.
+pub fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::pencil_marks::PencilMarks;
+    use super::super::{CellLocation, ParseMode, SudokuCell, SudokuTable};
+    use super::{escape_xml, number, Color, DrawOptions, Drawing, Point, Shape};
+
+    #[test]
+    fn numbers() {
+        assert_eq!(number(40.0, 2), "40");
+        assert_eq!(number(0.8, 2), "0.8");
+        assert_eq!(number(-0.001, 2), "0");
+        assert_eq!(number(1.0 / 3.0, 2), "0.33");
+        assert_eq!(number(1.0 / 3.0, 3), "0.333");
+        assert_eq!(escape_xml("<a & \"b\">"), "&lt;a &amp; &quot;b&quot;&gt;");
+    }
+
+    #[test]
+    fn cage_outline_and_region_borders() {
+        let input_table = "XXXXXXXXX\n\
+        XXXXXXXXX\n\
+        XXXXXXXXX\n\
+        XXXXXXXXX\n\
+        XXXXXXXXX\n\
+        XXXXXXXXX\n\
+        XXXXXXXXX\n\
+        XXXXXXXXX\n\
+        XXXXXXXXX\n";
+        let table =
+            SudokuTable::parse_unchecked(input_table.lines().map(String::from), ParseMode::Strict)
+                .unwrap();
+
+        let drawing = Drawing::new(&table, &DrawOptions::default());
+        let thick_lines = drawing
+            .shapes()
+            .filter(|x| matches!(x, Shape::Line { width, .. } if *width == Drawing::THICK_LINE))
+            .count();
+        // Two full lines each way inside the grid, and the four sides.
+        assert_eq!(thick_lines, 8);
+
+        let mut drawing = Drawing::new(&table, &DrawOptions::default());
+        let cells = [(0, 0), (0, 1), (1, 1)].map(|(row, col)| CellLocation::new(row, col));
+        drawing.cage(&cells, Some(String::from("10")));
+
+        let cage_lines: Vec<(Point, Point)> = drawing
+            .shapes()
+            .filter_map(|x| match x {
+                Shape::Line {
+                    from,
+                    to,
+                    dashed: true,
+                    ..
+                } => Some((*from, *to)),
+                _ => None,
+            })
+            .collect();
+
+        let has_line = |from: (f64, f64), to: (f64, f64)| {
+            let is_near = |point: Point, (x, y): (f64, f64)| {
+                (point.x - x).abs() < 1e-9 && (point.y - y).abs() < 1e-9
+            };
+            cage_lines
+                .iter()
+                .any(|(first, second)| is_near(*first, from) && is_near(*second, to))
+        };
+
+        assert_eq!(cage_lines.len(), 8);
+        // The top of r1c1 runs on into the top of r1c2.
+        assert!(has_line((0.08, 0.08), (1.0, 0.08)));
+        // The bottom of r1c1 meets the left side of r2c2 at the inner corner.
+        assert!(has_line((0.08, 0.92), (1.08, 0.92)));
+        assert!(has_line((1.08, 0.92), (1.08, 1.92)));
+    }
+
+    #[test]
+    fn player_progress() {
+        let table = SudokuTable::from_string(["X".repeat(81)].into_iter()).unwrap();
+        let mut player = table.clone();
+        player.contents_mut()[0][0] = SudokuCell::Filled(5);
+        let mut progress = PencilMarks::from_table(&player);
+        progress.set_candidates(CellLocation::new(0, 1), &[1, 2]);
+
+        let drawing = Drawing::new(
+            &table,
+            &DrawOptions {
+                pencil_marks: true,
+                progress: Some(&progress),
+                ..Default::default()
+            },
+        );
+        let texts_in = |row: usize, col: usize| {
+            drawing
+                .shapes()
+                .filter_map(|x| match x {
+                    Shape::Text {
+                        center,
+                        text,
+                        color,
+                        ..
+                    } if center.x.floor() == col as f64 && center.y.floor() == row as f64 => {
+                        Some((text.clone(), *color))
+                    }
+                    _ => None,
+                })
+                .collect::<Vec<(String, Color)>>()
+        };
+
+        assert_eq!(texts_in(0, 0), [(String::from("5"), Color::BLUE)]);
+
+        let marks: Vec<String> = texts_in(0, 1).into_iter().map(|x| x.0).collect();
+        assert_eq!(marks, ["1", "2"]);
+        assert_eq!(texts_in(0, 2).len(), 8);
+        assert_eq!(texts_in(8, 8).len(), 9);
+    }
+}
@@ -0,0 +1,532 @@
+use crate::error::SudokuError;
+use crate::json::JsonValue;
+
+use super::constraint;
+use super::{CellLocation, ParseMode, SudokuTable};
+
+pub struct FPuzzlesImport {
+    pub table: SudokuTable,
+    pub unsupported: Vec<String>,
+}
+
+pub fn import(json_str: &str) -> Result<FPuzzlesImport, SudokuError> {
+    let mut converter = Converter::default();
+    converter.convert(json_str).map_err(SudokuError::Import)?;
+
+    let unsupported = std::mem::take(&mut converter.unsupported);
+    let (document, keys): (Vec<String>, Vec<String>) =
+        converter.into_document().into_iter().unzip();
+    let table =
+        SudokuTable::parse_unchecked(document.into_iter(), ParseMode::Strict).map_err(|error| {
+            match error {
+                // The line numbers belong to the generated document, so name the f-puzzles key instead.
+                SudokuError::InvalidRule { line, message } => SudokuError::Import(format!(
+                    "Invalid f-puzzles input in '{}': {}",
+                    keys[line - 1],
+                    message.trim_start_matches("Invalid input: ")
+                )),
+                error => error,
+            }
+        })?;
+
+    Ok(FPuzzlesImport { table, unsupported })
+}
+
+#[derive(Default)]
+struct Converter {
+    grid: Vec<String>,
+    regions: Option<Vec<String>>,
+    parity: Option<Vec<Vec<char>>>,
+    sandwich_rows: [Option<u8>; 9],
+    sandwich_cols: [Option<u8>; 9],
+    lines: Vec<(String, String)>,
+    unsupported: Vec<String>,
+    key: String,
+}
+
+impl Converter {
+    const IGNORED_KEYS: [&'static str; 3] = ["ruleset", "solution", "highlightConflicts"];
+
+    fn convert(&mut self, json_str: &str) -> Result<(), String> {
+        let puzzle = JsonValue::parse(json_str)?;
+        let entries = puzzle
+            .as_object()
+            .ok_or_else(|| String::from("Invalid f-puzzles input: expected a JSON object"))?;
+
+        for (key, value) in entries {
+            self.convert_entry(key, value)?;
+        }
+
+        Ok(())
+    }
+
+    fn convert_entry(&mut self, key: &str, value: &JsonValue) -> Result<(), String> {
+        self.key = String::from(key);
+
+        match key {
+            "size" => match value.as_f64() {
+                Some(9.0) => (),
+                _ => {
+                    return Err(String::from(
+                        "Invalid f-puzzles input: only 9x9 grids are supported",
+                    ))
+                }
+            },
+            "title" | "author" => {
+                if let Some(x) = value.as_str() {
+                    self.push_line(format!("{}: {}", key, x.replace('\n', " ")));
+                }
+            }
+            "grid" => self.convert_grid(value)?,
+            "killercage" => self.convert_killer_cages(value)?,
+            "thermometer" => self.convert_lines(value, "thermo")?,
+            "whispers" => self.convert_lines(value, "whisper")?,
+            "renban" => self.convert_lines(value, "renban")?,
+            "palindrome" => self.convert_lines(value, "palindrome")?,
+            "regionsumline" => self.convert_lines(value, "region-sum")?,
+            "betweenline" => self.convert_lines(value, "between")?,
+            "arrow" => self.convert_arrows(value)?,
+            "difference" => self.convert_dots(value, "difference", "1", "kropki-white")?,
+            "ratio" => self.convert_dots(value, "ratio", "2", "kropki-black")?,
+            "odd" => self.convert_parity(value, 'O')?,
+            "even" => self.convert_parity(value, 'E')?,
+            "sandwichsum" => self.convert_sandwich_sums(value)?,
+            "littlekillersum" => self.convert_little_killers(value)?,
+            "extraregion" => self.convert_extra_regions(value)?,
+            "disjointgroups" => {
+                if value.as_bool() == Some(true) {
+                    self.push_line(String::from("disjoint-groups:"));
+                }
+            }
+            _ if Self::IGNORED_KEYS.contains(&key) => (),
+            _ => {
+                if !Self::is_empty(value) {
+                    self.unsupported.push(String::from(key));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn is_empty(value: &JsonValue) -> bool {
+        match value {
+            JsonValue::Null | JsonValue::Bool(false) => true,
+            JsonValue::Array(x) => x.is_empty(),
+            _ => false,
+        }
+    }
+
+    fn push_line(&mut self, line: String) {
+        self.lines.push((line, self.key.clone()));
+    }
+
+    /// Returns the document lines, each paired with the f-puzzles key it was converted from.
+    fn into_document(self) -> Vec<(String, String)> {
+        let mut result: Vec<(String, String)> = self
+            .grid
+            .into_iter()
+            .map(|x| (x, String::from("grid")))
+            .collect();
+
+        if let Some(regions) = self.regions {
+            result.push((
+                format!("regions: {}", regions.join(" ")),
+                String::from("grid"),
+            ));
+        }
+
+        if let Some(parity) = self.parity {
+            let rows: Vec<String> = parity
+                .into_iter()
+                .map(|x| x.into_iter().collect())
+                .collect();
+            result.push((
+                format!("parity: {}", rows.join(" ")),
+                String::from("odd/even"),
+            ));
+        }
+
+        if self.sandwich_rows.iter().any(|x| x.is_some()) {
+            result.push((
+                format!(
+                    "sandwich-rows: {}",
+                    constraint::outside_clues_to_string(&self.sandwich_rows)
+                ),
+                String::from("sandwichsum"),
+            ));
+        }
+
+        if self.sandwich_cols.iter().any(|x| x.is_some()) {
+            result.push((
+                format!(
+                    "sandwich-cols: {}",
+                    constraint::outside_clues_to_string(&self.sandwich_cols)
+                ),
+                String::from("sandwichsum"),
+            ));
+        }
+
+        result.extend(self.lines);
+        result
+    }
+
+    fn convert_grid(&mut self, value: &JsonValue) -> Result<(), String> {
+        let rows = value
+            .as_array()
+            .filter(|x| x.len() == 9)
+            .ok_or_else(|| String::from("Invalid f-puzzles input: grid should have 9 rows"))?;
+
+        let mut regions = vec![];
+        let mut has_regions = false;
+
+        for (i, row) in rows.iter().enumerate() {
+            let cells = row.as_array().filter(|x| x.len() == 9).ok_or_else(|| {
+                String::from("Invalid f-puzzles input: grid rows should have 9 cells")
+            })?;
+
+            let mut grid_row = String::new();
+            let mut region_row = String::new();
+
+            for (j, cell) in cells.iter().enumerate() {
+                let is_given = cell.get("given").and_then(|x| x.as_bool()) == Some(true);
+                grid_row.push(match cell.get("value").and_then(|x| x.as_f64()) {
+                    Some(x) if is_given && (1.0..=9.0).contains(&x) => {
+                        std::char::from_digit(x as u32, 10).unwrap()
+                    }
+                    _ => 'X',
+                });
+
+                let region = match cell.get("region").and_then(|x| x.as_f64()) {
+                    Some(x) if (0.0..9.0).contains(&x) => {
+                        has_regions = true;
+                        x as u32
+                    }
+                    _ => (3 * (i / 3) + j / 3) as u32,
+                };
+                region_row.push(std::char::from_digit(region + 1, 10).unwrap());
+            }
+
+            self.grid.push(grid_row);
+            regions.push(region_row);
+        }
+
+        if has_regions {
+            self.regions = Some(regions);
+        }
+
+        Ok(())
+    }
+
+    fn items<'a>(value: &'a JsonValue, key: &str) -> Result<&'a [JsonValue], String> {
+        value
+            .as_array()
+            .ok_or_else(|| format!("Invalid f-puzzles input: '{}' should be an array", key))
+    }
+
+    fn cells(value: Option<&JsonValue>, key: &str) -> Result<Vec<CellLocation>, String> {
+        let cells = value
+            .and_then(|x| x.as_array())
+            .ok_or_else(|| format!("Invalid f-puzzles input: missing cells in '{}'", key))?;
+
+        cells
+            .iter()
+            .map(|x| match x.as_str() {
+                Some(x) => x.parse::<CellLocation>(),
+                None => Err(format!(
+                    "Invalid f-puzzles input: illegal cell in '{}'",
+                    key
+                )),
+            })
+            .collect()
+    }
+
+    fn clue(value: Option<&JsonValue>) -> Option<String> {
+        match value {
+            Some(JsonValue::String(x)) if !x.trim().is_empty() => Some(x.trim().to_string()),
+            Some(JsonValue::Number(x)) => Some(x.to_string()),
+            _ => None,
+        }
+    }
+
+    fn outside_cell(value: Option<&JsonValue>, key: &str) -> Result<(isize, isize), String> {
+        let invalid_cell = || format!("Invalid f-puzzles input: illegal cell in '{}'", key);
+
+        let (row, col) = value
+            .and_then(|x| x.as_str())
+            .map(|x| x.to_ascii_lowercase())
+            .and_then(|x| {
+                x.strip_prefix('r')?
+                    .split_once('c')
+                    .map(|(row, col)| (row.parse::<isize>(), col.parse::<isize>()))
+            })
+            .ok_or_else(invalid_cell)?;
+
+        match (row, col) {
+            (Ok(row), Ok(col)) => Ok((row - 1, col - 1)),
+            _ => Err(invalid_cell()),
+        }
+    }
+
+    fn convert_killer_cages(&mut self, value: &JsonValue) -> Result<(), String> {
+        for cage in Self::items(value, "killercage")? {
+            let cells = Self::cells(cage.get("cells"), "killercage")?;
+            let sum = Self::clue(cage.get("value")).unwrap_or_else(|| String::from("X"));
+
+            self.push_line(format!(
+                "cage: {} {}",
+                sum,
+                constraint::cells_to_string(&cells)
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn convert_lines(&mut self, value: &JsonValue, name: &str) -> Result<(), String> {
+        for item in Self::items(value, name)? {
+            let lines = item
+                .get("lines")
+                .and_then(|x| x.as_array())
+                .ok_or_else(|| format!("Invalid f-puzzles input: missing lines in '{}'", name))?;
+
+            for line in lines {
+                let cells = Self::cells(Some(line), name)?;
+                self.push_line(format!("{}: {}", name, constraint::cells_to_string(&cells)));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn convert_arrows(&mut self, value: &JsonValue) -> Result<(), String> {
+        for arrow in Self::items(value, "arrow")? {
+            let circle = Self::cells(arrow.get("cells"), "arrow")?;
+            let lines = arrow
+                .get("lines")
+                .and_then(|x| x.as_array())
+                .ok_or_else(|| String::from("Invalid f-puzzles input: missing lines in 'arrow'"))?;
+
+            let mut shaft = vec![];
+            for line in lines {
+                for cell in Self::cells(Some(line), "arrow")? {
+                    if !circle.contains(&cell) && !shaft.contains(&cell) {
+                        shaft.push(cell);
+                    }
+                }
+            }
+
+            self.push_line(format!(
+                "arrow: {} -> {}",
+                constraint::cells_to_string(&circle),
+                constraint::cells_to_string(&shaft)
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn convert_dots(
+        &mut self,
+        value: &JsonValue,
+        name: &str,
+        supported_value: &str,
+        variant: &str,
+    ) -> Result<(), String> {
+        let mut dots = vec![];
+
+        for dot in Self::items(value, name)? {
+            match Self::clue(dot.get("value")) {
+                Some(x) if x != supported_value => {
+                    self.unsupported.push(format!("{} with value {}", name, x));
+                    continue;
+                }
+                _ => (),
+            }
+
+            match Self::cells(dot.get("cells"), name)?.as_slice() {
+                [first, second] => dots.push(format!("{}-{}", first, second)),
+                _ => {
+                    return Err(format!(
+                        "Invalid f-puzzles input: '{}' should join 2 cells",
+                        name
+                    ))
+                }
+            }
+        }
+
+        if !dots.is_empty() {
+            self.push_line(format!("{}: {}", variant, dots.join(" ")));
+        }
+
+        Ok(())
+    }
+
+    fn convert_parity(&mut self, value: &JsonValue, parity: char) -> Result<(), String> {
+        let mask = self.parity.get_or_insert_with(|| vec![vec!['X'; 9]; 9]);
+
+        for item in Self::items(value, "odd/even")? {
+            let cell = item
+                .get("cell")
+                .and_then(|x| x.as_str())
+                .ok_or_else(|| String::from("Invalid f-puzzles input: missing cell in 'odd/even'"))?
+                .parse::<CellLocation>()?;
+
+            mask[cell.row][cell.col] = parity;
+        }
+
+        Ok(())
+    }
+
+    fn convert_sandwich_sums(&mut self, value: &JsonValue) -> Result<(), String> {
+        for sum in Self::items(value, "sandwichsum")? {
+            let clue = match Self::clue(sum.get("value")).map(|x| x.parse::<u8>()) {
+                Some(Ok(x)) => x,
+                _ => {
+                    return Err(String::from(
+                        "Invalid f-puzzles input: illegal sandwich sum",
+                    ))
+                }
+            };
+
+            match Self::outside_cell(sum.get("cell"), "sandwichsum")? {
+                (-1, col @ 0..=8) => self.sandwich_cols[col as usize] = Some(clue),
+                (row @ 0..=8, -1) => self.sandwich_rows[row as usize] = Some(clue),
+                _ => {
+                    return Err(String::from(
+                        "Invalid f-puzzles input: illegal sandwich sum cell",
+                    ))
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn convert_little_killers(&mut self, value: &JsonValue) -> Result<(), String> {
+        for clue in Self::items(value, "littlekillersum")? {
+            let sum = match Self::clue(clue.get("value")) {
+                Some(x) => x,
+                None => continue,
+            };
+
+            let (row, col) = Self::outside_cell(clue.get("cell"), "littlekillersum")?;
+            let (row_step, col_step, direction) =
+                match clue.get("direction").and_then(|x| x.as_str()) {
+                    Some("UR") => (-1, 1, "NE"),
+                    Some("UL") => (-1, -1, "NW"),
+                    Some("DR") => (1, 1, "SE"),
+                    Some("DL") => (1, -1, "SW"),
+                    _ => {
+                        return Err(String::from(
+                            "Invalid f-puzzles input: illegal little killer direction",
+                        ))
+                    }
+                };
+
+            let start = format!("r{}c{}", row + row_step + 1, col + col_step + 1)
+                .parse::<CellLocation>()
+                .map_err(|_| {
+                    String::from("Invalid f-puzzles input: little killer points outside the grid")
+                })?;
+
+            self.push_line(format!("little-killer: {} {} {}", start, direction, sum));
+        }
+
+        Ok(())
+    }
+
+    fn convert_extra_regions(&mut self, value: &JsonValue) -> Result<(), String> {
+        for region in Self::items(value, "extraregion")? {
+            let cells = Self::cells(region.get("cells"), "extraregion")?;
+
+            if cells.len() == 9 {
+                self.push_line(format!("house: {}", constraint::cells_to_string(&cells)));
+            } else {
+                self.unsupported
+                    .push(format!("extraregion with {} cells", cells.len()));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::import;
+    use crate::error::SudokuError;
+
+    fn grid(rows: &[&str]) -> String {
+        let row = |values: &str| {
+            let cells: Vec<String> = values
+                .chars()
+                .map(|x| match x {
+                    'X' => String::from("{}"),
+                    _ => format!("{{\"value\": {}, \"given\": true}}", x),
+                })
+                .collect();
+
+            format!("[{}]", cells.join(", "))
+        };
+        let rows: Vec<String> = rows.iter().map(|x| row(x)).collect();
+
+        rows.join(", ")
+    }
+
+    #[test]
+    fn import_givens_and_constraints() {
+        let grid = grid(&[
+            "XX1XXXXX2",
+            "XXXX34XXX",
+            "X5XXX1XX6",
+            "X2X6XXXX3",
+            "X3XXXXX5X",
+            "7XXXX8X9X",
+            "9XX4XXX3X",
+            "XXX71XXXX",
+            "8XXXXX4XX",
+        ]);
+
+        let puzzle = format!(
+            r#"{{
+                "size": 9,
+                "title": "Test",
+                "grid": [{}],
+                "killercage": [{{"cells": ["R9C2", "R9C3"], "value": "4"}}],
+                "thermometer": [{{"lines": [["R7C2", "R8C2", "R8C3"]]}}],
+                "ratio": [{{"cells": ["R9C4", "R9C5"]}}, {{"cells": ["R1C1", "R1C2"], "value": "3"}}],
+                "sandwichsum": [{{"cell": "R0C1", "value": "13"}}],
+                "antiknight": true,
+                "diagonal+": false
+            }}"#,
+            grid
+        );
+
+        let result = import(&puzzle).unwrap();
+
+        assert_eq!(result.table.constraints().len(), 4);
+        assert_eq!(result.table.metadata().title.as_deref(), Some("Test"));
+        assert_eq!(result.unsupported, vec!["ratio with value 3", "antiknight"]);
+    }
+
+    #[test]
+    fn invalid_constraint_names_key() {
+        let puzzle = format!(
+            r#"{{
+                "size": 9,
+                "grid": [{}],
+                "thermometer": [{{"lines": [["R1C1", "R1C2"]]}}],
+                "killercage": [{{"cells": ["R9C2", "R9C3"], "value": "0"}}]
+            }}"#,
+            grid(&["XXXXXXXXX"; 9])
+        );
+
+        match import(&puzzle) {
+            Err(SudokuError::Import(message)) => {
+                assert!(message.starts_with("Invalid f-puzzles input in 'killercage': "))
+            }
+            _ => panic!("expected an import error"),
+        }
+    }
+}
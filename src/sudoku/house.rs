@@ -0,0 +1,114 @@
+use super::CellLocation;
+
+pub type House = [CellLocation; 9];
+
+/// A predefined set of extra houses declared by a single puzzle file line.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HousePreset {
+    Windoku,
+    DisjointGroups,
+}
+
+impl HousePreset {
+    pub fn from_key(key: &str) -> Option<HousePreset> {
+        match key {
+            "windoku" => Some(HousePreset::Windoku),
+            "disjoint-groups" => Some(HousePreset::DisjointGroups),
+            _ => None,
+        }
+    }
+
+    pub fn key(&self) -> &'static str {
+        match self {
+            HousePreset::Windoku => "windoku",
+            HousePreset::DisjointGroups => "disjoint-groups",
+        }
+    }
+
+    pub fn houses(&self) -> Vec<House> {
+        match self {
+            HousePreset::Windoku => windoku_houses(),
+            HousePreset::DisjointGroups => disjoint_group_houses(),
+        }
+    }
+}
+
+pub fn windoku_houses() -> Vec<House> {
+    [(1usize, 1usize), (1, 5), (5, 1), (5, 5)]
+        .iter()
+        .map(|(row, col)| {
+            let mut house = [CellLocation { row: 0, col: 0 }; 9];
+            for (i, cell) in house.iter_mut().enumerate() {
+                *cell = CellLocation {
+                    row: row + i / 3,
+                    col: col + i % 3,
+                };
+            }
+
+            house
+        })
+        .collect()
+}
+
+pub fn disjoint_group_houses() -> Vec<House> {
+    (0usize..9)
+        .map(|position| {
+            let mut house = [CellLocation { row: 0, col: 0 }; 9];
+            for (i, cell) in house.iter_mut().enumerate() {
+                *cell = CellLocation {
+                    row: 3 * (i / 3) + position / 3,
+                    col: 3 * (i % 3) + position % 3,
+                };
+            }
+
+            house
+        })
+        .collect()
+}
+
+pub fn house_from_str(cells_str: &str) -> Result<House, String> {
+    let cells = cells_str
+        .split_whitespace()
+        .map(|x| x.parse::<CellLocation>())
+        .collect::<Result<Vec<CellLocation>, String>>()?;
+
+    if cells.len() != 9 {
+        return Err(format!(
+            "Invalid input: a house should have exactly 9 cells, found {}",
+            cells.len()
+        ));
+    }
+
+    for (i, cell) in cells.iter().enumerate() {
+        if cells[..i].contains(cell) {
+            return Err(format!("Invalid input: cell {} repeated in house", cell));
+        }
+    }
+
+    let mut house = [CellLocation { row: 0, col: 0 }; 9];
+    house.copy_from_slice(&cells);
+
+    Ok(house)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{disjoint_group_houses, house_from_str, windoku_houses};
+
+    #[test]
+    fn predefined_houses_cover_distinct_cells() {
+        for houses in [windoku_houses(), disjoint_group_houses()] {
+            for house in houses {
+                for (i, cell) in house.iter().enumerate() {
+                    assert!(!house[..i].contains(cell));
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "repeated in house")]
+    fn repeated_cell_in_house() {
+        house_from_str("r1c1 r1c2 r1c3 r2c1 r2c2 r2c3 r3c1 r3c2 r1c1").unwrap();
+    }
+}
@@ -0,0 +1,169 @@
+use super::constraint::Constraint;
+use super::drawing::{Color, Drawing, Point, Shape};
+use super::render::Canvas;
+use super::{CellLocation, SudokuCell, SudokuTable};
+
+pub struct Inequalities {
+    pairs: Vec<(CellLocation, CellLocation)>,
+}
+
+impl Inequalities {
+    pub fn from_str(inequalities_str: &str) -> Result<Inequalities, String> {
+        let mut pairs = vec![];
+
+        for inequality in inequalities_str.split_whitespace() {
+            let pair = if let Some((greater, smaller)) = inequality.split_once('>') {
+                (greater.parse()?, smaller.parse()?)
+            } else if let Some((smaller, greater)) = inequality.split_once('<') {
+                (greater.parse()?, smaller.parse()?)
+            } else {
+                return Err(format!(
+                    "Invalid input: illegal inequality '{}'",
+                    inequality
+                ));
+            };
+
+            if !Self::are_adjacent(pair) {
+                return Err(format!(
+                    "Invalid input: cells in '{}' are not adjacent",
+                    inequality
+                ));
+            }
+
+            pairs.push(pair);
+        }
+
+        Ok(Inequalities { pairs })
+    }
+
+    fn are_adjacent((first, second): (CellLocation, CellLocation)) -> bool {
+        first.row.abs_diff(second.row) + first.col.abs_diff(second.col) == 1
+    }
+}
+
+impl Constraint for Inequalities {
+    fn cells(&self) -> Vec<CellLocation> {
+        self.pairs
+            .iter()
+            .flat_map(|(greater, smaller)| [*greater, *smaller])
+            .collect()
+    }
+
+    fn is_satisfiable(&self, table: &SudokuTable) -> bool {
+        self.pairs.iter().all(|(greater, smaller)| {
+            match (
+                table.contents()[greater.row][greater.col],
+                table.contents()[smaller.row][smaller.col],
+            ) {
+                (SudokuCell::Filled(x), SudokuCell::Filled(y)) => x > y,
+                (SudokuCell::Filled(x), SudokuCell::Empty) => x > 1,
+                (SudokuCell::Empty, SudokuCell::Filled(y)) => y < 9,
+                (SudokuCell::Empty, SudokuCell::Empty) => true,
+            }
+        })
+    }
+
+    fn prune(&self, table: &SudokuTable, cell: CellLocation, candidates: &mut Vec<u8>) {
+        for (greater, smaller) in &self.pairs {
+            if *greater == cell {
+                match table.contents()[smaller.row][smaller.col] {
+                    SudokuCell::Filled(y) => candidates.retain(|x| *x > y),
+                    SudokuCell::Empty => candidates.retain(|x| *x > 1),
+                }
+            } else if *smaller == cell {
+                match table.contents()[greater.row][greater.col] {
+                    SudokuCell::Filled(y) => candidates.retain(|x| *x < y),
+                    SudokuCell::Empty => candidates.retain(|x| *x < 9),
+                }
+            }
+        }
+    }
+
+    fn render(&self, canvas: &mut Canvas) {
+        for (greater, smaller) in &self.pairs {
+            let sign = match (greater.row == smaller.row, greater.col < smaller.col) {
+                (true, true) => '>',
+                (true, false) => '<',
+                (false, _) if greater.row < smaller.row => '∨',
+                (false, _) => '∧',
+            };
+
+            canvas.set_char(Canvas::position_between(*greater, *smaller), sign);
+        }
+    }
+
+    fn draw(&self, drawing: &mut Drawing) {
+        for (greater, smaller) in &self.pairs {
+            // A chevron pointing at the smaller cell.
+            let center = Point::between(*greater, *smaller);
+            let tip = center.towards(Point::cell_center(*smaller), 0.07);
+            let back = center.towards(Point::cell_center(*greater), 0.07);
+            let (dx, dy) = (tip.x - back.x, tip.y - back.y);
+
+            drawing.push(Shape::Polyline {
+                points: vec![
+                    Point::new(back.x - dy, back.y + dx),
+                    tip,
+                    Point::new(back.x + dy, back.y - dx),
+                ],
+                width: Drawing::THIN_LINE * 2.0,
+                color: Color::BLACK,
+            });
+        }
+    }
+
+    fn to_line(&self) -> Option<String> {
+        let pairs: Vec<String> = self
+            .pairs
+            .iter()
+            .map(|(greater, smaller)| format!("{}>{}", greater, smaller))
+            .collect();
+
+        Some(format!("greater-than: {}", pairs.join(" ")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::solver::tests::{digit, solutions};
+    use super::Inequalities;
+
+    #[test]
+    fn inequalities_during_search() {
+        let puzzle = "XX1XXXXX2\n\
+        XXXX34XXX\n\
+        X5XXX1XX6\n\
+        X2X6XXXX3\n\
+        X3XXXXX5X\n\
+        7XXXX8X9X\n\
+        9XX4XXX3X\n\
+        XXXXXXXXX\n\
+        XXXXXXXXX\n";
+        let found = solutions(
+            &format!("{}greater-than: r8c1>r8c2 r9c9<r8c9 r8c5>r9c5\n", puzzle),
+            20,
+        );
+
+        assert_eq!(found.len(), 20);
+
+        for solution in &found {
+            assert!(digit(solution, 7, 0) > digit(solution, 7, 1));
+            assert!(digit(solution, 8, 8) < digit(solution, 7, 8));
+            assert!(digit(solution, 7, 4) > digit(solution, 8, 4));
+        }
+
+        // The only solution has 3 in r1c1 and 9 in r1c2.
+        let unique = puzzle.replace("XXXXXXXXX\nXXXXXXXXX\n", "XXX71XXXX\n8XXXXX4XX\n");
+        assert_eq!(
+            solutions(&format!("{}greater-than: r1c1<r1c2\n", unique), 2).len(),
+            1
+        );
+        assert!(solutions(&format!("{}greater-than: r1c1>r1c2\n", unique), 1).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "not adjacent")]
+    fn non_adjacent_cells() {
+        Inequalities::from_str("r1c1>r1c2 r2c2<r3c3").unwrap();
+    }
+}
@@ -0,0 +1,167 @@
+use super::constraint::Constraint;
+use super::drawing::{Color, Drawing, Point, Shape};
+use super::render::Canvas;
+use super::{CellLocation, SudokuCell, SudokuTable};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DotColor {
+    White,
+    Black,
+}
+
+pub struct KropkiDots {
+    color: DotColor,
+    pairs: Vec<(CellLocation, CellLocation)>,
+}
+
+impl KropkiDots {
+    pub fn new(
+        color: DotColor,
+        pairs: Vec<(CellLocation, CellLocation)>,
+    ) -> Result<KropkiDots, String> {
+        for (first, second) in &pairs {
+            if first.row.abs_diff(second.row) + first.col.abs_diff(second.col) != 1 {
+                return Err(format!(
+                    "Invalid input: cells {} and {} of dot are not adjacent",
+                    first, second
+                ));
+            }
+        }
+
+        Ok(KropkiDots { color, pairs })
+    }
+
+    pub fn from_str(color: DotColor, dots_str: &str) -> Result<KropkiDots, String> {
+        let mut pairs = vec![];
+
+        for dot in dots_str.split_whitespace() {
+            match dot.split_once('-') {
+                Some((first, second)) => pairs.push((first.parse()?, second.parse()?)),
+                None => return Err(format!("Invalid input: illegal dot '{}'", dot)),
+            }
+        }
+
+        Self::new(color, pairs)
+    }
+
+    fn are_compatible(&self, x: u8, y: u8) -> bool {
+        match self.color {
+            DotColor::White => x.abs_diff(y) == 1,
+            DotColor::Black => x == 2 * y || y == 2 * x,
+        }
+    }
+}
+
+impl Constraint for KropkiDots {
+    fn cells(&self) -> Vec<CellLocation> {
+        self.pairs
+            .iter()
+            .flat_map(|(first, second)| [*first, *second])
+            .collect()
+    }
+
+    fn is_satisfiable(&self, table: &SudokuTable) -> bool {
+        self.pairs.iter().all(|(first, second)| {
+            match (
+                table.contents()[first.row][first.col],
+                table.contents()[second.row][second.col],
+            ) {
+                (SudokuCell::Filled(x), SudokuCell::Filled(y)) => self.are_compatible(x, y),
+                (SudokuCell::Filled(x), SudokuCell::Empty)
+                | (SudokuCell::Empty, SudokuCell::Filled(x)) => {
+                    (1u8..=9).any(|y| self.are_compatible(x, y))
+                }
+                (SudokuCell::Empty, SudokuCell::Empty) => true,
+            }
+        })
+    }
+
+    fn render(&self, canvas: &mut Canvas) {
+        let dot = match self.color {
+            DotColor::White => '○',
+            DotColor::Black => '●',
+        };
+
+        for (first, second) in &self.pairs {
+            canvas.set_char(Canvas::position_between(*first, *second), dot);
+        }
+    }
+
+    fn draw(&self, drawing: &mut Drawing) {
+        let fill = match self.color {
+            DotColor::White => Color::WHITE,
+            DotColor::Black => Color::BLACK,
+        };
+
+        for (first, second) in &self.pairs {
+            drawing.push(Shape::Circle {
+                center: Point::between(*first, *second),
+                radius: 0.12,
+                fill: Some(fill),
+                stroke: Some((Drawing::THIN_LINE, Color::BLACK)),
+            });
+        }
+    }
+
+    fn to_line(&self) -> Option<String> {
+        let key = match self.color {
+            DotColor::White => "kropki-white",
+            DotColor::Black => "kropki-black",
+        };
+        let dots: Vec<String> = self
+            .pairs
+            .iter()
+            .map(|(first, second)| format!("{}-{}", first, second))
+            .collect();
+
+        Some(format!("{}: {}", key, dots.join(" ")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::constraint::Constraint;
+    use super::super::solver::tests::{digit, solutions};
+    use super::{DotColor, KropkiDots};
+
+    #[test]
+    fn dots_during_search() {
+        let puzzle = "XX1XXXXX2\n\
+        XXXX34XXX\n\
+        X5XXX1XX6\n\
+        X2X6XXXX3\n\
+        X3XXXXX5X\n\
+        7XXXX8X9X\n\
+        9XX4XXX3X\n\
+        XXXXXXXXX\n\
+        XXXXXXXXX\n";
+        let rules = "kropki-white: r8c1-r8c2 r9c5-r9c6\nkropki-black: r8c3-r9c3\n";
+        let found = solutions(&format!("{}{}", puzzle, rules), 20);
+
+        assert_eq!(found.len(), 20);
+
+        for solution in &found {
+            assert_eq!(digit(solution, 7, 0).abs_diff(digit(solution, 7, 1)), 1);
+            assert_eq!(digit(solution, 8, 4).abs_diff(digit(solution, 8, 5)), 1);
+
+            let (x, y) = (digit(solution, 7, 2), digit(solution, 8, 2));
+            assert!(x == 2 * y || y == 2 * x);
+        }
+
+        // The only solution has 3 and 9 in r1c1 and r1c2, which no dot allows.
+        let unique = puzzle.replace("XXXXXXXXX\nXXXXXXXXX\n", "XXX71XXXX\n8XXXXX4XX\n");
+        assert_eq!(solutions(&unique, 2).len(), 1);
+        assert!(solutions(&format!("{}kropki-white: r1c1-r1c2\n", unique), 1).is_empty());
+        assert!(solutions(&format!("{}kropki-black: r1c1-r1c2\n", unique), 1).is_empty());
+    }
+
+    #[test]
+    fn line_round_trip() {
+        let line = "kropki-black: r1c1-r1c2 r5c5-r6c5";
+        let dots = KropkiDots::from_str(DotColor::Black, "r1c1-r1c2 r5c5-r6c5").unwrap();
+
+        assert_eq!(dots.to_line().as_deref(), Some(line));
+        assert!(KropkiDots::from_str(DotColor::White, "r1c1r1c2").is_err());
+        assert!(KropkiDots::from_str(DotColor::White, "r1c1-r2c2").is_err());
+    }
+}
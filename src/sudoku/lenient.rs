@@ -0,0 +1,137 @@
+use super::render::Canvas;
+use super::CellLocation;
+
+const BOX_DRAWING_CHARS: [char; 11] = ['─', '│', '┌', '┐', '└', '┘', '├', '┤', '┬', '┴', '┼'];
+const SEPARATOR_CHARS: [char; 5] = [' ', '\t', '|', '-', '+'];
+
+pub fn normalize_lines<T: Iterator<Item = String>>(table_str: T) -> Vec<(String, usize)> {
+    let lines: Vec<String> = table_str
+        .map(|line| {
+            line.trim_start_matches('\u{feff}')
+                .trim_end_matches('\r')
+                .to_string()
+        })
+        .collect();
+
+    let is_rendered_table = lines
+        .iter()
+        .any(|line| line.contains(BOX_DRAWING_CHARS) && !line.contains(':'));
+
+    lines
+        .into_iter()
+        .zip(1..)
+        .filter_map(|(line, line_number)| {
+            let line = if line.contains(':') {
+                Some(line.trim().to_string())
+            } else if is_rendered_table {
+                row_from_rendered_line(&line)
+            } else {
+                Some(strip_separators(&line)).filter(|x| !x.is_empty())
+            };
+
+            line.map(|x| (x, line_number))
+        })
+        .collect()
+}
+
+pub fn strip_separators(line: &str) -> String {
+    line.chars()
+        .filter(|x| !SEPARATOR_CHARS.contains(x))
+        .collect()
+}
+
+fn row_from_rendered_line(line: &str) -> Option<String> {
+    let chars: Vec<char> = line.chars().collect();
+    let grid_start = chars.iter().position(|x| *x == '│')?;
+
+    let row = (0..9)
+        .map(|col| {
+            let (_, position) = Canvas::cell_position(CellLocation::new(0, col));
+
+            match chars.get(grid_start + position) {
+                Some(' ') | None => 'X',
+                Some(x) => *x,
+            }
+        })
+        .collect();
+
+    Some(row)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{ParseMode, SudokuTable};
+    use crate::error::SudokuError;
+
+    #[test]
+    fn separators_and_line_endings() {
+        let input_table = "\u{feff}3 . 6 | 5 . 8 | 4 . .\r\n\
+        5 2 . | . . . | . . .\r\n\
+        . 8 7 | . . . | . 3 1\r\n\
+        ------+-------+------\r\n\
+        . . 3 | . 1 . | . 8 .\r\n\
+        9 . . | 8 6 3 | . . 5\r\n\
+        \r\n\
+        . 5 . | . 9 . | 6 . .\r\n\
+        ------+-------+------\r\n\
+        1 3 . | . . . | 2 5 .\r\n\
+        . . . | . . . | . 7 4\r\n\
+        . . 5 | 2 . 6 | 3 . .\r\n";
+
+        let table = SudokuTable::from_string_with_mode(
+            input_table.split('\n').map(String::from),
+            ParseMode::Lenient,
+        )
+        .unwrap();
+
+        assert_eq!(
+            table.to_single_line(),
+            "3.65.84..52........87....31..3.1..8.9..863..5.5..9.6..13....25........74..52.63.."
+        );
+    }
+
+    #[test]
+    fn rendered_table_round_trip() {
+        let document = "391867542\n\
+        286534719\n\
+        457291386\n\
+        129645873\n\
+        638179254\n\
+        745328691\n\
+        972486135\n\
+        XXXXXXXXX\n\
+        XXXXXXXXX\n\
+        sandwich-rows: 0 X X 2 7 X 27 3 3\n\
+        parity: OXXXXXXXX XXXXXXXXX XXXXXXXXX XXXXXXXXX XXXXXXXXX XXXXXXXXX XXXXXXXXX XXXXXXXXX XXXXXXXXX\n\
+        kropki-white: r1c5-r1c6\n";
+
+        let table = SudokuTable::from_string(document.lines().map(String::from)).unwrap();
+        let rendered = table.to_string();
+
+        let read_back = SudokuTable::from_string_with_mode(
+            rendered.lines().map(String::from),
+            ParseMode::Lenient,
+        )
+        .unwrap();
+
+        assert_eq!(read_back.to_single_line(), table.to_single_line());
+    }
+
+    #[test]
+    fn strict_mode_rejects_separators() {
+        let input_table = "3 . 6 | 5 . 8 | 4 . .\n".repeat(9);
+
+        let result = SudokuTable::from_string_with_mode(
+            input_table.lines().map(String::from),
+            ParseMode::Strict,
+        );
+
+        assert!(matches!(
+            result,
+            Err(SudokuError::WrongLineLength {
+                line: 1,
+                length: 21
+            })
+        ));
+    }
+}
@@ -0,0 +1,406 @@
+use super::constraint::{self, Constraint};
+use super::drawing::{Color, Drawing, Point, Shape};
+use super::{CellLocation, SudokuCell, SudokuTable};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LineKind {
+    GermanWhispers,
+    Renban,
+    Palindrome,
+    RegionSum,
+    Between,
+    Thermometer,
+}
+
+pub struct LineConstraint {
+    kind: LineKind,
+    cells: Vec<CellLocation>,
+}
+
+impl LineConstraint {
+    pub fn from_str(kind: LineKind, cells_str: &str) -> Result<LineConstraint, String> {
+        let cells = cells_str
+            .split_whitespace()
+            .map(|x| x.parse::<CellLocation>())
+            .collect::<Result<Vec<CellLocation>, String>>()?;
+
+        let minimum_length = match kind {
+            LineKind::Between => 3,
+            _ => 2,
+        };
+
+        if cells.len() < minimum_length {
+            return Err(format!(
+                "Invalid input: line should have at least {} cells, found {}",
+                minimum_length,
+                cells.len()
+            ));
+        }
+
+        for (i, cell) in cells.iter().enumerate() {
+            if cells[..i].contains(cell) {
+                return Err(format!("Invalid input: cell {} repeated in line", cell));
+            }
+        }
+
+        for pair in cells.windows(2) {
+            if pair[0].row.abs_diff(pair[1].row) > 1 || pair[0].col.abs_diff(pair[1].col) > 1 {
+                return Err(format!(
+                    "Invalid input: cells {} and {} of line are not adjacent",
+                    pair[0], pair[1]
+                ));
+            }
+        }
+
+        Ok(LineConstraint { kind, cells })
+    }
+
+    fn values(&self, table: &SudokuTable) -> Vec<Option<u8>> {
+        self.cells
+            .iter()
+            .map(|cell| match table.contents()[cell.row][cell.col] {
+                SudokuCell::Filled(x) => Some(x),
+                SudokuCell::Empty => None,
+            })
+            .collect()
+    }
+
+    fn is_german_whispers_satisfiable(values: &[Option<u8>]) -> bool {
+        let pairs_differ = values.windows(2).all(|pair| match (pair[0], pair[1]) {
+            (Some(x), Some(y)) => x.abs_diff(y) >= 5,
+            _ => true,
+        });
+
+        pairs_differ && !values.contains(&Some(5))
+    }
+
+    fn is_thermometer_satisfiable(values: &[Option<u8>]) -> bool {
+        let filled: Vec<(usize, u8)> = values
+            .iter()
+            .enumerate()
+            .filter_map(|(i, x)| x.map(|x| (i, x)))
+            .collect();
+
+        let fits_in_line = filled
+            .iter()
+            .all(|(i, x)| *x as usize > *i && *x as usize + values.len() - i <= 10);
+        let increases = filled.windows(2).all(|pair| {
+            let ((i, x), (j, y)) = (pair[0], pair[1]);
+            y > x && (y - x) as usize >= j - i
+        });
+
+        fits_in_line && increases
+    }
+
+    fn is_renban_satisfiable(values: &[Option<u8>]) -> bool {
+        let mut digits: Vec<u8> = values.iter().flatten().copied().collect();
+        digits.sort();
+
+        let are_distinct = digits.windows(2).all(|pair| pair[0] != pair[1]);
+
+        match (digits.first(), digits.last()) {
+            (Some(min), Some(max)) => are_distinct && ((max - min) as usize) < values.len(),
+            _ => true,
+        }
+    }
+
+    fn is_palindrome_satisfiable(values: &[Option<u8>]) -> bool {
+        values
+            .iter()
+            .zip(values.iter().rev())
+            .all(|pair| match pair {
+                (Some(x), Some(y)) => x == y,
+                _ => true,
+            })
+    }
+
+    fn is_region_sum_satisfiable(&self, table: &SudokuTable, values: &[Option<u8>]) -> bool {
+        let mut lowest_max = u32::MAX;
+        let mut highest_min = 0u32;
+        let mut start = 0usize;
+
+        while start < values.len() {
+            let region = table.region_of(self.cells[start]);
+            let end = (start..values.len())
+                .find(|i| table.region_of(self.cells[*i]) != region)
+                .unwrap_or(values.len());
+
+            let segment = &values[start..end];
+            let filled_sum: u32 = segment.iter().flatten().map(|x| *x as u32).sum();
+            let empty_count = segment.iter().filter(|x| x.is_none()).count() as u32;
+
+            highest_min = highest_min.max(filled_sum + (1..=empty_count).sum::<u32>());
+            lowest_max = lowest_max.min(filled_sum + (10 - empty_count..=9).sum::<u32>());
+
+            start = end;
+        }
+
+        highest_min <= lowest_max
+    }
+
+    fn is_between_satisfiable(values: &[Option<u8>]) -> bool {
+        let (first, middle, last) = (
+            values[0],
+            &values[1..values.len() - 1],
+            values[values.len() - 1],
+        );
+        let middle: Vec<u8> = middle.iter().flatten().copied().collect();
+
+        let (min, max) = match (middle.iter().min(), middle.iter().max()) {
+            (Some(min), Some(max)) => (*min, *max),
+            _ => {
+                return match (first, last) {
+                    (Some(x), Some(y)) => x.abs_diff(y) >= 2,
+                    _ => true,
+                }
+            }
+        };
+
+        match (first, last) {
+            (Some(x), Some(y)) => x.min(y) < min && max < x.max(y),
+            (Some(end), None) | (None, Some(end)) => {
+                (end < min && max < 9) || (max < end && min > 1)
+            }
+            (None, None) => min > 1 && max < 9,
+        }
+    }
+}
+
+impl Constraint for LineConstraint {
+    fn cells(&self) -> Vec<CellLocation> {
+        self.cells.clone()
+    }
+
+    fn is_satisfiable(&self, table: &SudokuTable) -> bool {
+        let values = self.values(table);
+
+        match self.kind {
+            LineKind::GermanWhispers => Self::is_german_whispers_satisfiable(&values),
+            LineKind::Renban => Self::is_renban_satisfiable(&values),
+            LineKind::Palindrome => Self::is_palindrome_satisfiable(&values),
+            LineKind::RegionSum => self.is_region_sum_satisfiable(table, &values),
+            LineKind::Between => Self::is_between_satisfiable(&values),
+            LineKind::Thermometer => Self::is_thermometer_satisfiable(&values),
+        }
+    }
+
+    fn draw(&self, drawing: &mut Drawing) {
+        let points: Vec<Point> = self.cells.iter().map(|x| Point::cell_center(*x)).collect();
+        let (width, color) = match self.kind {
+            LineKind::GermanWhispers => (0.2, Color(100, 200, 100)),
+            LineKind::Renban => (0.2, Color(220, 150, 235)),
+            LineKind::Palindrome => (0.15, Color::LIGHT_GRAY),
+            LineKind::RegionSum => (0.12, Color(110, 160, 255)),
+            LineKind::Between => (0.1, Color::LIGHT_GRAY),
+            LineKind::Thermometer => (0.3, Color::LIGHT_GRAY),
+        };
+
+        let ends = [points[0], points[points.len() - 1]];
+        drawing.push_under(Shape::Polyline {
+            points,
+            width,
+            color,
+        });
+
+        match self.kind {
+            LineKind::Thermometer => drawing.push_under(Shape::Circle {
+                center: ends[0],
+                radius: 0.38,
+                fill: Some(color),
+                stroke: None,
+            }),
+            LineKind::Between => {
+                for center in ends {
+                    drawing.push_under(Shape::Circle {
+                        center,
+                        radius: 0.4,
+                        fill: Some(Color::WHITE),
+                        stroke: Some((Drawing::THIN_LINE * 2.0, Color::GRAY)),
+                    });
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn to_line(&self) -> Option<String> {
+        let key = match self.kind {
+            LineKind::GermanWhispers => "whisper",
+            LineKind::Renban => "renban",
+            LineKind::Palindrome => "palindrome",
+            LineKind::RegionSum => "region-sum",
+            LineKind::Between => "between",
+            LineKind::Thermometer => "thermo",
+        };
+
+        Some(format!(
+            "{}: {}",
+            key,
+            constraint::cells_to_string(&self.cells)
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::solver::tests::{digit, solutions};
+    use super::super::{CellLocation, SudokuTable};
+    use super::{LineConstraint, LineKind};
+    use crate::error::SudokuError;
+
+    const PUZZLE: &str = "XX1XXXXX2\n\
+        XXXX34XXX\n\
+        X5XXX1XX6\n\
+        X2X6XXXX3\n\
+        X3XXXXX5X\n\
+        7XXXX8X9X\n\
+        9XX4XXX3X\n\
+        XXXXXXXXX\n\
+        XXXXXXXXX\n";
+
+    #[test]
+    fn german_whispers() {
+        let is_satisfiable = LineConstraint::is_german_whispers_satisfiable;
+
+        assert!(is_satisfiable(&[Some(1), Some(6), None]));
+        assert!(!is_satisfiable(&[Some(1), Some(5), None]));
+        assert!(!is_satisfiable(&[Some(2), Some(6), None]));
+    }
+
+    #[test]
+    fn renban() {
+        let is_satisfiable = LineConstraint::is_renban_satisfiable;
+
+        assert!(is_satisfiable(&[Some(4), None, Some(2)]));
+        assert!(!is_satisfiable(&[Some(4), None, Some(1)]));
+        assert!(!is_satisfiable(&[Some(4), None, Some(4)]));
+    }
+
+    #[test]
+    fn thermometer() {
+        let is_satisfiable = LineConstraint::is_thermometer_satisfiable;
+
+        assert!(is_satisfiable(&[Some(2), None, Some(4), None]));
+        assert!(!is_satisfiable(&[Some(2), None, Some(3), None]));
+        assert!(!is_satisfiable(&[None, Some(1), None]));
+        assert!(!is_satisfiable(&[None, Some(8), None, None]));
+    }
+
+    #[test]
+    fn palindrome() {
+        let is_satisfiable = LineConstraint::is_palindrome_satisfiable;
+
+        assert!(is_satisfiable(&[Some(3), None, Some(7), Some(3)]));
+        assert!(is_satisfiable(&[Some(3), Some(5), None, None, None]));
+        assert!(!is_satisfiable(&[Some(3), None, Some(7), Some(4)]));
+        assert!(!is_satisfiable(&[None, Some(2), Some(6), None]));
+    }
+
+    #[test]
+    fn region_sum() {
+        let table = SudokuTable::from_string(["X".repeat(81)].into_iter()).unwrap();
+        let is_satisfiable = |line: &str, values: &[Option<u8>]| {
+            LineConstraint::from_str(LineKind::RegionSum, line)
+                .unwrap()
+                .is_region_sum_satisfiable(&table, values)
+        };
+
+        // Two cells in box 1, one in box 2.
+        let line = "r1c2 r1c3 r1c4";
+        assert!(is_satisfiable(line, &[Some(1), Some(2), Some(3)]));
+        assert!(is_satisfiable(line, &[Some(1), None, Some(3)]));
+        assert!(!is_satisfiable(line, &[Some(1), Some(2), Some(4)]));
+        assert!(!is_satisfiable(line, &[None, None, Some(2)]));
+        assert!(!is_satisfiable(line, &[Some(8), None, Some(8)]));
+
+        // The line comes back into box 1, and each of its segments there has the sum on its
+        // own: 1 + 2, then 3, and not 1 + 2 + 3 for the whole box.
+        let line = "r1c2 r1c3 r1c4 r2c3";
+        assert!(is_satisfiable(line, &[Some(1), Some(2), Some(3), Some(3)]));
+        assert!(is_satisfiable(line, &[Some(1), Some(2), None, None]));
+        assert!(!is_satisfiable(line, &[Some(1), Some(2), None, Some(4)]));
+        assert!(!is_satisfiable(line, &[Some(1), Some(2), Some(3), Some(6)]));
+    }
+
+    #[test]
+    fn between() {
+        let is_satisfiable = LineConstraint::is_between_satisfiable;
+
+        assert!(is_satisfiable(&[Some(2), Some(5), None]));
+        assert!(!is_satisfiable(&[Some(9), Some(5), Some(3), Some(4)]));
+        assert!(!is_satisfiable(&[None, Some(9), None]));
+        assert!(!is_satisfiable(&[Some(4), None, None, Some(5)]));
+    }
+
+    #[test]
+    #[should_panic(expected = "not adjacent")]
+    fn non_adjacent_line_cells() {
+        LineConstraint::from_str(LineKind::Palindrome, "r1c1 r1c2 r1c4").unwrap();
+    }
+
+    #[test]
+    fn lines_during_search() {
+        // Each rule, with a check of the digits of a solution along its line.
+        type Holds = fn(&[u8]) -> bool;
+        let cases: [(&str, Holds); 6] = [
+            ("whisper: r8c1 r8c2 r8c3 r9c3", |x| {
+                x.windows(2).all(|pair| pair[0].abs_diff(pair[1]) >= 5)
+            }),
+            ("renban: r8c7 r8c8 r8c9", |x| {
+                x.iter().max().unwrap() - x.iter().min().unwrap() == 2
+            }),
+            ("palindrome: r9c1 r9c2 r8c3 r8c4 r8c5", |x| {
+                x[0] == x[4] && x[1] == x[3]
+            }),
+            ("region-sum: r9c2 r9c3 r9c4 r8c3", |x| {
+                x[0] + x[1] == x[2] && x[2] == x[3]
+            }),
+            ("between: r8c7 r8c8 r9c9", |x| {
+                x[0].min(x[2]) < x[1] && x[1] < x[0].max(x[2])
+            }),
+            ("thermo: r9c5 r9c6 r9c7", |x| x[0] < x[1] && x[1] < x[2]),
+        ];
+
+        for (rule, holds) in cases {
+            let document = format!("{}{}\n", PUZZLE, rule);
+            let cells: Vec<(usize, usize)> = rule
+                .split_whitespace()
+                .skip(1)
+                .map(|x| x.parse::<CellLocation>().unwrap())
+                .map(|x| (x.row(), x.col()))
+                .collect();
+            let found = solutions(&document, 10);
+
+            assert_eq!(found.len(), 10, "{}", rule);
+
+            for solution in &found {
+                let values: Vec<u8> = cells.iter().map(|(r, c)| digit(solution, *r, *c)).collect();
+                assert!(holds(&values), "{} broken by {:?}", rule, values);
+            }
+
+            let table = SudokuTable::from_string(document.lines().map(String::from)).unwrap();
+            assert_eq!(
+                table.to_document().unwrap(),
+                format!("size: 9\n{}", document)
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_lines() {
+        for rule in [
+            "whisper: r8c1",
+            "between: r8c1 r8c2",
+            "renban: r8c1 r8c2 r8c1",
+            "palindrome: r8c1 r8c3",
+            "region-sum: r8c1 r10c1",
+        ] {
+            let document = format!("{}{}\n", PUZZLE, rule);
+
+            match SudokuTable::from_string(document.lines().map(String::from)) {
+                Err(SudokuError::InvalidRule { line, .. }) => assert_eq!(line, 10),
+                _ => panic!("expected '{}' to be rejected", rule),
+            }
+        }
+    }
+}
@@ -0,0 +1,310 @@
+use super::constraint::Constraint;
+use super::drawing::{Color, Drawing, Point};
+use super::render::{Canvas, Corner, Side};
+use super::{CellLocation, SudokuCell, SudokuTable};
+
+pub struct LittleKiller {
+    start: CellLocation,
+    direction: (isize, isize),
+    sum: u32,
+}
+
+impl LittleKiller {
+    pub fn from_str(clue_str: &str) -> Result<LittleKiller, String> {
+        let tokens: Vec<&str> = clue_str.split_whitespace().collect();
+
+        if tokens.len() != 3 {
+            return Err(format!(
+                "Invalid input: malformed little killer clue '{}'",
+                clue_str
+            ));
+        }
+
+        let start = tokens[0].parse::<CellLocation>()?;
+        let direction = match tokens[1] {
+            "NE" => (-1, 1),
+            "NW" => (-1, -1),
+            "SE" => (1, 1),
+            "SW" => (1, -1),
+            _ => {
+                return Err(format!(
+                    "Invalid input: illegal little killer direction '{}'",
+                    tokens[1]
+                ))
+            }
+        };
+        if Self::step(start, (-direction.0, -direction.1)).is_some() {
+            return Err(format!(
+                "Invalid input: little killer diagonal should start at the edge, found {}",
+                start
+            ));
+        }
+
+        let sum = match tokens[2].parse::<u32>() {
+            Ok(x) if x > 0 => x,
+            _ => {
+                return Err(format!(
+                    "Invalid input: illegal little killer sum '{}'",
+                    tokens[2]
+                ))
+            }
+        };
+
+        Ok(LittleKiller {
+            start,
+            direction,
+            sum,
+        })
+    }
+
+    fn step(cell: CellLocation, (row_step, col_step): (isize, isize)) -> Option<CellLocation> {
+        let row = cell.row.checked_add_signed(row_step).filter(|x| *x < 9)?;
+        let col = cell.col.checked_add_signed(col_step).filter(|x| *x < 9)?;
+
+        Some(CellLocation { row, col })
+    }
+
+    fn diagonal(&self) -> Vec<CellLocation> {
+        let mut result = vec![self.start];
+
+        while let Some(cell) = Self::step(*result.last().unwrap(), self.direction) {
+            result.push(cell);
+        }
+
+        result
+    }
+
+    /// The sum of the filled cells of the diagonal, and the least and greatest sums its empty
+    /// cells, other than `skipped`, can add up to. Empty cells in the same region take different
+    /// digits.
+    fn sum_range(&self, table: &SudokuTable, skipped: Option<CellLocation>) -> (u32, u32, u32) {
+        let mut filled_sum = 0u32;
+        let mut empty_by_region = [0u32; 9];
+
+        for cell in self.diagonal() {
+            match table.contents()[cell.row][cell.col] {
+                SudokuCell::Filled(x) => filled_sum += x as u32,
+                SudokuCell::Empty if Some(cell) == skipped => (),
+                SudokuCell::Empty => empty_by_region[table.region_of(cell)] += 1,
+            }
+        }
+
+        let least = empty_by_region.iter().map(|n| n * (n + 1) / 2).sum();
+        let most = empty_by_region.iter().map(|n| n * (19 - n) / 2).sum();
+
+        (filled_sum, least, most)
+    }
+
+    fn direction_name(&self) -> &'static str {
+        match self.direction {
+            (-1, 1) => "NE",
+            (-1, -1) => "NW",
+            (1, 1) => "SE",
+            _ => "SW",
+        }
+    }
+
+    /// The side and index of the clue, or `None` when the clue sits at a corner.
+    fn clue_position(&self) -> Option<(Side, usize)> {
+        let (row_step, col_step) = self.direction;
+        let row = self
+            .start
+            .row
+            .checked_add_signed(-row_step)
+            .filter(|x| *x < 9);
+        let col = self
+            .start
+            .col
+            .checked_add_signed(-col_step)
+            .filter(|x| *x < 9);
+
+        match (row, col) {
+            (None, Some(col)) if row_step == 1 => Some((Side::Top, col)),
+            (None, Some(col)) => Some((Side::Bottom, col)),
+            (Some(row), None) if col_step == 1 => Some((Side::Left, row)),
+            (Some(row), None) => Some((Side::Right, row)),
+            _ => None,
+        }
+    }
+
+    /// The corner of the clue, when the diagonal starts at a corner cell.
+    fn corner(&self) -> Option<Corner> {
+        if self.clue_position().is_some() {
+            return None;
+        }
+
+        Some(match self.direction {
+            (1, 1) => Corner::TopLeft,
+            (1, -1) => Corner::TopRight,
+            (-1, 1) => Corner::BottomLeft,
+            _ => Corner::BottomRight,
+        })
+    }
+
+    fn arrow(&self) -> char {
+        match self.direction {
+            (-1, 1) => '↗',
+            (-1, -1) => '↖',
+            (1, 1) => '↘',
+            _ => '↙',
+        }
+    }
+}
+
+impl Constraint for LittleKiller {
+    fn cells(&self) -> Vec<CellLocation> {
+        self.diagonal()
+    }
+
+    fn is_satisfiable(&self, table: &SudokuTable) -> bool {
+        let (filled_sum, least, most) = self.sum_range(table, None);
+
+        filled_sum + least <= self.sum && self.sum <= filled_sum + most
+    }
+
+    fn prune(&self, table: &SudokuTable, cell: CellLocation, candidates: &mut Vec<u8>) {
+        if !self.diagonal().contains(&cell) {
+            return;
+        }
+
+        let (filled_sum, least, most) = self.sum_range(table, Some(cell));
+
+        candidates.retain(|x| {
+            let sum = filled_sum + *x as u32;
+            sum + least <= self.sum && self.sum <= sum + most
+        });
+    }
+
+    fn outside_clues(&self) -> Vec<(Side, usize)> {
+        self.clue_position().into_iter().collect()
+    }
+
+    fn corner_clues(&self) -> Vec<Corner> {
+        self.corner().into_iter().collect()
+    }
+
+    fn render(&self, canvas: &mut Canvas) {
+        let label = format!("{}{}", self.sum, self.arrow());
+
+        match (self.clue_position(), self.corner()) {
+            (Some((side, index)), _) => canvas.set_outside_clue(side, index, label),
+            (None, Some(corner)) => canvas.set_corner_clue(corner, label),
+            (None, None) => (),
+        }
+    }
+
+    fn draw(&self, drawing: &mut Drawing) {
+        // The clue sits in the cell before the start of the diagonal, outside the grid.
+        let (row_step, col_step) = (self.direction.0 as f64, self.direction.1 as f64);
+        let start = Point::cell_center(self.start);
+        let clue = Point::new(start.x - col_step, start.y - row_step);
+        let arrow_start = Point::new(clue.x + 0.15 * col_step, clue.y + 0.15 * row_step);
+        let arrow_end = Point::new(clue.x + 0.4 * col_step, clue.y + 0.4 * row_step);
+        let label = Point::new(clue.x - 0.1 * col_step, clue.y - 0.1 * row_step);
+
+        drawing.outside_text(label, self.sum.to_string(), Drawing::CLUE_SIZE * 0.8);
+        drawing.arrow(
+            vec![arrow_start, arrow_end],
+            Drawing::THIN_LINE * 1.5,
+            Color::BLACK,
+        );
+    }
+
+    fn to_line(&self) -> Option<String> {
+        Some(format!(
+            "little-killer: {} {} {}",
+            self.start,
+            self.direction_name(),
+            self.sum
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::constraint::Constraint;
+    use super::super::solver::tests::{digit, solutions};
+    use super::super::{CellLocation, SudokuTable};
+    use super::LittleKiller;
+    use crate::error::SudokuError;
+
+    const PUZZLE: &str = "XX1XXXXX2\n\
+        XXXX34XXX\n\
+        X5XXX1XX6\n\
+        X2X6XXXX3\n\
+        X3XXXXX5X\n\
+        7XXXX8X9X\n\
+        9XX4XXX3X\n\
+        XXXXXXXXX\n\
+        XXXXXXXXX\n";
+
+    #[test]
+    fn diagonal_cells() {
+        let little_killer = LittleKiller::from_str("r1c7 SE 10").unwrap();
+
+        assert_eq!(
+            little_killer.diagonal(),
+            vec![
+                CellLocation { row: 0, col: 6 },
+                CellLocation { row: 1, col: 7 },
+                CellLocation { row: 2, col: 8 },
+            ]
+        );
+    }
+
+    #[test]
+    fn pruned_candidates() {
+        let table = SudokuTable::from_string(["X".repeat(81)].into_iter()).unwrap();
+        let little_killer = LittleKiller::from_str("r1c7 SE 6").unwrap();
+        let mut candidates: Vec<u8> = (1..=9).collect();
+
+        // The three cells share a box, so they hold 1, 2 and 3.
+        little_killer.prune(&table, CellLocation::new(1, 7), &mut candidates);
+        assert_eq!(candidates, [1, 2, 3]);
+
+        let mut candidates: Vec<u8> = (1..=9).collect();
+        little_killer.prune(&table, CellLocation::new(1, 6), &mut candidates);
+        assert_eq!(candidates.len(), 9);
+    }
+
+    #[test]
+    fn little_killers_during_search() {
+        let rules = "little-killer: r1c7 SE 10\nlittle-killer: r9c1 NE 45\n";
+        let document = format!("{}{}", PUZZLE, rules);
+        let found = solutions(&document, 20);
+
+        assert_eq!(found.len(), 20);
+
+        for solution in &found {
+            assert_eq!(digit(solution, 0, 6) + digit(solution, 1, 7) + 6, 10);
+            assert_eq!((0..9).map(|i| digit(solution, 8 - i, i)).sum::<u8>(), 45);
+        }
+
+        let table = SudokuTable::from_string(document.lines().map(String::from)).unwrap();
+        assert_eq!(
+            table.to_document().unwrap(),
+            format!("size: 9\n{}", document)
+        );
+
+        // With the 6 in r3c9, r1c7 and r2c8 would have to add up to 1.
+        assert!(solutions(&format!("{}little-killer: r1c7 SE 7\n", PUZZLE), 1).is_empty());
+    }
+
+    #[test]
+    fn invalid_little_killers() {
+        for clue in [
+            "r1c7 S 10",
+            "r5c5 SE 10",
+            "r2c2 NE 10",
+            "r1c7 SE 0",
+            "r1c7 SE",
+        ] {
+            let document = format!("{}little-killer: {}\n", PUZZLE, clue);
+
+            match SudokuTable::from_string(document.lines().map(String::from)) {
+                Err(SudokuError::InvalidRule { line, .. }) => assert_eq!(line, 10),
+                _ => panic!("expected '{}' to be rejected", clue),
+            }
+        }
+    }
+}
@@ -0,0 +1,56 @@
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Metadata {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub description: Option<String>,
+    pub difficulty: Option<String>,
+    pub comments: Vec<String>,
+}
+
+impl Metadata {
+    pub fn is_empty(&self) -> bool {
+        self == &Metadata::default()
+    }
+
+    pub fn add_from_line(&mut self, key: &str, value: &str) -> bool {
+        let value = value.to_string();
+
+        match key {
+            "title" => self.title = Some(value),
+            "author" => self.author = Some(value),
+            "description" => self.description = Some(value),
+            "difficulty" => self.difficulty = Some(value),
+            "comment" => self.comments.push(value),
+            _ => return false,
+        }
+
+        true
+    }
+
+    pub fn to_lines(&self) -> Vec<String> {
+        let fields = [
+            ("title", &self.title),
+            ("author", &self.author),
+            ("description", &self.description),
+            ("difficulty", &self.difficulty),
+        ];
+
+        fields
+            .into_iter()
+            .filter_map(|(key, value)| {
+                value
+                    .as_ref()
+                    .map(|x| format!("{}: {}", key, single_line(x)))
+            })
+            .chain(
+                self.comments
+                    .iter()
+                    .map(|x| format!("comment: {}", single_line(x))),
+            )
+            .collect()
+    }
+}
+
+fn single_line(value: &str) -> String {
+    value.lines().collect::<Vec<&str>>().join(" ")
+}
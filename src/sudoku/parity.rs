@@ -0,0 +1,199 @@
+use super::constraint::Constraint;
+use super::drawing::{Color, Drawing, Point, Shape};
+use super::render::Canvas;
+use super::{CellLocation, SudokuCell, SudokuTable};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Parity {
+    Odd,
+    Even,
+}
+
+pub struct ParityMask {
+    mask: [[Option<Parity>; 9]; 9],
+}
+
+impl ParityMask {
+    pub fn from_str(mask_str: &str) -> Result<ParityMask, String> {
+        let rows: Vec<&str> = mask_str.split_whitespace().collect();
+
+        if rows.len() != 9 {
+            return Err(format!(
+                "Invalid input: expected 9 parity mask rows, found {}",
+                rows.len()
+            ));
+        }
+
+        let mut mask = [[None; 9]; 9];
+
+        for (i, row) in rows.into_iter().enumerate() {
+            if row.chars().count() != 9 {
+                return Err(String::from(
+                    "Invalid input: parity mask row should have exactly 9 characters",
+                ));
+            }
+
+            for (j, char) in row.chars().enumerate() {
+                mask[i][j] = match char {
+                    'O' => Some(Parity::Odd),
+                    'E' => Some(Parity::Even),
+                    'X' => None,
+                    _ => {
+                        return Err(format!(
+                            "Invalid input: illegal parity mask character '{}'",
+                            char
+                        ))
+                    }
+                };
+            }
+        }
+
+        Ok(ParityMask { mask })
+    }
+}
+
+impl Constraint for ParityMask {
+    fn cells(&self) -> Vec<CellLocation> {
+        let mut result = vec![];
+
+        for (i, row) in self.mask.iter().enumerate() {
+            for (j, parity) in row.iter().enumerate() {
+                if parity.is_some() {
+                    result.push(CellLocation { row: i, col: j });
+                }
+            }
+        }
+
+        result
+    }
+
+    fn is_satisfiable(&self, table: &SudokuTable) -> bool {
+        for (i, row) in self.mask.iter().enumerate() {
+            for (j, parity) in row.iter().enumerate() {
+                if let (Some(parity), SudokuCell::Filled(x)) = (parity, table.contents()[i][j]) {
+                    let is_odd = x % 2 == 1;
+                    if is_odd != (*parity == Parity::Odd) {
+                        return false;
+                    }
+                }
+            }
+        }
+
+        true
+    }
+
+    fn prune(&self, _table: &SudokuTable, cell: CellLocation, candidates: &mut Vec<u8>) {
+        match self.mask[cell.row][cell.col] {
+            Some(Parity::Odd) => candidates.retain(|x| x % 2 == 1),
+            Some(Parity::Even) => candidates.retain(|x| x % 2 == 0),
+            None => (),
+        }
+    }
+
+    fn render(&self, canvas: &mut Canvas) {
+        for (i, row) in self.mask.iter().enumerate() {
+            for (j, parity) in row.iter().enumerate() {
+                let (opening, closing) = match parity {
+                    Some(Parity::Odd) => ('(', ')'),
+                    Some(Parity::Even) => ('[', ']'),
+                    None => continue,
+                };
+
+                let (line, col) = Canvas::cell_position(CellLocation { row: i, col: j });
+                canvas.set_char((line, col - 1), opening);
+                canvas.set_char((line, col + 1), closing);
+            }
+        }
+    }
+
+    fn draw(&self, drawing: &mut Drawing) {
+        for (i, row) in self.mask.iter().enumerate() {
+            for (j, parity) in row.iter().enumerate() {
+                let center = Point::cell_center(CellLocation { row: i, col: j });
+
+                match parity {
+                    Some(Parity::Odd) => drawing.push_under(Shape::Circle {
+                        center,
+                        radius: 0.38,
+                        fill: Some(Color::LIGHT_GRAY),
+                        stroke: None,
+                    }),
+                    Some(Parity::Even) => drawing.push_under(Shape::Rect {
+                        corner: Point::new(center.x - 0.36, center.y - 0.36),
+                        width: 0.72,
+                        height: 0.72,
+                        fill: Color::LIGHT_GRAY,
+                    }),
+                    None => (),
+                }
+            }
+        }
+    }
+
+    fn to_line(&self) -> Option<String> {
+        let rows: Vec<String> = self
+            .mask
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|parity| match parity {
+                        Some(Parity::Odd) => 'O',
+                        Some(Parity::Even) => 'E',
+                        None => 'X',
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Some(format!("parity: {}", rows.join(" ")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{CellLocation, SudokuTable};
+    use crate::error::SudokuError;
+
+    const SOLVED_TABLE: &str = "391867542\n\
+        286534719\n\
+        457291386\n\
+        129645873\n\
+        638179254\n\
+        745328691\n\
+        972486135\n\
+        564713928\n\
+        813952467\n";
+
+    #[test]
+    fn display_parity_mask() {
+        let input_table = format!(
+            "{}parity: OOXXXXXXX XXXXXXXXX XXXXXXXXX XXXXXXXXX XXXXXXXXX \
+            XXXXXXXXX XXXXXXXXX XXXXXXXXX XXXXXXXXX",
+            SOLVED_TABLE
+        );
+
+        let sudoku_table = SudokuTable::from_string(input_table.lines().map(String::from)).unwrap();
+        let display = format!("{}", sudoku_table);
+
+        assert_eq!(
+            display.lines().nth(1).unwrap(),
+            "│(3)│(9)│ 1 │ │ 8 │ 6 │ 7 │ │ 5 │ 4 │ 2 │"
+        );
+    }
+
+    #[test]
+    fn violated_parity_mask() {
+        let input_table = format!(
+            "{}parity: XEXXXXXXX XXXXXXXXX XXXXXXXXX XXXXXXXXX XXXXXXXXX \
+            XXXXXXXXX XXXXXXXXX XXXXXXXXX XXXXXXXXX",
+            SOLVED_TABLE
+        );
+
+        match SudokuTable::from_string(input_table.lines().map(String::from)) {
+            Err(SudokuError::RuleConflict { cells, .. }) => {
+                assert_eq!(cells, vec![CellLocation::new(0, 1)])
+            }
+            _ => panic!("expected a rule conflict"),
+        }
+    }
+}
@@ -0,0 +1,410 @@
+use std::fmt::Display;
+
+use super::conflict::{self, ConflictSource};
+use super::consistency;
+use super::solver::SudokuSolver;
+use super::{CellLocation, ParseMode, SudokuCell, SudokuTable};
+use crate::error::SudokuError;
+
+/// Characters of the lines drawn between rows of boxes, and around the grid.
+const BORDER_CHARS: [char; 9] = ['.', '-', '\'', ':', '+', '*', '|', '=', ' '];
+
+/// A grid with the candidates left in each cell, as a player has narrowed them down.
+#[derive(Clone)]
+pub struct PencilMarks {
+    table: SudokuTable,
+    /// A bit per digit, with the lowest bit for 1. A placed value is its only candidate.
+    candidates: [[u16; 9]; 9],
+}
+
+/// The next digit that can be placed, found from the candidates alone.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Hint {
+    pub cell: CellLocation,
+    pub digit: u8,
+    /// The house where the cell is the only place for the digit, or `None` when the digit is the
+    /// only candidate left in the cell.
+    pub house: Option<ConflictSource>,
+}
+
+impl PencilMarks {
+    pub const ALL_DIGITS: u16 = 0x1ff;
+
+    /// The candidates of every cell, as left by the values already placed.
+    pub fn from_table(table: &SudokuTable) -> PencilMarks {
+        let mut solver = SudokuSolver::new(table);
+        let mut candidates = [[0; 9]; 9];
+
+        for (row, cols) in candidates.iter_mut().enumerate() {
+            for (col, mask) in cols.iter_mut().enumerate() {
+                *mask = match table.contents()[row][col] {
+                    SudokuCell::Filled(value) => 1 << (value - 1),
+                    SudokuCell::Empty => solver
+                        .candidates(CellLocation::new(row, col))
+                        .iter()
+                        .fold(0, |mask, x| mask | 1 << (x - 1)),
+                };
+            }
+        }
+
+        PencilMarks {
+            table: table.clone(),
+            candidates,
+        }
+    }
+
+    /// Reads the layout of Sudoku Explainer and HoDoKu: the candidates of each cell written
+    /// together, cells separated by spaces and boxes by `|`, with border lines in between. A cell
+    /// with a single candidate holds a placed value.
+    pub fn parse(text: &str) -> Result<PencilMarks, SudokuError> {
+        let tokens: Vec<&str> = text
+            .lines()
+            .filter(|x| {
+                !x.chars()
+                    .all(|x| BORDER_CHARS.contains(&x) || x == '\u{feff}')
+            })
+            .flat_map(|x| x.split(|x: char| x == '|' || x.is_whitespace()))
+            .filter(|x| !x.is_empty())
+            .collect();
+
+        if tokens.len() != 81 {
+            return Err(SudokuError::Import(format!(
+                "A pencil-mark grid has 81 cells, found {}",
+                tokens.len()
+            )));
+        }
+
+        let mut candidates = [[0u16; 9]; 9];
+
+        for (i, token) in tokens.iter().enumerate() {
+            let cell = CellLocation::new(i / 9, i % 9);
+            let mask = &mut candidates[cell.row][cell.col];
+
+            for c in token.chars() {
+                let bit = match c.to_digit(10) {
+                    Some(digit @ 1..=9) => 1 << (digit - 1),
+                    _ => {
+                        return Err(SudokuError::Import(format!(
+                            "Invalid candidates '{}' in {}",
+                            token, cell
+                        )))
+                    }
+                };
+
+                *mask |= bit;
+            }
+        }
+
+        let rows = candidates.iter().map(|cols| {
+            cols.iter()
+                .map(|x| match x.count_ones() {
+                    1 => (b'1' + x.trailing_zeros() as u8) as char,
+                    _ => 'X',
+                })
+                .collect()
+        });
+        let table = SudokuTable::parse_unchecked(rows, ParseMode::Strict)?;
+
+        Ok(PencilMarks { table, candidates })
+    }
+
+    /// Whether the lines look like a pencil-mark grid: 81 cells of digits, some of them holding
+    /// more than one candidate.
+    pub fn is_pencil_marks(lines: &[String]) -> bool {
+        let tokens: Vec<&str> = lines
+            .iter()
+            .flat_map(|x| x.split(|x: char| x == '|' || x.is_whitespace()))
+            .filter(|x| {
+                !x.chars()
+                    .all(|x| BORDER_CHARS.contains(&x) || x == '\u{feff}')
+            })
+            .collect();
+
+        tokens.len() == 81
+            && tokens.iter().all(|x| x.chars().all(|x| x.is_ascii_digit()))
+            && tokens.iter().any(|x| x.len() > 1)
+    }
+
+    /// The placed values.
+    pub fn table(&self) -> &SudokuTable {
+        &self.table
+    }
+
+    pub fn candidates(&self, cell: CellLocation) -> Vec<u8> {
+        (1..=9)
+            .filter(|x| self.candidates[cell.row][cell.col] & 1 << (x - 1) != 0)
+            .collect()
+    }
+
+    pub fn candidate_mask(&self, cell: CellLocation) -> u16 {
+        self.candidates[cell.row][cell.col]
+    }
+
+    /// Replaces the candidates of an empty cell, as a player does when erasing marks. Digits
+    /// outside 1 to 9 are ignored.
+    pub fn set_candidates(&mut self, cell: CellLocation, digits: &[u8]) {
+        if self.table.contents()[cell.row][cell.col] == SudokuCell::Empty {
+            self.candidates[cell.row][cell.col] = digits
+                .iter()
+                .filter(|x| (1..=9).contains(*x))
+                .fold(0, |mask, x| mask | 1 << (x - 1));
+        }
+    }
+
+    /// Checks that every empty cell has a candidate left, and every digit a cell in each house
+    /// where it is not placed yet, counting only the candidates the player kept.
+    pub fn check_consistency(&self) -> Result<(), SudokuError> {
+        let contradictions = consistency::find_contradictions_with(
+            &self.table,
+            SudokuSolver::from_pencil_marks(self),
+        );
+
+        match contradictions.is_empty() {
+            true => Ok(()),
+            false => Err(SudokuError::Contradiction { contradictions }),
+        }
+    }
+
+    /// Looks for a hidden single, then for a naked single. Candidates that a placed value rules
+    /// out are left out first, as players often forget to erase them.
+    pub fn hint(&self) -> Option<Hint> {
+        let houses = conflict::houses(&self.table);
+        let mut candidates = self.candidates;
+
+        for (_, house) in &houses {
+            let placed = house
+                .iter()
+                .filter_map(|x| match self.table.contents()[x.row][x.col] {
+                    SudokuCell::Filled(value) => Some(1 << (value - 1)),
+                    SudokuCell::Empty => None,
+                })
+                .fold(0, |mask, x| mask | x);
+
+            for cell in house {
+                if self.table.contents()[cell.row][cell.col] == SudokuCell::Empty {
+                    candidates[cell.row][cell.col] &= !placed;
+                }
+            }
+        }
+
+        let is_empty = |x: &CellLocation| self.table.contents()[x.row][x.col] == SudokuCell::Empty;
+
+        for (source, house) in &houses {
+            for digit in 1..=9 {
+                let places: Vec<&CellLocation> = house
+                    .iter()
+                    .filter(|x| is_empty(x))
+                    .filter(|x| candidates[x.row][x.col] & 1 << (digit - 1) != 0)
+                    .collect();
+
+                if let [cell] = places[..] {
+                    return Some(Hint {
+                        cell: *cell,
+                        digit,
+                        house: Some(source.clone()),
+                    });
+                }
+            }
+        }
+
+        for (row, cols) in candidates.iter().enumerate() {
+            for (col, mask) in cols.iter().enumerate() {
+                let cell = CellLocation::new(row, col);
+
+                if is_empty(&cell) && mask.count_ones() == 1 {
+                    return Some(Hint {
+                        cell,
+                        digit: mask.trailing_zeros() as u8 + 1,
+                        house: None,
+                    });
+                }
+            }
+        }
+
+        None
+    }
+}
+
+/// Writes the layout read by [`PencilMarks::parse`], with each column as wide as its widest cell.
+impl Display for PencilMarks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let cells: Vec<Vec<String>> = (0..9)
+            .map(|row| {
+                (0..9)
+                    .map(|col| {
+                        self.candidates(CellLocation::new(row, col))
+                            .iter()
+                            .map(|x| x.to_string())
+                            .collect()
+                    })
+                    .collect()
+            })
+            .collect();
+        let widths: Vec<usize> = (0..9)
+            .map(|col| cells.iter().map(|x| x[col].len()).max().unwrap_or(0))
+            .collect();
+        let border = |corner: char, middle: char| {
+            let boxes: Vec<String> = widths
+                .chunks(3)
+                .map(|x| "-".repeat(x.iter().sum::<usize>() + 6))
+                .collect();
+            format!("{}{}{}", corner, boxes.join(&middle.to_string()), corner)
+        };
+
+        for (row, cols) in cells.iter().enumerate() {
+            match row {
+                0 => writeln!(f, "{}", border('.', '.'))?,
+                3 | 6 => writeln!(f, "{}", border(':', '+'))?,
+                _ => {}
+            }
+
+            write!(f, "|")?;
+
+            for (col, cell) in cols.iter().enumerate() {
+                write!(f, " {:width$} ", cell, width = widths[col])?;
+
+                if col % 3 == 2 {
+                    write!(f, "|")?;
+                }
+            }
+
+            writeln!(f)?;
+        }
+
+        writeln!(f, "{}", border('\'', '\''))
+    }
+}
+
+impl Display for Hint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} must be {}: ", self.cell, self.digit)?;
+
+        match &self.house {
+            Some(house) => write!(f, "it is the only place for {} in {}", self.digit, house),
+            None => write!(f, "it is the only candidate left in the cell"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::solver::SudokuSolver;
+    use super::super::{CellLocation, ParseMode, SudokuTable};
+    use super::PencilMarks;
+
+    const PUZZLE: &str = "XX1XXXXX2\n\
+    XXXX34XXX\n\
+    X5XXX1XX6\n\
+    X2X6XXXX3\n\
+    X3XXXXX5X\n\
+    7XXXX8X9X\n\
+    9XX4XXX3X\n\
+    XXX71XXXX\n\
+    8XXXXX4XX\n";
+
+    fn marks() -> PencilMarks {
+        let table =
+            SudokuTable::parse_unchecked(PUZZLE.lines().map(String::from), ParseMode::Strict)
+                .unwrap();
+
+        PencilMarks::from_table(&table)
+    }
+
+    #[test]
+    fn round_trip() {
+        let marks = marks();
+        let text = marks.to_string();
+        let lines: Vec<String> = text.lines().map(String::from).collect();
+
+        assert!(PencilMarks::is_pencil_marks(&lines));
+        assert!(!PencilMarks::is_pencil_marks(
+            &PUZZLE.lines().map(String::from).collect::<Vec<_>>()
+        ));
+        assert_eq!(PencilMarks::parse(&text).unwrap().to_string(), text);
+        assert_eq!(
+            marks.table().to_single_line(),
+            PUZZLE.replace('\n', "").replace('X', ".")
+        );
+        assert!(lines[0].starts_with(".---"));
+        assert!(lines[4].starts_with(":---"));
+        assert_eq!(lines.len(), 13);
+        assert_eq!(marks.candidates(CellLocation::new(0, 2)), [1]);
+        assert!(PencilMarks::parse("| 1 2 3 |").is_err());
+
+        let mut marks = marks;
+        let cell = CellLocation::new(0, 0);
+        marks.set_candidates(cell, &[0, 3, 17, 255]);
+        assert_eq!(marks.candidates(cell), [3]);
+    }
+
+    #[test]
+    fn solving_starts_from_the_candidates() {
+        let marks = marks();
+        let solution = SudokuSolver::from_pencil_marks(&marks).next().unwrap();
+        assert_eq!(&solution.to_single_line()[..9], "391867542");
+
+        // Taking the answer out of r1c1 leaves the grid without a solution.
+        let text = marks.to_string().replacen("| 346", "| 46 ", 1);
+        let marks = PencilMarks::parse(&text).unwrap();
+        assert_eq!(marks.candidates(CellLocation::new(0, 0)), [4, 6]);
+        assert!(SudokuSolver::from_pencil_marks(&marks).next().is_none());
+    }
+
+    #[test]
+    fn hints() {
+        let marks = marks();
+        let hint = marks.hint().unwrap();
+        let solution = SudokuSolver::new(marks.table()).next().unwrap();
+
+        assert_eq!(
+            solution.contents()[hint.cell.row][hint.cell.col],
+            super::SudokuCell::Filled(hint.digit)
+        );
+        assert!(hint.house.is_some());
+
+        // The 5 of r9c8 is ruled out by the 5 placed in r3c8, although it was not erased.
+        let text = "\
+            .-----------.-----------.-----------.\n\
+            | 1  2  3   | 4  5  6   | 7  8  9   |\n\
+            | 4  5  6   | 7  8  9   | 1  2  3   |\n\
+            | 7  8  9   | 1  2  3   | 4  5  6   |\n\
+            :-----------+-----------+-----------:\n\
+            | 2  3  1   | 5  6  4   | 8  9  7   |\n\
+            | 5  6  4   | 8  9  7   | 2  3  1   |\n\
+            | 8  9  7   | 2  3  1   | 5  6  4   |\n\
+            :-----------+-----------+-----------:\n\
+            | 3  1  2   | 6  4  5   | 9  7  8   |\n\
+            | 6  4  5   | 9  7  8   | 3  1  2   |\n\
+            | 9  7  8   | 3  1  2   | 6  45 56  |\n\
+            '-----------'-----------'-----------'\n";
+        let hint = PencilMarks::parse(text).unwrap().hint().unwrap();
+
+        assert_eq!(
+            hint.to_string(),
+            "r9c8 must be 4: it is the only place for 4 in row 9"
+        );
+    }
+
+    #[test]
+    fn contradictions_among_candidates() {
+        let mut marks = marks();
+        assert!(marks.check_consistency().is_ok());
+
+        // The givens leave 9 a place in row 1, but the player erased it everywhere.
+        for col in 0..9 {
+            let cell = CellLocation::new(0, col);
+            let digits: Vec<u8> = marks
+                .candidates(cell)
+                .into_iter()
+                .filter(|x| *x != 9)
+                .collect();
+            marks.set_candidates(cell, &digits);
+        }
+        marks.set_candidates(CellLocation::new(8, 8), &[]);
+
+        let error = marks.check_consistency().err().unwrap().to_string();
+        assert!(marks.table().check_consistency().is_ok());
+        assert!(error.contains("r9c9 has no candidates left"));
+        assert!(error.contains("row 1: digit 9 has no place left"));
+    }
+}
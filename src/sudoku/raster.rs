@@ -0,0 +1,451 @@
+use std::f64::consts::PI;
+
+use super::drawing::{Color, Drawing, Point, Shape};
+
+/// The width of a glyph of the built-in font, and its height, in font sizes.
+pub const GLYPH_WIDTH: f64 = 0.5;
+pub const GLYPH_HEIGHT: f64 = 0.7;
+const GLYPH_ADVANCE: f64 = 0.6;
+const REGULAR_STROKE: f64 = 0.09;
+const BOLD_STROKE: f64 = 0.14;
+
+#[derive(Clone, Copy)]
+pub struct RasterOptions {
+    /// The side of a cell, in pixels.
+    pub cell_size: f64,
+    /// The size of the digits in the cells, in pixels. Other text is scaled along with it.
+    /// Defaults to the size the layout gives them.
+    pub font_size: Option<f64>,
+}
+
+impl Default for RasterOptions {
+    fn default() -> RasterOptions {
+        RasterOptions {
+            cell_size: 40.0,
+            font_size: None,
+        }
+    }
+}
+
+/// An RGB image, row by row.
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl Image {
+    pub fn new(width: u32, height: u32, background: Color) -> Image {
+        let Color(r, g, b) = background;
+
+        Image {
+            width,
+            height,
+            pixels: [r, g, b].repeat(width as usize * height as usize),
+        }
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> Color {
+        let i = (y as usize * self.width as usize + x as usize) * 3;
+        Color(self.pixels[i], self.pixels[i + 1], self.pixels[i + 2])
+    }
+
+    fn blend(&mut self, x: u32, y: u32, color: Color, alpha: f64) {
+        let i = (y as usize * self.width as usize + x as usize) * 3;
+        let Color(r, g, b) = color;
+
+        for (channel, value) in self.pixels[i..i + 3].iter_mut().zip([r, g, b]) {
+            *channel = (*channel as f64 * (1.0 - alpha) + value as f64 * alpha).round() as u8;
+        }
+    }
+
+    /// Draws the text in the built-in font, centered on `center`, in pixels.
+    pub fn text(&mut self, text: &str, center: Point, size: f64, bold: bool, color: Color) {
+        let stroke = match bold {
+            true => BOLD_STROKE,
+            false => REGULAR_STROKE,
+        };
+        let strokes = text_strokes(text, center, size);
+
+        stroke_polylines(self, &strokes, stroke * size / 2.0, color);
+    }
+
+    /// Paints the color over the pixels, by how much of each pixel the shape covers.
+    fn fill(&mut self, color: Color, bounds: Bounds, coverage: impl Fn(f64, f64) -> f64) {
+        let left = bounds.left.floor().max(0.0) as u32;
+        let top = bounds.top.floor().max(0.0) as u32;
+        let right = (bounds.right.ceil().max(0.0) as u32).min(self.width);
+        let bottom = (bounds.bottom.ceil().max(0.0) as u32).min(self.height);
+
+        for y in top..bottom {
+            for x in left..right {
+                let alpha = coverage(x as f64 + 0.5, y as f64 + 0.5).clamp(0.0, 1.0);
+
+                if alpha > 0.0 {
+                    self.blend(x, y, color, alpha);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Bounds {
+    left: f64,
+    top: f64,
+    right: f64,
+    bottom: f64,
+}
+
+impl Bounds {
+    fn around(points: &[Point], padding: f64) -> Bounds {
+        Bounds {
+            left: points.iter().map(|x| x.x).fold(f64::MAX, f64::min) - padding,
+            top: points.iter().map(|x| x.y).fold(f64::MAX, f64::min) - padding,
+            right: points.iter().map(|x| x.x).fold(f64::MIN, f64::max) + padding,
+            bottom: points.iter().map(|x| x.y).fold(f64::MIN, f64::max) + padding,
+        }
+    }
+}
+
+/// A segment in pixels, with the half width of its stroke.
+#[derive(Clone, Copy)]
+struct Segment {
+    from: Point,
+    to: Point,
+    half_width: f64,
+}
+
+impl Segment {
+    /// How much of the pixel centered on `(x, y)` a stroke with square caps covers.
+    fn square_coverage(&self, x: f64, y: f64) -> f64 {
+        let (dx, dy) = (self.to.x - self.from.x, self.to.y - self.from.y);
+        let length = dx.hypot(dy);
+
+        if length == 0.0 {
+            return 0.0;
+        }
+
+        let (px, py) = (x - self.from.x, y - self.from.y);
+        let along = (px * dx + py * dy) / length;
+        let across = (px * dy - py * dx) / length;
+
+        (self.half_width + 0.5 - across.abs()).clamp(0.0, 1.0)
+            * (length / 2.0 + self.half_width + 0.5 - (along - length / 2.0).abs()).clamp(0.0, 1.0)
+    }
+
+    /// How much of the pixel centered on `(x, y)` a stroke with round caps covers.
+    fn round_coverage(&self, x: f64, y: f64) -> f64 {
+        let (dx, dy) = (self.to.x - self.from.x, self.to.y - self.from.y);
+        let length_squared = dx * dx + dy * dy;
+        let t = match length_squared == 0.0 {
+            true => 0.0,
+            false => {
+                (((x - self.from.x) * dx + (y - self.from.y) * dy) / length_squared).clamp(0.0, 1.0)
+            }
+        };
+        let distance = (x - self.from.x - t * dx).hypot(y - self.from.y - t * dy);
+
+        self.half_width + 0.5 - distance
+    }
+}
+
+/// Renders the drawing, with the grid in the middle of the margin the layout asks for.
+pub fn render(drawing: &Drawing, options: &RasterOptions) -> Image {
+    let cell_size = options.cell_size;
+    let margin = drawing.margin();
+    let size = ((9.0 + 2.0 * margin) * cell_size).ceil() as u32;
+    let font_scale = options
+        .font_size
+        .map_or(1.0, |x| x / (Drawing::DIGIT_SIZE * cell_size));
+    let mut image = Image::new(size, size, Color::WHITE);
+
+    let point = |point: &Point| {
+        Point::new(
+            (point.x + margin) * cell_size,
+            (point.y + margin) * cell_size,
+        )
+    };
+
+    for shape in drawing.shapes() {
+        match shape {
+            Shape::Line {
+                from,
+                to,
+                width,
+                color,
+                dashed,
+            } => {
+                let (from, to) = (point(from), point(to));
+                let half_width = width * cell_size / 2.0;
+                let segments = match dashed {
+                    true => dashes(from, to, width * cell_size * 3.0),
+                    false => vec![(from, to)],
+                };
+
+                for (from, to) in segments {
+                    let segment = Segment {
+                        from,
+                        to,
+                        half_width,
+                    };
+                    let bounds = Bounds::around(&[from, to], half_width + 1.0);
+                    image.fill(*color, bounds, |x, y| segment.square_coverage(x, y));
+                }
+            }
+            Shape::Polyline {
+                points,
+                width,
+                color,
+            } => {
+                let points: Vec<Point> = points.iter().map(point).collect();
+                stroke_polylines(&mut image, &[points], width * cell_size / 2.0, *color);
+            }
+            Shape::Circle {
+                center,
+                radius,
+                fill,
+                stroke,
+            } => {
+                let center = point(center);
+                let radius = radius * cell_size;
+
+                if let Some(fill) = fill {
+                    let bounds = Bounds::around(&[center], radius + 1.0);
+                    image.fill(*fill, bounds, |x, y| {
+                        radius + 0.5 - (x - center.x).hypot(y - center.y)
+                    });
+                }
+
+                if let Some((width, color)) = stroke {
+                    let half_width = width * cell_size / 2.0;
+                    let bounds = Bounds::around(&[center], radius + half_width + 1.0);
+                    image.fill(*color, bounds, |x, y| {
+                        half_width + 0.5 - ((x - center.x).hypot(y - center.y) - radius).abs()
+                    });
+                }
+            }
+            Shape::Rect {
+                corner,
+                width,
+                height,
+                fill,
+            } => {
+                let corner = point(corner);
+                let bounds = Bounds {
+                    left: corner.x,
+                    top: corner.y,
+                    right: corner.x + width * cell_size,
+                    bottom: corner.y + height * cell_size,
+                };
+
+                image.fill(*fill, bounds, |x, y| {
+                    let horizontal = (x + 0.5).min(bounds.right) - (x - 0.5).max(bounds.left);
+                    let vertical = (y + 0.5).min(bounds.bottom) - (y - 0.5).max(bounds.top);
+                    horizontal.clamp(0.0, 1.0) * vertical.clamp(0.0, 1.0)
+                });
+            }
+            Shape::Text {
+                center,
+                text,
+                size,
+                color,
+                bold,
+            } => {
+                let size = size * cell_size * font_scale;
+                image.text(text, point(center), size, *bold, *color);
+            }
+        }
+    }
+
+    image
+}
+
+/// Strokes the polylines with round caps and joins, as a single shape, so that their overlaps
+/// are not painted twice.
+fn stroke_polylines(image: &mut Image, polylines: &[Vec<Point>], half_width: f64, color: Color) {
+    let segments: Vec<Segment> = polylines
+        .iter()
+        .flat_map(|points| {
+            let points = match points.len() {
+                1 => vec![points[0], points[0]],
+                _ => points.clone(),
+            };
+
+            points
+                .windows(2)
+                .map(|x| Segment {
+                    from: x[0],
+                    to: x[1],
+                    half_width,
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect();
+
+    if segments.is_empty() {
+        return;
+    }
+
+    let points: Vec<Point> = segments.iter().flat_map(|x| [x.from, x.to]).collect();
+    let bounds = Bounds::around(&points, half_width + 1.0);
+
+    image.fill(color, bounds, |x, y| {
+        segments
+            .iter()
+            .map(|segment| segment.round_coverage(x, y))
+            .fold(0.0, f64::max)
+    });
+}
+
+fn dashes(from: Point, to: Point, dash: f64) -> Vec<(Point, Point)> {
+    let length = (to.x - from.x).hypot(to.y - from.y);
+    let mut result = vec![];
+    let mut start = 0.0;
+
+    while start < length {
+        let end = (start + dash).min(length);
+        let at = |distance: f64| {
+            Point::new(
+                from.x + (to.x - from.x) * distance / length,
+                from.y + (to.y - from.y) * distance / length,
+            )
+        };
+
+        result.push((at(start), at(end)));
+        start += 2.0 * dash;
+    }
+
+    result
+}
+
+/// The strokes of the text in the built-in font, centered on `center`.
+pub fn text_strokes(text: &str, center: Point, size: f64) -> Vec<Vec<Point>> {
+    let count = text.chars().count() as f64;
+    let width = (count * GLYPH_ADVANCE - (GLYPH_ADVANCE - GLYPH_WIDTH)) * size;
+    let left = center.x - width / 2.0;
+    let top = center.y - GLYPH_HEIGHT * size / 2.0;
+
+    text.chars()
+        .enumerate()
+        .flat_map(|(i, c)| {
+            let origin = Point::new(left + i as f64 * GLYPH_ADVANCE * size, top);
+
+            glyph(c).into_iter().map(move |stroke| {
+                stroke
+                    .into_iter()
+                    .map(|x| Point::new(origin.x + x.x * size, origin.y + x.y * size))
+                    .collect()
+            })
+        })
+        .collect()
+}
+
+/// The strokes of a glyph of the built-in font, in a box of [`GLYPH_WIDTH`] by
+/// [`GLYPH_HEIGHT`] font sizes. Only digits and the minus sign have glyphs.
+pub fn glyph(c: char) -> Vec<Vec<Point>> {
+    let line = |points: &[(f64, f64)]| points.iter().map(|(x, y)| Point::new(*x, *y)).collect();
+
+    match c {
+        '0' => vec![arc((0.25, 0.35), (0.25, 0.35), 0.0, 360.0)],
+        '1' => vec![line(&[(0.1, 0.14), (0.28, 0.0), (0.28, 0.7)])],
+        '2' => vec![
+            arc((0.25, 0.2), (0.23, 0.2), 180.0, 405.0),
+            line(&[(0.413, 0.341), (0.0, 0.7), (0.5, 0.7)]),
+        ],
+        '3' => vec![
+            arc((0.25, 0.18), (0.22, 0.18), 200.0, 450.0),
+            arc((0.25, 0.52), (0.24, 0.18), 270.0, 520.0),
+        ],
+        '4' => vec![line(&[(0.38, 0.7), (0.38, 0.0), (0.0, 0.48), (0.5, 0.48)])],
+        '5' => vec![
+            line(&[(0.45, 0.0), (0.08, 0.0), (0.053, 0.338)]),
+            arc((0.25, 0.47), (0.24, 0.23), 215.0, 520.0),
+        ],
+        '6' => vec![
+            arc((0.25, 0.47), (0.24, 0.23), 0.0, 360.0),
+            arc((0.5, 0.47), (0.48, 0.47), 180.0, 260.0),
+        ],
+        '7' => vec![line(&[(0.0, 0.0), (0.5, 0.0), (0.18, 0.7)])],
+        '8' => vec![
+            arc((0.25, 0.17), (0.2, 0.17), 0.0, 360.0),
+            arc((0.25, 0.52), (0.24, 0.18), 0.0, 360.0),
+        ],
+        '9' => vec![
+            arc((0.25, 0.23), (0.24, 0.23), 0.0, 360.0),
+            arc((0.0, 0.23), (0.48, 0.47), 0.0, 80.0),
+        ],
+        '-' => vec![line(&[(0.08, 0.4), (0.42, 0.4)])],
+        _ => vec![],
+    }
+}
+
+/// An elliptical arc, with angles in degrees, clockwise from the right.
+fn arc(center: (f64, f64), radius: (f64, f64), start: f64, end: f64) -> Vec<Point> {
+    let steps = ((end - start) / 15.0).ceil() as usize;
+
+    (0..=steps)
+        .map(|i| {
+            let angle = (start + (end - start) * i as f64 / steps as f64) * PI / 180.0;
+            Point::new(
+                center.0 + radius.0 * angle.cos(),
+                center.1 + radius.1 * angle.sin(),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::drawing::{Color, DrawOptions, Drawing};
+    use super::super::{ParseMode, SudokuTable};
+    use super::{render, RasterOptions};
+
+    #[test]
+    fn grid_lines_and_digits() {
+        let input_table = "1XXXXXXXX\n\
+        XXXXXXXXX\n\
+        XXXXXXXXX\n\
+        XXXXXXXXX\n\
+        XXXXXXXXX\n\
+        XXXXXXXXX\n\
+        XXXXXXXXX\n\
+        XXXXXXXXX\n\
+        XXXXXXXXX\n";
+
+        let table =
+            SudokuTable::parse_unchecked(input_table.lines().map(String::from), ParseMode::Strict)
+                .unwrap();
+        let drawing = Drawing::new(&table, &DrawOptions::default());
+        let image = render(&drawing, &RasterOptions::default());
+
+        // A margin of a quarter cell on each side of 9 cells of 40 pixels.
+        assert_eq!((image.width, image.height), (380, 380));
+        assert_eq!(image.pixel(5, 5), Color::WHITE);
+        // The outer border, and the anti-aliased thin line between the second and the third column.
+        assert_eq!(image.pixel(10, 200), Color::BLACK);
+        assert_ne!(image.pixel(90, 200), Color::WHITE);
+        assert_eq!(image.pixel(70, 200), Color::WHITE);
+        // The stem of the given 1 in the first cell.
+        assert_eq!(image.pixel(30, 30), Color::BLACK);
+        assert_eq!(image.pixel(60, 60), Color::WHITE);
+    }
+
+    #[test]
+    fn font_size() {
+        let input_table = "1XXXXXXXX\n".to_string() + &"XXXXXXXXX\n".repeat(8);
+        let table =
+            SudokuTable::parse_unchecked(input_table.lines().map(String::from), ParseMode::Strict)
+                .unwrap();
+        let drawing = Drawing::new(&table, &DrawOptions::default());
+        let options = RasterOptions {
+            cell_size: 40.0,
+            font_size: Some(14.0),
+        };
+        let image = render(&drawing, &options);
+
+        // The top of the given 1 moves closer to the center of its cell.
+        assert_eq!(image.pixel(30, 22), Color::WHITE);
+        assert_eq!(image.pixel(30, 28), Color::BLACK);
+    }
+}
@@ -0,0 +1,173 @@
+use std::fmt::Display;
+
+use super::{CellLocation, SudokuTable};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Side {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+pub struct Canvas {
+    grid: Vec<Vec<char>>,
+    top: Vec<String>,
+    bottom: Vec<String>,
+    left: Vec<String>,
+    right: Vec<String>,
+    corners: [String; 4],
+}
+
+impl Canvas {
+    const GRID_WIDTH: usize = 41;
+
+    pub fn new(table: &SudokuTable) -> Result<Canvas, std::fmt::Error> {
+        let mut plain = String::new();
+        table.write_grid(&mut plain)?;
+
+        Ok(Canvas {
+            grid: plain.lines().map(|line| line.chars().collect()).collect(),
+            top: vec![String::new(); 9],
+            bottom: vec![String::new(); 9],
+            left: vec![String::new(); 9],
+            right: vec![String::new(); 9],
+            corners: Default::default(),
+        })
+    }
+
+    pub fn set_outside_clue(&mut self, side: Side, index: usize, label: String) {
+        let labels = match side {
+            Side::Top => &mut self.top,
+            Side::Bottom => &mut self.bottom,
+            Side::Left => &mut self.left,
+            Side::Right => &mut self.right,
+        };
+
+        labels[index] = label;
+    }
+
+    pub fn set_corner_clue(&mut self, corner: Corner, label: String) {
+        self.corners[corner as usize] = label;
+    }
+
+    pub fn set_char(&mut self, (line, col): (usize, usize), c: char) {
+        self.grid[line][col] = c;
+    }
+
+    pub fn mark_cell(&mut self, cell: CellLocation) {
+        let (line, col) = Self::cell_position(cell);
+        self.set_char((line, col - 1), '>');
+        self.set_char((line, col + 1), '<');
+    }
+
+    pub fn cell_position(cell: CellLocation) -> (usize, usize) {
+        (Self::line_of_row(cell.row), Self::col_of_column(cell.col))
+    }
+
+    pub fn position_between(first: CellLocation, second: CellLocation) -> (usize, usize) {
+        let (first_line, first_col) = Self::cell_position(first);
+        let (second_line, second_col) = Self::cell_position(second);
+
+        ((first_line + second_line) / 2, (first_col + second_col) / 2)
+    }
+
+    fn line_of_row(row: usize) -> usize {
+        7 * (row / 3) + 1 + 2 * (row % 3)
+    }
+
+    fn col_of_column(col: usize) -> usize {
+        14 * (col / 3) + 2 + 4 * (col % 3)
+    }
+
+    fn has_labels(labels: &[String]) -> bool {
+        labels.iter().any(|x| !x.is_empty())
+    }
+
+    fn label_width(labels: &[String]) -> usize {
+        labels.iter().map(|x| x.chars().count()).max().unwrap_or(0)
+    }
+
+    fn write_column_labels(
+        f: &mut std::fmt::Formatter<'_>,
+        labels: &[String],
+        (left_corner, right_corner): (&str, &str),
+        left_width: usize,
+    ) -> std::fmt::Result {
+        let margin = Self::margin(left_width);
+        let mut line: Vec<char> = match margin {
+            0 => vec![],
+            _ => format!("{:>width$} ", left_corner, width = left_width)
+                .chars()
+                .collect(),
+        };
+
+        for (i, label) in labels.iter().enumerate() {
+            let label: Vec<char> = label.chars().collect();
+            let start = margin + Self::col_of_column(i) - label.len() / 2;
+
+            if line.len() < start + label.len() {
+                line.resize(start + label.len(), ' ');
+            }
+            line[start..start + label.len()].copy_from_slice(&label);
+        }
+
+        if !right_corner.is_empty() {
+            line.resize(margin + Self::GRID_WIDTH + 1, ' ');
+            line.extend(right_corner.chars());
+        }
+
+        writeln!(f, "{}", line.into_iter().collect::<String>().trim_end())
+    }
+
+    fn margin(left_width: usize) -> usize {
+        match left_width {
+            0 => 0,
+            x => x + 1,
+        }
+    }
+}
+
+impl Display for Canvas {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let [top_left, top_right, bottom_left, bottom_right] =
+            self.corners.each_ref().map(String::as_str);
+        let left_width = Self::label_width(&self.left)
+            .max(top_left.chars().count())
+            .max(bottom_left.chars().count());
+
+        if Self::has_labels(&self.top) || !top_left.is_empty() || !top_right.is_empty() {
+            Self::write_column_labels(f, &self.top, (top_left, top_right), left_width)?;
+        }
+
+        for (i, line) in self.grid.iter().enumerate() {
+            let (left, right) = match (0usize..9).find(|row| Self::line_of_row(*row) == i) {
+                Some(row) => (self.left[row].as_str(), self.right[row].as_str()),
+                None => ("", ""),
+            };
+
+            if left_width > 0 {
+                write!(f, "{:>width$} ", left, width = left_width)?;
+            }
+            write!(f, "{}", line.iter().collect::<String>())?;
+            if !right.is_empty() {
+                write!(f, " {}", right)?;
+            }
+            writeln!(f)?;
+        }
+
+        if Self::has_labels(&self.bottom) || !bottom_left.is_empty() || !bottom_right.is_empty() {
+            Self::write_column_labels(f, &self.bottom, (bottom_left, bottom_right), left_width)?;
+        }
+
+        Ok(())
+    }
+}
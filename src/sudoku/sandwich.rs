@@ -0,0 +1,206 @@
+use super::constraint::{self, Constraint};
+use super::drawing::Drawing;
+use super::render::{Canvas, Side};
+use super::{CellLocation, SudokuCell, SudokuTable};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Direction {
+    Rows,
+    Cols,
+}
+
+pub struct SandwichClues {
+    direction: Direction,
+    clues: [Option<u8>; 9],
+}
+
+impl SandwichClues {
+    const MAX_SUM: u8 = 2 + 3 + 4 + 5 + 6 + 7 + 8;
+
+    pub fn rows_from_str(clues: &str) -> Result<SandwichClues, String> {
+        Self::from_str(Direction::Rows, clues)
+    }
+
+    pub fn cols_from_str(clues: &str) -> Result<SandwichClues, String> {
+        Self::from_str(Direction::Cols, clues)
+    }
+
+    fn from_str(direction: Direction, clues_str: &str) -> Result<SandwichClues, String> {
+        let clues = constraint::outside_clues_from_str(clues_str, Self::MAX_SUM, "sandwich")?;
+
+        Ok(SandwichClues { direction, clues })
+    }
+
+    fn line(&self, table: &SudokuTable, index: usize) -> [SudokuCell; 9] {
+        let mut result = [SudokuCell::Empty; 9];
+
+        for (i, cell) in result.iter_mut().enumerate() {
+            *cell = match self.direction {
+                Direction::Rows => table.contents()[index][i],
+                Direction::Cols => table.contents()[i][index],
+            };
+        }
+
+        result
+    }
+
+    fn is_line_satisfiable(line: &[SudokuCell; 9], sum: u8) -> bool {
+        let mut present = [false; 10];
+        for cell in line {
+            if let SudokuCell::Filled(x) = cell {
+                present[*x as usize] = true;
+            }
+        }
+
+        let available: Vec<u8> = (2u8..=8).filter(|x| !present[*x as usize]).collect();
+
+        for one in Self::possible_positions(line, 1) {
+            for nine in Self::possible_positions(line, 9) {
+                if one == nine {
+                    continue;
+                }
+
+                let between = &line[one.min(nine) + 1..one.max(nine)];
+                let mut filled_sum = 0u8;
+                let mut empty_count = 0usize;
+
+                for cell in between {
+                    match cell {
+                        SudokuCell::Filled(x) => filled_sum += x,
+                        SudokuCell::Empty => empty_count += 1,
+                    }
+                }
+
+                if filled_sum <= sum
+                    && Self::is_sum_reachable(&available, empty_count, sum - filled_sum)
+                {
+                    return true;
+                }
+            }
+        }
+
+        false
+    }
+
+    fn possible_positions(line: &[SudokuCell; 9], digit: u8) -> Vec<usize> {
+        match line.iter().position(|x| *x == SudokuCell::Filled(digit)) {
+            Some(i) => vec![i],
+            None => (0usize..9)
+                .filter(|i| line[*i] == SudokuCell::Empty)
+                .collect(),
+        }
+    }
+
+    fn is_sum_reachable(digits: &[u8], count: usize, sum: u8) -> bool {
+        (0u32..1 << digits.len())
+            .filter(|mask| mask.count_ones() as usize == count)
+            .any(|mask| {
+                let combination_sum: u8 = (0..digits.len())
+                    .filter(|i| mask & (1 << i) != 0)
+                    .map(|i| digits[i])
+                    .sum();
+
+                combination_sum == sum
+            })
+    }
+
+    /// The side of the grid where the clues are written.
+    fn side(&self) -> Side {
+        match self.direction {
+            Direction::Rows => Side::Left,
+            Direction::Cols => Side::Top,
+        }
+    }
+}
+
+impl Constraint for SandwichClues {
+    fn cells(&self) -> Vec<CellLocation> {
+        let mut result = vec![];
+
+        for (index, _) in self.clues.iter().enumerate().filter(|x| x.1.is_some()) {
+            for i in 0usize..9 {
+                result.push(match self.direction {
+                    Direction::Rows => CellLocation { row: index, col: i },
+                    Direction::Cols => CellLocation { row: i, col: index },
+                });
+            }
+        }
+
+        result
+    }
+
+    fn is_satisfiable(&self, table: &SudokuTable) -> bool {
+        self.clues.iter().enumerate().all(|(i, clue)| match clue {
+            Some(sum) => Self::is_line_satisfiable(&self.line(table, i), *sum),
+            None => true,
+        })
+    }
+
+    fn outside_clues(&self) -> Vec<(Side, usize)> {
+        (0..9)
+            .filter(|i| self.clues[*i].is_some())
+            .map(|i| (self.side(), i))
+            .collect()
+    }
+
+    fn render(&self, canvas: &mut Canvas) {
+        for (i, clue) in self.clues.iter().enumerate() {
+            if let Some(sum) = clue {
+                canvas.set_outside_clue(self.side(), i, sum.to_string());
+            }
+        }
+    }
+
+    fn draw(&self, drawing: &mut Drawing) {
+        for (i, clue) in self.clues.iter().enumerate() {
+            if let Some(sum) = clue {
+                drawing.outside_clue(self.side(), i, sum.to_string());
+            }
+        }
+    }
+
+    fn to_line(&self) -> Option<String> {
+        let key = match self.direction {
+            Direction::Rows => "sandwich-rows",
+            Direction::Cols => "sandwich-cols",
+        };
+
+        Some(format!(
+            "{}: {}",
+            key,
+            constraint::outside_clues_to_string(&self.clues)
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::SudokuCell::{Empty, Filled};
+    use super::SandwichClues;
+
+    #[test]
+    fn line_satisfiability() {
+        let line = [
+            Filled(1),
+            Empty,
+            Empty,
+            Filled(9),
+            Empty,
+            Empty,
+            Empty,
+            Empty,
+            Empty,
+        ];
+
+        assert!(SandwichClues::is_line_satisfiable(&line, 5));
+        assert!(SandwichClues::is_line_satisfiable(&line, 15));
+        assert!(!SandwichClues::is_line_satisfiable(&line, 4));
+        assert!(!SandwichClues::is_line_satisfiable(&line, 16));
+    }
+
+    #[test]
+    #[should_panic(expected = "expected 9 sandwich clues")]
+    fn too_few_clues() {
+        SandwichClues::rows_from_str("X 3 X").unwrap();
+    }
+}
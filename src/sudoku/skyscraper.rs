@@ -0,0 +1,273 @@
+use super::constraint::{self, Constraint};
+use super::drawing::Drawing;
+use super::render::{Canvas, Side};
+use super::{CellLocation, SudokuCell, SudokuTable};
+
+pub struct SkyscraperClues {
+    side: Side,
+    clues: [Option<u8>; 9],
+}
+
+impl SkyscraperClues {
+    pub fn from_str(side: Side, clues_str: &str) -> Result<SkyscraperClues, String> {
+        let clues = constraint::outside_clues_from_str(clues_str, 9, "skyscraper")?;
+
+        // At least the tallest building is always seen.
+        if clues.contains(&Some(0)) {
+            return Err(String::from(
+                "Invalid input: illegal skyscraper clue '0', clues are from 1 to 9",
+            ));
+        }
+
+        Ok(SkyscraperClues { side, clues })
+    }
+
+    fn line_seen_from_side(&self, table: &SudokuTable, index: usize) -> [SudokuCell; 9] {
+        let mut result = [SudokuCell::Empty; 9];
+
+        for (i, cell) in result.iter_mut().enumerate() {
+            *cell = match self.side {
+                Side::Top => table.contents()[i][index],
+                Side::Bottom => table.contents()[8 - i][index],
+                Side::Left => table.contents()[index][i],
+                Side::Right => table.contents()[index][8 - i],
+            };
+        }
+
+        result
+    }
+
+    /// How far `cell` is from the clue of line `index`, or `None` when it is not on that line.
+    fn distance_from_side(&self, cell: CellLocation, index: usize) -> Option<usize> {
+        match self.side {
+            Side::Top | Side::Bottom if cell.col != index => None,
+            Side::Left | Side::Right if cell.row != index => None,
+            Side::Top => Some(cell.row),
+            Side::Bottom => Some(8 - cell.row),
+            Side::Left => Some(cell.col),
+            Side::Right => Some(8 - cell.col),
+        }
+    }
+
+    fn is_line_satisfiable(line: &[SudokuCell; 9], clue: u8) -> bool {
+        let mut visible = 0u8;
+        let mut highest = 0u8;
+        let mut seen = 0u8;
+
+        for cell in line {
+            match cell {
+                SudokuCell::Filled(x) => {
+                    if *x > highest {
+                        visible += 1;
+                        highest = *x;
+                    }
+                    seen += 1;
+                }
+                SudokuCell::Empty => break,
+            }
+        }
+
+        if seen == 9 {
+            return visible == clue;
+        }
+
+        let least_visible = match highest {
+            9 => visible,
+            _ => visible + 1,
+        };
+        let most_visible = visible + (9 - seen).min(9 - highest);
+
+        least_visible <= clue && clue <= most_visible
+    }
+}
+
+impl Constraint for SkyscraperClues {
+    fn cells(&self) -> Vec<CellLocation> {
+        let mut result = vec![];
+
+        for (index, _) in self.clues.iter().enumerate().filter(|x| x.1.is_some()) {
+            for i in 0usize..9 {
+                result.push(match self.side {
+                    Side::Top | Side::Bottom => CellLocation { row: i, col: index },
+                    Side::Left | Side::Right => CellLocation { row: index, col: i },
+                });
+            }
+        }
+
+        result
+    }
+
+    fn is_satisfiable(&self, table: &SudokuTable) -> bool {
+        self.clues.iter().enumerate().all(|(i, clue)| match clue {
+            Some(clue) => Self::is_line_satisfiable(&self.line_seen_from_side(table, i), *clue),
+            None => true,
+        })
+    }
+
+    fn prune(&self, _table: &SudokuTable, cell: CellLocation, candidates: &mut Vec<u8>) {
+        for (index, clue) in self.clues.iter().enumerate() {
+            let (Some(clue), Some(distance)) = (clue, self.distance_from_side(cell, index)) else {
+                continue;
+            };
+
+            // With x at this distance, at most the buildings before it, x itself and the 9 - x
+            // taller ones after it are seen. The first building is the 9 only if it hides the
+            // rest.
+            candidates.retain(|x| {
+                *x as usize + *clue as usize <= 10 + distance
+                    && (distance > 0 || (*x == 9) == (*clue == 1))
+            });
+        }
+    }
+
+    fn outside_clues(&self) -> Vec<(Side, usize)> {
+        (0..9)
+            .filter(|i| self.clues[*i].is_some())
+            .map(|i| (self.side, i))
+            .collect()
+    }
+
+    fn render(&self, canvas: &mut Canvas) {
+        for (i, clue) in self.clues.iter().enumerate() {
+            if let Some(clue) = clue {
+                canvas.set_outside_clue(self.side, i, clue.to_string());
+            }
+        }
+    }
+
+    fn draw(&self, drawing: &mut Drawing) {
+        for (i, clue) in self.clues.iter().enumerate() {
+            if let Some(clue) = clue {
+                drawing.outside_clue(self.side, i, clue.to_string());
+            }
+        }
+    }
+
+    fn to_line(&self) -> Option<String> {
+        let key = match self.side {
+            Side::Top => "skyscrapers-top",
+            Side::Bottom => "skyscrapers-bottom",
+            Side::Left => "skyscrapers-left",
+            Side::Right => "skyscrapers-right",
+        };
+
+        Some(format!(
+            "{}: {}",
+            key,
+            constraint::outside_clues_to_string(&self.clues)
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::constraint::Constraint;
+    use super::super::render::Side;
+    use super::super::solver::tests::{digit, solutions};
+    use super::super::SudokuCell::{Empty, Filled};
+    use super::super::{CellLocation, SudokuTable};
+    use super::SkyscraperClues;
+    use crate::error::SudokuError;
+
+    const PUZZLE: &str = "XX1XXXXX2\n\
+        XXXX34XXX\n\
+        X5XXX1XX6\n\
+        X2X6XXXX3\n\
+        X3XXXXX5X\n\
+        7XXXX8X9X\n\
+        9XX4XXX3X\n\
+        XXXXXXXXX\n\
+        XXXXXXXXX\n";
+
+    /// The number of buildings seen along a line of digits.
+    fn visible(digits: impl Iterator<Item = u8>) -> u8 {
+        let mut highest = 0;
+
+        digits
+            .filter(|x| {
+                let is_visible = *x > highest;
+                highest = highest.max(*x);
+                is_visible
+            })
+            .count() as u8
+    }
+
+    #[test]
+    fn line_satisfiability() {
+        let line = [
+            Filled(3),
+            Filled(5),
+            Empty,
+            Empty,
+            Empty,
+            Empty,
+            Empty,
+            Empty,
+            Empty,
+        ];
+
+        assert!(SkyscraperClues::is_line_satisfiable(&line, 3));
+        assert!(SkyscraperClues::is_line_satisfiable(&line, 6));
+        assert!(!SkyscraperClues::is_line_satisfiable(&line, 2));
+        assert!(!SkyscraperClues::is_line_satisfiable(&line, 7));
+    }
+
+    #[test]
+    fn pruned_candidates() {
+        let table = SudokuTable::from_string(["X".repeat(81)].into_iter()).unwrap();
+        let clues = SkyscraperClues::from_str(Side::Right, "1 9 3 X X X X X X").unwrap();
+        let pruned = |row: usize, col: usize| {
+            let mut candidates: Vec<u8> = (1..=9).collect();
+            clues.prune(&table, CellLocation::new(row, col), &mut candidates);
+            candidates
+        };
+
+        assert_eq!(pruned(0, 8), [9]);
+        assert_eq!(pruned(1, 8), [1]);
+        assert_eq!(pruned(1, 6), [1, 2, 3]);
+        assert_eq!(pruned(2, 8), [1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!(pruned(2, 7), [1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(pruned(3, 8).len(), 9);
+    }
+
+    #[test]
+    fn skyscrapers_during_search() {
+        let rules = "skyscrapers-bottom: X X X X 4 X X X X\nskyscrapers-right: X X X X X X X 1 X\n";
+        let document = format!("{}{}", PUZZLE, rules);
+        let found = solutions(&document, 20);
+
+        assert_eq!(found.len(), 20);
+
+        for solution in &found {
+            assert_eq!(visible((0..9).rev().map(|row| digit(solution, row, 4))), 4);
+            assert_eq!(digit(solution, 7, 8), 9);
+        }
+
+        let table = SudokuTable::from_string(document.lines().map(String::from)).unwrap();
+        assert_eq!(
+            table.to_document().unwrap(),
+            format!("size: 9\n{}", document)
+        );
+
+        // A clue of 1 needs a 9 in r6c1, which holds a 7.
+        let hidden = format!("{}skyscrapers-left: X X X X X 1 X X X\n", PUZZLE);
+        assert!(solutions(&hidden, 1).is_empty());
+    }
+
+    #[test]
+    fn invalid_skyscrapers() {
+        for (key, clues) in [
+            ("skyscrapers-top", "X X 0 X X X X X X"),
+            ("skyscrapers-top", "X X 10 X X X X X X"),
+            ("skyscrapers-left", "X X 3 X X"),
+            ("skyscrapers-middle", "X X 3 X X X X X X"),
+        ] {
+            let document = format!("{}{}: {}\n", PUZZLE, key, clues);
+
+            match SudokuTable::from_string(document.lines().map(String::from)) {
+                Err(SudokuError::InvalidRule { line, .. }) => assert_eq!(line, 10),
+                _ => panic!("expected '{}: {}' to be rejected", key, clues),
+            }
+        }
+    }
+}
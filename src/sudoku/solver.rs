@@ -1,19 +1,55 @@
+use std::rc::Rc;
+
+use super::constraint::Constraint;
+use super::pencil_marks::PencilMarks;
 use super::{CellLocation, SudokuCell, SudokuTable};
 
 struct RecursionState {
     attempted_cell: CellLocation,
     possible_values: Vec<u8>,
 }
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SolverStats {
+    pub nodes_explored: u64,
+    /// The dead ends: the values tried that left the next empty cell without a candidate.
+    pub backtracks: u64,
+}
+
 pub struct SudokuSolver {
     table: SudokuTable,
     recursion_stack: Vec<RecursionState>,
+    constraints_by_cell: Vec<Rc<[Rc<dyn Constraint>]>>,
+    /// The digits each cell may take, as a bit per digit.
+    allowed_digits: [u16; 81],
+    is_complete_table_pending: bool,
+    stats: SolverStats,
 }
 
 impl SudokuSolver {
     pub fn new(table: &SudokuTable) -> SudokuSolver {
+        Self::with_allowed_digits(table, [PencilMarks::ALL_DIGITS; 81])
+    }
+
+    /// Solves from the placed values of the grid, trying only the candidates left in each cell.
+    pub fn from_pencil_marks(marks: &PencilMarks) -> SudokuSolver {
+        let mut allowed_digits = [0; 81];
+
+        for (i, allowed) in allowed_digits.iter_mut().enumerate() {
+            *allowed = marks.candidate_mask(CellLocation::new(i / 9, i % 9));
+        }
+
+        Self::with_allowed_digits(marks.table(), allowed_digits)
+    }
+
+    fn with_allowed_digits(table: &SudokuTable, allowed_digits: [u16; 81]) -> SudokuSolver {
         let mut result = SudokuSolver {
             table: table.clone(),
             recursion_stack: Vec::with_capacity(81),
+            constraints_by_cell: Self::constraints_by_cell(table),
+            allowed_digits,
+            is_complete_table_pending: false,
+            stats: SolverStats::default(),
         };
 
         if let Some(cell) = result.next_empty_cell_starting_from(CellLocation { row: 0, col: 0 }) {
@@ -22,11 +58,33 @@ impl SudokuSolver {
                 possible_values: result.possible_values(cell),
             };
             result.recursion_stack.push(initial_state);
+        } else {
+            result.is_complete_table_pending = true;
         }
 
         result
     }
 
+    fn constraints_by_cell(table: &SudokuTable) -> Vec<Rc<[Rc<dyn Constraint>]>> {
+        let mut result: Vec<Vec<Rc<dyn Constraint>>> = vec![vec![]; 81];
+
+        for constraint in table.constraints() {
+            let mut cells = constraint.cells();
+            if cells.is_empty() {
+                cells = (0..81).map(|i| CellLocation::new(i / 9, i % 9)).collect();
+            }
+
+            for cell in cells {
+                let constraints = &mut result[cell.row * 9 + cell.col];
+                if !constraints.iter().any(|x| Rc::ptr_eq(x, constraint)) {
+                    constraints.push(Rc::clone(constraint));
+                }
+            }
+        }
+
+        result.into_iter().map(Rc::from).collect()
+    }
+
     fn next_empty_cell_starting_from(
         &self,
         CellLocation { row: x, col: y }: CellLocation,
@@ -46,19 +104,54 @@ impl SudokuSolver {
         None
     }
 
-    fn possible_values(&self, cell: CellLocation) -> Vec<u8> {
+    pub fn stats(&self) -> SolverStats {
+        self.stats
+    }
+
+    pub fn candidates(&mut self, cell: CellLocation) -> Vec<u8> {
+        self.possible_values(cell)
+    }
+
+    fn possible_values(&mut self, cell: CellLocation) -> Vec<u8> {
         let mut existing_digits = [false; 9];
 
         self.mark_existing_row_values_in_array(cell.row, &mut existing_digits);
         self.mark_existing_col_values_in_array(cell.col, &mut existing_digits);
-        self.mark_existing_values_spanning_3_by_3_cell_in_array(cell, &mut existing_digits);
+        self.mark_existing_values_in_region_in_array(cell, &mut existing_digits);
+        self.mark_existing_values_in_extra_houses_in_array(cell, &mut existing_digits);
 
-        existing_digits
+        let allowed = self.allowed_digits[cell.row * 9 + cell.col];
+        let mut candidates: Vec<u8> = existing_digits
             .iter()
             .enumerate()
-            .filter(|x| !x.1)
+            .filter(|x| !x.1 && allowed & 1 << x.0 != 0)
             .map(|x| x.0 as u8 + 1)
-            .collect()
+            .collect();
+
+        let constraints = Rc::clone(&self.constraints_by_cell[cell.row * 9 + cell.col]);
+        if constraints.is_empty() {
+            return candidates;
+        }
+
+        for constraint in constraints.iter() {
+            constraint.prune(&self.table, cell, &mut candidates);
+        }
+
+        candidates.retain(|x| self.satisfies_constraints(&constraints, cell, *x));
+        candidates
+    }
+
+    fn satisfies_constraints(
+        &mut self,
+        constraints: &[Rc<dyn Constraint>],
+        cell: CellLocation,
+        value: u8,
+    ) -> bool {
+        self.table.contents_mut()[cell.row][cell.col] = SudokuCell::Filled(value);
+        let result = constraints.iter().all(|x| x.is_satisfiable(&self.table));
+        self.table.contents_mut()[cell.row][cell.col] = SudokuCell::Empty;
+
+        result
     }
 
     fn mark_existing_row_values_in_array(&self, row_index: usize, mark_array: &mut [bool; 9]) {
@@ -80,12 +173,12 @@ impl SudokuSolver {
         }
     }
 
-    fn mark_existing_values_spanning_3_by_3_cell_in_array(
+    fn mark_existing_values_in_region_in_array(
         &self,
         cell: CellLocation,
         mark_array: &mut [bool; 9],
     ) {
-        for inside_cell in Self::cells_inside_3_by_3_cell(Self::index_of_3_by_3_cell(cell)) {
+        for inside_cell in &self.table.regions()[self.table.region_of(cell)] {
             if let SudokuCell::Filled(value) =
                 self.table.contents()[inside_cell.row][inside_cell.col]
             {
@@ -95,57 +188,25 @@ impl SudokuSolver {
         }
     }
 
-    fn index_of_3_by_3_cell(cell: CellLocation) -> CellLocation {
-        CellLocation {
-            row: cell.row / 3,
-            col: cell.col / 3,
-        }
-    }
-
-    fn cells_inside_3_by_3_cell(the_3_by_3_cell: CellLocation) -> [CellLocation; 9] {
-        let top_left_cell = CellLocation {
-            row: the_3_by_3_cell.row * 3,
-            col: the_3_by_3_cell.col * 3,
-        };
+    fn mark_existing_values_in_extra_houses_in_array(
+        &self,
+        cell: CellLocation,
+        mark_array: &mut [bool; 9],
+    ) {
+        for house in self.table.extra_houses() {
+            if !house.contains(&cell) {
+                continue;
+            }
 
-        [
-            CellLocation {
-                row: top_left_cell.row,
-                col: top_left_cell.col,
-            },
-            CellLocation {
-                row: top_left_cell.row,
-                col: top_left_cell.col + 1,
-            },
-            CellLocation {
-                row: top_left_cell.row,
-                col: top_left_cell.col + 2,
-            },
-            CellLocation {
-                row: top_left_cell.row + 1,
-                col: top_left_cell.col,
-            },
-            CellLocation {
-                row: top_left_cell.row + 1,
-                col: top_left_cell.col + 1,
-            },
-            CellLocation {
-                row: top_left_cell.row + 1,
-                col: top_left_cell.col + 2,
-            },
-            CellLocation {
-                row: top_left_cell.row + 2,
-                col: top_left_cell.col,
-            },
-            CellLocation {
-                row: top_left_cell.row + 2,
-                col: top_left_cell.col + 1,
-            },
-            CellLocation {
-                row: top_left_cell.row + 2,
-                col: top_left_cell.col + 2,
-            },
-        ]
+            for inside_cell in house {
+                if let SudokuCell::Filled(value) =
+                    self.table.contents()[inside_cell.row][inside_cell.col]
+                {
+                    let value = value as usize - 1;
+                    mark_array[value] = true;
+                }
+            }
+        }
     }
 
     fn try_next_possible_value(
@@ -163,18 +224,18 @@ impl SudokuSolver {
         }
     }
 
-    fn presolve_next_empty_cell(&self, last_state: &RecursionState) -> Result<RecursionState, ()> {
-        let CellLocation { row: x, col: y } = last_state.attempted_cell;
+    fn presolve_next_empty_cell(
+        &mut self,
+        CellLocation { row: x, col: y }: CellLocation,
+    ) -> Result<RecursionState, ()> {
         let empty_cell = self.next_empty_cell_starting_from(CellLocation { row: x, col: y + 1 });
-        if let None = empty_cell {
-            Err(())
-        } else {
-            let empty_cell = empty_cell.unwrap();
-
+        if let Some(empty_cell) = empty_cell {
             Ok(RecursionState {
                 attempted_cell: empty_cell,
                 possible_values: self.possible_values(empty_cell),
             })
+        } else {
+            Err(())
         }
     }
 
@@ -191,10 +252,19 @@ impl Iterator for SudokuSolver {
     type Item = SudokuTable;
 
     fn next(&mut self) -> Option<Self::Item> {
+        if self.is_complete_table_pending {
+            self.is_complete_table_pending = false;
+            return Some(self.table.clone());
+        }
+
         while let Some(last_state) = self.recursion_stack.last_mut() {
-            if let Ok(_) = Self::try_next_possible_value(&mut self.table, last_state) {
-                let last_state = self.recursion_stack.last().unwrap();
-                if let Ok(presolved_state) = self.presolve_next_empty_cell(last_state) {
+            if Self::try_next_possible_value(&mut self.table, last_state).is_ok() {
+                self.stats.nodes_explored += 1;
+                let last_cell = last_state.attempted_cell;
+                if let Ok(presolved_state) = self.presolve_next_empty_cell(last_cell) {
+                    if presolved_state.possible_values.is_empty() {
+                        self.stats.backtracks += 1;
+                    }
                     self.recursion_stack.push(presolved_state);
                 } else {
                     return Some(self.table.clone());
@@ -209,11 +279,54 @@ impl Iterator for SudokuSolver {
 }
 
 #[cfg(test)]
-mod tests {
-    use crate::sudoku::SudokuTable;
+pub(super) mod tests {
+    use crate::sudoku::constraint::Constraint;
+    use crate::sudoku::{CellLocation, ParseMode, SudokuCell, SudokuTable};
 
     use super::SudokuSolver;
 
+    /// The first `count` solutions of a puzzle file, for the tests of the variant rules.
+    pub(in crate::sudoku) fn solutions(input: &str, count: usize) -> Vec<SudokuTable> {
+        let table =
+            SudokuTable::parse_unchecked(input.lines().map(String::from), ParseMode::Strict)
+                .unwrap();
+
+        SudokuSolver::new(&table).take(count).collect()
+    }
+
+    pub(in crate::sudoku) fn digit(table: &SudokuTable, row: usize, col: usize) -> u8 {
+        match table.contents()[row][col] {
+            SudokuCell::Filled(x) => x,
+            SudokuCell::Empty => 0,
+        }
+    }
+
+    /// A rule on the first cell that does not list the cells it depends on.
+    struct FirstCellIs(u8);
+
+    impl Constraint for FirstCellIs {
+        fn cells(&self) -> Vec<CellLocation> {
+            vec![]
+        }
+
+        fn is_satisfiable(&self, table: &SudokuTable) -> bool {
+            match table.contents()[0][0] {
+                SudokuCell::Filled(x) => x == self.0,
+                SudokuCell::Empty => true,
+            }
+        }
+    }
+
+    #[test]
+    fn constraint_without_cells() {
+        let mut table = SudokuTable::from_string(["X".repeat(81)].into_iter()).unwrap();
+        table.add_constraint(FirstCellIs(1));
+
+        let solution = SudokuSolver::new(&table).next().unwrap();
+
+        assert_eq!(solution.contents[0][0], SudokuCell::Filled(1));
+    }
+
     #[test]
     fn single_solution() {
         let input_puzzle = "XX1XXXXX2\n\
@@ -236,8 +349,8 @@ mod tests {
         564713928\n\
         813952467\n";
 
-        let mut table = SudokuTable::from_string(input_puzzle.lines().map(String::from)).unwrap();
-        let mut solver = SudokuSolver::new(&mut table);
+        let table = SudokuTable::from_string(input_puzzle.lines().map(String::from)).unwrap();
+        let mut solver = SudokuSolver::new(&table);
 
         let solution = solver.next().unwrap();
 
@@ -249,5 +362,96 @@ mod tests {
         );
 
         assert!(solver.next().is_none());
+
+        let stats = solver.stats();
+        assert_eq!(stats.nodes_explored, 81632);
+        assert_eq!(stats.backtracks, 24717);
+    }
+
+    #[test]
+    fn sandwich_clues_single_solution() {
+        let input_puzzle = "XX1XXXXX2\n\
+        XXXX34XXX\n\
+        X5XXX1XX6\n\
+        X2X6XXXX3\n\
+        X3XXXXX5X\n\
+        7XXXX8X9X\n\
+        9XX4XXX3X\n\
+        XXXXXXXXX\n\
+        XXXXXXXXX\n\
+        sandwich-rows: 0 0 0 2 7 0 27 3 3\n\
+        sandwich-cols: 13 35 13 14 21 5 0 20 13";
+
+        let solution_string = "391867542\n\
+        286534719\n\
+        457291386\n\
+        129645873\n\
+        638179254\n\
+        745328691\n\
+        972486135\n\
+        564713928\n\
+        813952467\n";
+
+        let table = SudokuTable::from_string(input_puzzle.lines().map(String::from)).unwrap();
+        let mut solver = SudokuSolver::new(&table);
+
+        assert_eq!(
+            solver.next().unwrap().contents,
+            SudokuTable::from_string(solution_string.lines().map(String::from))
+                .unwrap()
+                .contents
+        );
+
+        assert!(solver.next().is_none());
+    }
+
+    #[test]
+    fn windoku_single_solution() {
+        let input_puzzle = "XXXXXXXXX\n\
+        XXXXXXXXX\n\
+        XXXXXXXXX\n\
+        XXXXXXXXX\n\
+        XXXXXXXXX\n\
+        XXXXXXXXX\n\
+        XXXXXXXXX\n\
+        XXXXXXXXX\n\
+        XXXXXXXXX\n\
+        windoku:";
+
+        let table = SudokuTable::from_string(input_puzzle.lines().map(String::from)).unwrap();
+        let solution = SudokuSolver::new(&table).next().unwrap();
+
+        for house in table.extra_houses() {
+            let mut digits: Vec<u8> = house
+                .iter()
+                .filter_map(|x| match solution.contents[x.row][x.col] {
+                    SudokuCell::Filled(value) => Some(value),
+                    SudokuCell::Empty => None,
+                })
+                .collect();
+            digits.sort();
+            digits.dedup();
+
+            assert_eq!(digits.len(), 9);
+        }
+    }
+
+    #[test]
+    fn complete_table_is_its_own_solution() {
+        let input_puzzle = "391867542\n\
+        286534719\n\
+        457291386\n\
+        129645873\n\
+        638179254\n\
+        745328691\n\
+        972486135\n\
+        564713928\n\
+        813952467";
+
+        let table = SudokuTable::from_string(input_puzzle.lines().map(String::from)).unwrap();
+        let solutions: Vec<SudokuTable> = SudokuSolver::new(&table).collect();
+
+        assert_eq!(solutions.len(), 1);
+        assert_eq!(solutions[0].to_single_line(), table.to_single_line());
     }
 }
@@ -0,0 +1,204 @@
+use std::fmt::Write;
+
+use super::drawing::{escape_xml, number, Color, Drawing, Point, Shape};
+
+pub const CELL_SIZE: f64 = 40.0;
+const DECIMALS: usize = 2;
+
+pub fn write(drawing: &Drawing, cell_size: f64) -> String {
+    let margin = drawing.margin() * cell_size;
+    let size = 9.0 * cell_size + 2.0 * margin;
+    let mut svg = String::new();
+
+    // Writing to a String cannot fail.
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{size}" height="{size}" viewBox="{origin} {origin} {size} {size}" font-family="sans-serif">"#,
+        size = number(size, DECIMALS),
+        origin = number(-margin, DECIMALS),
+    );
+    let _ = writeln!(
+        svg,
+        r#"<rect x="{origin}" y="{origin}" width="{size}" height="{size}" fill="white"/>"#,
+        size = number(size, DECIMALS),
+        origin = number(-margin, DECIMALS),
+    );
+
+    for shape in drawing.shapes() {
+        write_shape(&mut svg, shape, cell_size);
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn write_shape(svg: &mut String, shape: &Shape, scale: f64) {
+    let point = |point: &Point| {
+        (
+            number(point.x * scale, DECIMALS),
+            number(point.y * scale, DECIMALS),
+        )
+    };
+
+    let _ = match shape {
+        Shape::Line {
+            from,
+            to,
+            width,
+            color,
+            dashed,
+        } => {
+            let ((x1, y1), (x2, y2)) = (point(from), point(to));
+            let dash = match dashed {
+                true => format!(
+                    r#" stroke-dasharray="{0} {0}""#,
+                    number(width * scale * 3.0, DECIMALS)
+                ),
+                false => String::new(),
+            };
+
+            writeln!(
+                svg,
+                r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="{}" stroke-width="{}" stroke-linecap="square"{}/>"#,
+                x1,
+                y1,
+                x2,
+                y2,
+                color.to_hex(),
+                number(width * scale, DECIMALS),
+                dash
+            )
+        }
+        Shape::Polyline {
+            points,
+            width,
+            color,
+        } => {
+            let points: Vec<String> = points
+                .iter()
+                .map(|x| {
+                    let (x, y) = point(x);
+                    format!("{},{}", x, y)
+                })
+                .collect();
+
+            writeln!(
+                svg,
+                r#"<polyline points="{}" fill="none" stroke="{}" stroke-width="{}" stroke-linecap="round" stroke-linejoin="round"/>"#,
+                points.join(" "),
+                color.to_hex(),
+                number(width * scale, DECIMALS)
+            )
+        }
+        Shape::Circle {
+            center,
+            radius,
+            fill,
+            stroke,
+        } => {
+            let (x, y) = point(center);
+
+            writeln!(
+                svg,
+                r#"<circle cx="{}" cy="{}" r="{}" fill="{}"{}/>"#,
+                x,
+                y,
+                number(radius * scale, DECIMALS),
+                fill.map_or(String::from("none"), Color::to_hex),
+                stroke_attributes(*stroke, scale)
+            )
+        }
+        Shape::Rect {
+            corner,
+            width,
+            height,
+            fill,
+        } => {
+            let (x, y) = point(corner);
+
+            writeln!(
+                svg,
+                r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+                x,
+                y,
+                number(width * scale, DECIMALS),
+                number(height * scale, DECIMALS),
+                fill.to_hex()
+            )
+        }
+        Shape::Text {
+            center,
+            text,
+            size,
+            color,
+            bold,
+        } => {
+            let (x, y) = point(center);
+            let weight = match bold {
+                true => r#" font-weight="bold""#,
+                false => "",
+            };
+
+            writeln!(
+                svg,
+                r#"<text x="{}" y="{}" font-size="{}" fill="{}" text-anchor="middle" dominant-baseline="central"{}>{}</text>"#,
+                x,
+                y,
+                number(size * scale, DECIMALS),
+                color.to_hex(),
+                weight,
+                escape_xml(text)
+            )
+        }
+    };
+}
+
+fn stroke_attributes(stroke: Option<(f64, Color)>, scale: f64) -> String {
+    match stroke {
+        Some((width, color)) => format!(
+            r#" stroke="{}" stroke-width="{}""#,
+            color.to_hex(),
+            number(width * scale, DECIMALS)
+        ),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::drawing::DrawOptions;
+    use super::super::solver::SudokuSolver;
+    use super::super::{ParseMode, SudokuTable};
+
+    #[test]
+    fn givens_and_solved_digits() {
+        let input_table = "XX1XXXXX2\n\
+        XXXX34XXX\n\
+        X5XXX1XX6\n\
+        X2X6XXXX3\n\
+        X3XXXXX5X\n\
+        7XXXX8X9X\n\
+        9XX4XXX3X\n\
+        XXX71XXXX\n\
+        8XXXXX4XX\n\
+        cage: 12 r1c1 r1c2\n\
+        thermo: r2c1 r3c1\n";
+
+        let table =
+            SudokuTable::parse_unchecked(input_table.lines().map(String::from), ParseMode::Strict)
+                .unwrap();
+        let solution = SudokuSolver::new(&table).next().unwrap();
+        let svg = table.to_svg(&DrawOptions {
+            solution: Some(&solution),
+            ..Default::default()
+        });
+
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert!(svg.ends_with("</svg>\n"));
+        assert_eq!(svg.matches("font-weight=\"bold\"").count(), 22);
+        assert_eq!(svg.matches("fill=\"#1e5ac8\"").count(), 59);
+        assert!(svg.contains(">12</text>"));
+        assert!(svg.contains("stroke-dasharray"));
+        assert!(svg.contains("<polyline points=\"20,60 20,100\""));
+    }
+}
@@ -0,0 +1,277 @@
+use std::fmt::Write;
+
+use super::drawing::{number, Color, Drawing, Point, Shape};
+
+/// The side of a cell, in centimeters.
+pub const CELL_SIZE: f64 = 0.6;
+/// The space left between grids drawn side by side, in cells.
+const GRID_GAP: f64 = 1.0;
+const POINTS_PER_CM: f64 = 28.4528;
+const DECIMALS: usize = 3;
+
+/// Writes the drawings as a single `tikzpicture`, side by side from left to right.
+///
+/// The picture needs the `tikz` package, which loads `xcolor`.
+pub fn write(drawings: &[Drawing], cell_size: f64) -> String {
+    let mut tikz = String::new();
+    let mut colors: Vec<Color> = vec![];
+
+    for shape in drawings.iter().flat_map(Drawing::shapes) {
+        for color in shape_colors(shape) {
+            if !colors.contains(&color) {
+                colors.push(color);
+            }
+        }
+    }
+
+    let _ = writeln!(
+        tikz,
+        "\\begin{{tikzpicture}}[x={0}cm, y=-{0}cm]",
+        number(cell_size, DECIMALS)
+    );
+
+    for Color(r, g, b) in colors.iter().copied() {
+        let _ = writeln!(
+            tikz,
+            "\\definecolor{{{}}}{{RGB}}{{{},{},{}}}",
+            color_name(Color(r, g, b)),
+            r,
+            g,
+            b
+        );
+    }
+
+    let mut left = 0.0;
+
+    for drawing in drawings {
+        let margin = drawing.margin();
+        let _ = writeln!(
+            tikz,
+            "\\begin{{scope}}[shift={{({},0)}}]",
+            number(left + margin, DECIMALS)
+        );
+
+        for shape in drawing.shapes() {
+            write_shape(&mut tikz, shape, cell_size);
+        }
+
+        tikz.push_str("\\end{scope}\n");
+        left += 9.0 + 2.0 * margin + GRID_GAP;
+    }
+
+    tikz.push_str("\\end{tikzpicture}\n");
+    tikz
+}
+
+/// Wraps a picture in a `standalone` document, which compiles to a page cropped to the picture.
+pub fn document(picture: &str) -> String {
+    format!(
+        "\\documentclass[border=2mm]{{standalone}}\n\
+         \\usepackage{{tikz}}\n\
+         \\begin{{document}}\n\
+         {}\\end{{document}}\n",
+        picture
+    )
+}
+
+fn write_shape(tikz: &mut String, shape: &Shape, cell_size: f64) {
+    let point = |point: &Point| {
+        format!(
+            "({},{})",
+            number(point.x, DECIMALS),
+            number(point.y, DECIMALS)
+        )
+    };
+    let width = |width: f64| format!("line width={}cm", number(width * cell_size, DECIMALS));
+
+    let _ = match shape {
+        Shape::Line {
+            from,
+            to,
+            width: line_width,
+            color,
+            dashed,
+        } => {
+            let dash = match dashed {
+                true => format!(
+                    ", dash pattern=on {0}cm off {0}cm",
+                    number(line_width * cell_size * 3.0, DECIMALS)
+                ),
+                false => String::new(),
+            };
+
+            writeln!(
+                tikz,
+                "\\draw[{}, {}, line cap=rect{}] {} -- {};",
+                color_name(*color),
+                width(*line_width),
+                dash,
+                point(from),
+                point(to)
+            )
+        }
+        Shape::Polyline {
+            points,
+            width: line_width,
+            color,
+        } => {
+            let points: Vec<String> = points.iter().map(point).collect();
+
+            writeln!(
+                tikz,
+                "\\draw[{}, {}, line cap=round, line join=round] {};",
+                color_name(*color),
+                width(*line_width),
+                points.join(" -- ")
+            )
+        }
+        Shape::Circle {
+            center,
+            radius,
+            fill,
+            stroke,
+        } => {
+            let mut options = vec![];
+
+            if let Some(fill) = fill {
+                options.push(format!("fill={}", color_name(*fill)));
+            }
+            if let Some((line_width, color)) = stroke {
+                options.push(format!("draw={}", color_name(*color)));
+                options.push(width(*line_width));
+            }
+
+            writeln!(
+                tikz,
+                "\\path[{}] {} circle[radius={}];",
+                options.join(", "),
+                point(center),
+                number(*radius, DECIMALS)
+            )
+        }
+        Shape::Rect {
+            corner,
+            width,
+            height,
+            fill,
+        } => writeln!(
+            tikz,
+            "\\fill[{}] {} rectangle {};",
+            color_name(*fill),
+            point(corner),
+            point(&Point::new(corner.x + width, corner.y + height))
+        ),
+        Shape::Text {
+            center,
+            text,
+            size,
+            color,
+            bold,
+        } => {
+            let size = number(size * cell_size * POINTS_PER_CM, DECIMALS);
+            let weight = match bold {
+                true => "\\bfseries",
+                false => "",
+            };
+
+            writeln!(
+                tikz,
+                "\\node[text={color}, inner sep=0, font=\\fontsize{{{size}pt}}{{{size}pt}}\\selectfont\\sffamily{weight}] at {center} {{{text}}};",
+                color = color_name(*color),
+                center = point(center),
+                text = escape_latex(text)
+            )
+        }
+    };
+}
+
+fn shape_colors(shape: &Shape) -> Vec<Color> {
+    match shape {
+        Shape::Line { color, .. } | Shape::Polyline { color, .. } | Shape::Text { color, .. } => {
+            vec![*color]
+        }
+        Shape::Circle { fill, stroke, .. } => fill
+            .iter()
+            .copied()
+            .chain(stroke.iter().map(|(_, color)| *color))
+            .collect(),
+        Shape::Rect { fill, .. } => vec![*fill],
+    }
+}
+
+fn color_name(color: Color) -> String {
+    format!("sudoku{}", &color.to_hex()[1..])
+}
+
+fn escape_latex(text: &str) -> String {
+    let mut result = String::new();
+
+    for c in text.chars() {
+        match c {
+            '\\' => result.push_str("\\textbackslash{}"),
+            '~' => result.push_str("\\textasciitilde{}"),
+            '^' => result.push_str("\\textasciicircum{}"),
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                result.push('\\');
+                result.push(c);
+            }
+            _ => result.push(c),
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::drawing::{DrawOptions, Drawing};
+    use super::super::{CellLocation, ParseMode, SudokuTable};
+    use super::{escape_latex, write, CELL_SIZE};
+
+    #[test]
+    fn grids_side_by_side() {
+        let input_table = "XX1XXXXX2\n\
+        XXXX34XXX\n\
+        X5XXX1XX6\n\
+        X2X6XXXX3\n\
+        X3XXXXX5X\n\
+        7XXXX8X9X\n\
+        9XX4XXX3X\n\
+        XXX71XXXX\n\
+        8XXXXX4XX\n\
+        cage: 12 r1c1 r1c2\n";
+
+        let table =
+            SudokuTable::parse_unchecked(input_table.lines().map(String::from), ParseMode::Strict)
+                .unwrap();
+        let highlighted = ["r5c5".parse::<CellLocation>().unwrap()];
+        let first = Drawing::new(&table, &DrawOptions::default());
+        let second = Drawing::new(
+            &table,
+            &DrawOptions {
+                highlighted: &highlighted,
+                ..Default::default()
+            },
+        );
+        let tikz = write(&[first, second], CELL_SIZE);
+
+        assert!(tikz.starts_with("\\begin{tikzpicture}[x=0.6cm, y=-0.6cm]\n"));
+        assert!(tikz.ends_with("\\end{tikzpicture}\n"));
+        assert!(tikz.contains("\\begin{scope}[shift={(0.25,0)}]"));
+        assert!(tikz.contains("\\begin{scope}[shift={(10.75,0)}]"));
+        assert!(tikz.contains("\\definecolor{sudokuffe68c}{RGB}{255,230,140}"));
+        assert_eq!(
+            tikz.matches("\\fill[sudokuffe68c] (4,4) rectangle (5,5);")
+                .count(),
+            1
+        );
+        assert_eq!(tikz.matches("\\bfseries] at").count(), 44);
+        assert_eq!(tikz.matches("dash pattern").count(), 12);
+    }
+
+    #[test]
+    fn special_characters() {
+        assert_eq!(escape_latex("50% & #1"), "50\\% \\& \\#1");
+        assert_eq!(escape_latex("a\\b"), "a\\textbackslash{}b");
+    }
+}